@@ -0,0 +1,23 @@
+-- Criar tabela de refresh tokens
+-- Tokens são opacos e armazenados apenas como hash SHA-256.
+-- Cada login inicia uma família; cada uso rotaciona o token dentro da família.
+
+CREATE TABLE refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    replaced_by UUID REFERENCES refresh_tokens(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_refresh_tokens_user_id ON refresh_tokens(user_id);
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens(family_id);
+
+-- Comentários para documentação
+COMMENT ON TABLE refresh_tokens IS 'Refresh tokens opacos com rotação e detecção de reuso';
+COMMENT ON COLUMN refresh_tokens.family_id IS 'Família de tokens originada de um mesmo login';
+COMMENT ON COLUMN refresh_tokens.used_at IS 'Momento em que o token foi trocado por um novo (rotação)';
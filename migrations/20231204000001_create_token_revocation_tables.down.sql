@@ -0,0 +1,5 @@
+-- Remover tabelas de revogação de JWT
+
+DROP TABLE IF EXISTS user_session_revocations;
+DROP INDEX IF EXISTS idx_revoked_tokens_expires_at;
+DROP TABLE IF EXISTS revoked_tokens;
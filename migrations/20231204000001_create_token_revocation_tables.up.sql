@@ -0,0 +1,21 @@
+-- Criar tabelas de revogação de JWT
+-- revoked_tokens: denylist de tokens individuais (por jti)
+-- user_session_revocations: tokens emitidos antes de revoked_before são inválidos
+
+CREATE TABLE revoked_tokens (
+    jti VARCHAR(64) PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_revoked_tokens_expires_at ON revoked_tokens(expires_at);
+
+CREATE TABLE user_session_revocations (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    revoked_before TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Comentários para documentação
+COMMENT ON TABLE revoked_tokens IS 'JWTs revogados antes da expiração (logout)';
+COMMENT ON TABLE user_session_revocations IS 'Revogação de todas as sessões de um usuário emitidas antes de revoked_before';
@@ -0,0 +1,8 @@
+-- Remover versão de token dos usuários
+
+CREATE TABLE IF NOT EXISTS user_session_revocations (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    revoked_before TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+ALTER TABLE users DROP COLUMN IF EXISTS token_version;
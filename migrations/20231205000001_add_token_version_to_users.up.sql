@@ -0,0 +1,12 @@
+-- Adicionar versão de token aos usuários
+-- Cada JWT carrega a versão vigente no momento da emissão; incrementar a
+-- versão invalida todos os tokens anteriores (troca de role, troca de senha,
+-- logout de todas as sessões).
+
+ALTER TABLE users
+ADD COLUMN token_version INTEGER DEFAULT 0 NOT NULL;
+
+-- A revogação por timestamp de corte é substituída pela versão de token
+DROP TABLE IF EXISTS user_session_revocations;
+
+COMMENT ON COLUMN users.token_version IS 'Versão dos tokens do usuário; tokens com versão diferente são rejeitados';
@@ -0,0 +1,4 @@
+-- Remover tabela de tokens de redefinição de senha
+
+DROP INDEX IF EXISTS idx_password_reset_tokens_user_id;
+DROP TABLE IF EXISTS password_reset_tokens;
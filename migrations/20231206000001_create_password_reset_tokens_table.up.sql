@@ -0,0 +1,17 @@
+-- Criar tabela de tokens de redefinição de senha
+-- Tokens são opacos, de uso único e armazenados apenas como hash SHA-256.
+
+CREATE TABLE password_reset_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_password_reset_tokens_user_id ON password_reset_tokens(user_id);
+
+-- Comentários para documentação
+COMMENT ON TABLE password_reset_tokens IS 'Tokens de uso único para redefinição de senha';
+COMMENT ON COLUMN password_reset_tokens.used_at IS 'Momento em que o token foi consumido (ou invalidado por um token mais novo)';
@@ -0,0 +1,20 @@
+-- Verificação de email
+-- Usuários já existentes são considerados verificados.
+
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMP WITH TIME ZONE;
+UPDATE users SET email_verified_at = created_at;
+
+CREATE TABLE email_verification_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_email_verification_tokens_user_id ON email_verification_tokens(user_id);
+
+-- Comentários para documentação
+COMMENT ON COLUMN users.email_verified_at IS 'Momento em que o email foi confirmado (NULL = não verificado)';
+COMMENT ON TABLE email_verification_tokens IS 'Tokens de uso único para confirmação de email';
@@ -0,0 +1,34 @@
+-- Autenticação em dois fatores (TOTP) com códigos de recuperação
+
+ALTER TABLE users ADD COLUMN totp_secret VARCHAR(64);
+ALTER TABLE users ADD COLUMN totp_enabled_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE users ADD COLUMN totp_last_step BIGINT;
+
+CREATE TABLE two_factor_recovery_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_two_factor_recovery_codes_user_id ON two_factor_recovery_codes(user_id);
+
+CREATE TABLE two_factor_challenges (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_two_factor_challenges_user_id ON two_factor_challenges(user_id);
+
+-- Comentários para documentação
+COMMENT ON COLUMN users.totp_secret IS 'Segredo TOTP em base32 (pendente de confirmação enquanto totp_enabled_at for NULL)';
+COMMENT ON COLUMN users.totp_enabled_at IS 'Momento em que o 2FA foi ativado (NULL = desativado)';
+COMMENT ON COLUMN users.totp_last_step IS 'Último intervalo TOTP aceito, para impedir reutilização do código';
+COMMENT ON TABLE two_factor_recovery_codes IS 'Códigos de recuperação de uso único (apenas o hash é armazenado)';
+COMMENT ON TABLE two_factor_challenges IS 'Desafios emitidos no login para contas com 2FA';
@@ -0,0 +1,3 @@
+-- Remover controle de tentativas de login
+
+DROP TABLE IF EXISTS login_throttles;
@@ -0,0 +1,15 @@
+-- Controle de tentativas de login malsucedidas por conta (email) e por IP
+
+CREATE TABLE login_throttles (
+    scope VARCHAR(16) NOT NULL,
+    identifier VARCHAR(255) NOT NULL,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (scope, identifier)
+);
+
+-- Comentários para documentação
+COMMENT ON TABLE login_throttles IS 'Falhas de login consecutivas para atraso progressivo e bloqueio temporário';
+COMMENT ON COLUMN login_throttles.scope IS 'account (identificador = email) ou ip (identificador = endereço de origem)';
+COMMENT ON COLUMN login_throttles.locked_until IS 'Bloqueio temporário após atingir o limite de falhas';
@@ -0,0 +1,17 @@
+-- Histórico de senhas (impede reutilização) e data da última troca (validade máxima)
+
+ALTER TABLE users ADD COLUMN password_changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW();
+
+CREATE TABLE password_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    senha_hash VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_password_history_user_id ON password_history(user_id, created_at DESC);
+
+-- Comentários para documentação
+COMMENT ON TABLE password_history IS 'Hashes de senhas anteriores de cada usuário';
+COMMENT ON COLUMN password_history.senha_hash IS 'Hash (Argon2id ou bcrypt) da senha substituída';
+COMMENT ON COLUMN users.password_changed_at IS 'Data da última troca de senha, usada para a validade máxima';
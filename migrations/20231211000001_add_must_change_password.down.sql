@@ -0,0 +1,3 @@
+-- Remover troca de senha obrigatória
+
+ALTER TABLE users DROP COLUMN IF EXISTS must_change_password;
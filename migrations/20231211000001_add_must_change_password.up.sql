@@ -0,0 +1,10 @@
+-- Troca de senha obrigatória no próximo login
+
+ALTER TABLE users ADD COLUMN must_change_password BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- O administrador padrão é criado com a senha conhecida `admin123`
+UPDATE users SET must_change_password = TRUE
+WHERE id = '00000000-0000-0000-0000-000000000001'::uuid;
+
+-- Comentários para documentação
+COMMENT ON COLUMN users.must_change_password IS 'Login emite token restrito à troca de senha até que ela seja alterada';
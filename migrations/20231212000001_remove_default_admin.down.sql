@@ -0,0 +1,6 @@
+-- O administrador padrão não é recriado: a senha dele é pública
+
+DO $$
+BEGIN
+    RAISE NOTICE 'Administrador padrão não recriado; use BOOTSTRAP_ADMIN_EMAIL / BOOTSTRAP_ADMIN_PASSWORD_FILE';
+END $$;
@@ -0,0 +1,13 @@
+-- Remover o administrador padrão com senha conhecida
+-- O primeiro admin passa a ser criado na inicialização a partir de BOOTSTRAP_ADMIN_EMAIL /
+-- BOOTSTRAP_ADMIN_PASSWORD_FILE. Contas cuja senha já foi alterada são mantidas.
+
+DELETE FROM users
+WHERE id = '00000000-0000-0000-0000-000000000001'::uuid
+AND senha = '$2b$12$TcWd/dBbtG16Q7LXDrjKdubheLLzdoHT/Z55SKko/1vOcus8BYzDy'; -- admin123
+
+-- Log da operação
+DO $$
+BEGIN
+    RAISE NOTICE 'Administrador padrão com senha conhecida removido (se existia)';
+END $$;
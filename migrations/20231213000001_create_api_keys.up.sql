@@ -0,0 +1,23 @@
+-- Criar tabela de chaves de API (tokens de acesso pessoal)
+-- A chave é exibida uma única vez na criação; apenas o hash SHA-256 é armazenado.
+
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    prefix VARCHAR(16) NOT NULL,
+    key_hash VARCHAR(64) UNIQUE NOT NULL,
+    scopes TEXT[] NOT NULL DEFAULT '{}',
+    expires_at TIMESTAMP WITH TIME ZONE,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_api_keys_user_id ON api_keys(user_id);
+
+-- Comentários para documentação
+COMMENT ON TABLE api_keys IS 'Chaves de API de usuários para scripts e integrações';
+COMMENT ON COLUMN api_keys.prefix IS 'Início da chave, exibido nas listagens para identificá-la';
+COMMENT ON COLUMN api_keys.scopes IS 'Escopos concedidos; vazio = mesmos acessos do usuário';
+COMMENT ON COLUMN api_keys.expires_at IS 'Validade da chave (NULL = sem expiração)';
@@ -0,0 +1,25 @@
+-- Restaurar o enum user_role a partir das roles atribuídas
+-- Usuários com a role ADMIN voltam a ser ADMIN; os demais, USER.
+
+CREATE TYPE user_role AS ENUM ('USER', 'ADMIN');
+
+ALTER TABLE users
+ADD COLUMN role user_role DEFAULT 'USER' NOT NULL;
+
+UPDATE users SET role = 'ADMIN'
+WHERE id IN (
+    SELECT ur.user_id
+    FROM user_roles ur
+    JOIN roles r ON r.id = ur.role_id
+    WHERE r.name = 'ADMIN'
+);
+
+CREATE INDEX idx_users_role ON users(role);
+
+COMMENT ON TYPE user_role IS 'Tipos de role disponíveis: USER (usuário comum) e ADMIN (administrador)';
+COMMENT ON COLUMN users.role IS 'Role do usuário no sistema (USER ou ADMIN)';
+
+DROP TABLE IF EXISTS user_roles;
+DROP TABLE IF EXISTS role_permissions;
+DROP TABLE IF EXISTS roles;
+DROP TABLE IF EXISTS permissions;
@@ -0,0 +1,69 @@
+-- Controle de acesso baseado em roles definidas no banco (RBAC)
+-- Substitui o enum user_role: roles e permissões passam a ser gerenciadas pela API,
+-- e cada usuário pode ter várias roles.
+
+CREATE TABLE permissions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) UNIQUE NOT NULL,
+    description TEXT,
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE TABLE roles (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(50) UNIQUE NOT NULL,
+    description TEXT,
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE TABLE role_permissions (
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    permission_id UUID NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    PRIMARY KEY (role_id, permission_id)
+);
+
+CREATE TABLE user_roles (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (user_id, role_id)
+);
+
+CREATE INDEX idx_role_permissions_permission_id ON role_permissions(permission_id);
+CREATE INDEX idx_user_roles_role_id ON user_roles(role_id);
+
+-- Permissões verificadas pelo código
+INSERT INTO permissions (name, description, is_system) VALUES
+    ('users:read', 'Consultar os próprios dados', TRUE),
+    ('users:write', 'Alterar os próprios dados, 2FA, chaves de API e sessões', TRUE),
+    ('users:admin', 'Acessar dados de outros usuários e as rotas de administração', TRUE);
+
+-- Roles do antigo enum
+INSERT INTO roles (name, description, is_system) VALUES
+    ('USER', 'Usuário comum', TRUE),
+    ('ADMIN', 'Administrador', TRUE);
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r
+JOIN permissions p ON r.name = 'ADMIN' OR p.name IN ('users:read', 'users:write');
+
+-- Cada usuário recebe a role que tinha na coluna users.role
+INSERT INTO user_roles (user_id, role_id)
+SELECT u.id, r.id
+FROM users u
+JOIN roles r ON r.name = u.role::TEXT;
+
+DROP INDEX IF EXISTS idx_users_role;
+ALTER TABLE users DROP COLUMN role;
+DROP TYPE user_role;
+
+-- Comentários para documentação
+COMMENT ON TABLE permissions IS 'Permissões atribuíveis a roles; também usadas como escopos de chaves de API';
+COMMENT ON TABLE roles IS 'Roles de usuário com o conjunto de permissões que concedem';
+COMMENT ON TABLE user_roles IS 'Roles atribuídas a cada usuário (muitos-para-muitos)';
+COMMENT ON COLUMN permissions.is_system IS 'Permissão verificada pelo código; não pode ser removida';
+COMMENT ON COLUMN roles.is_system IS 'Role padrão (USER/ADMIN); não pode ser renomeada nem removida';
@@ -0,0 +1,9 @@
+-- Remover organizações e vínculos
+
+ALTER TABLE api_keys DROP COLUMN IF EXISTS organization_id;
+ALTER TABLE refresh_tokens DROP COLUMN IF EXISTS organization_id;
+
+DROP INDEX IF EXISTS idx_organization_members_user_id;
+DROP TABLE IF EXISTS organization_members;
+DROP TABLE IF EXISTS organizations;
+DROP TYPE IF EXISTS organization_role;
@@ -0,0 +1,34 @@
+-- Organizações (tenants) e vínculos de usuários com papel por organização
+-- Um usuário pode pertencer a várias organizações; a organização ativa é gravada no token.
+
+CREATE TYPE organization_role AS ENUM ('owner', 'admin', 'member');
+
+CREATE TABLE organizations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE TABLE organization_members (
+    organization_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role organization_role NOT NULL DEFAULT 'member',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (organization_id, user_id)
+);
+
+CREATE INDEX idx_organization_members_user_id ON organization_members(user_id);
+
+-- Organização ativa das sessões e das chaves de API
+ALTER TABLE refresh_tokens
+    ADD COLUMN organization_id UUID REFERENCES organizations(id) ON DELETE SET NULL;
+ALTER TABLE api_keys
+    ADD COLUMN organization_id UUID REFERENCES organizations(id) ON DELETE CASCADE;
+
+-- Comentários para documentação
+COMMENT ON TABLE organizations IS 'Organizações (tenants) que agrupam usuários';
+COMMENT ON TABLE organization_members IS 'Vínculo do usuário com a organização e seu papel nela';
+COMMENT ON COLUMN organization_members.role IS 'owner: controle total; admin: gerencia membros; member: acesso de leitura';
+COMMENT ON COLUMN refresh_tokens.organization_id IS 'Organização ativa mantida ao renovar a sessão';
+COMMENT ON COLUMN api_keys.organization_id IS 'Organização ativa na criação da chave (NULL = nenhuma)';
@@ -0,0 +1,4 @@
+-- Remover convites para organizações
+
+DROP INDEX IF EXISTS idx_organization_invitations_organization_id;
+DROP TABLE IF EXISTS organization_invitations;
@@ -0,0 +1,23 @@
+-- Convites para organizações enviados por email
+-- O token é armazenado apenas como hash; o link enviado contém o token em claro.
+
+CREATE TABLE organization_invitations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    organization_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    role organization_role NOT NULL DEFAULT 'member',
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    accepted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_organization_invitations_organization_id ON organization_invitations(organization_id);
+
+-- Comentários para documentação
+COMMENT ON TABLE organization_invitations IS 'Convites de uso único para participar de uma organização';
+COMMENT ON COLUMN organization_invitations.role IS 'Papel concedido ao aceitar o convite';
+COMMENT ON COLUMN organization_invitations.accepted_by IS 'Usuário vinculado ao aceitar (existente ou criado pelo convite)';
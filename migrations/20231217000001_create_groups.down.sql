@@ -0,0 +1,8 @@
+-- Remover grupos de usuários
+-- Permissões obtidas apenas por grupos deixam de valer.
+
+DROP VIEW IF EXISTS user_permission_grants;
+DROP TABLE IF EXISTS group_permissions;
+DROP TABLE IF EXISTS group_roles;
+DROP TABLE IF EXISTS group_members;
+DROP TABLE IF EXISTS groups;
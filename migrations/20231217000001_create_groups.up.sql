@@ -0,0 +1,57 @@
+-- Grupos de usuários para atribuição de roles e permissões em massa
+-- As permissões efetivas de um usuário são a união das concedidas pelas roles
+-- atribuídas diretamente a ele e pelas roles e permissões de todos os seus grupos.
+
+CREATE TABLE groups (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) UNIQUE NOT NULL,
+    description TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE TABLE group_members (
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (group_id, user_id)
+);
+
+CREATE TABLE group_roles (
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    PRIMARY KEY (group_id, role_id)
+);
+
+CREATE TABLE group_permissions (
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    permission_id UUID NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    PRIMARY KEY (group_id, permission_id)
+);
+
+CREATE INDEX idx_group_members_user_id ON group_members(user_id);
+CREATE INDEX idx_group_roles_role_id ON group_roles(role_id);
+CREATE INDEX idx_group_permissions_permission_id ON group_permissions(permission_id);
+
+-- Cada linha é uma origem de uma permissão do usuário: role direta (group_id NULL),
+-- role de um grupo ou permissão atribuída ao grupo (role_id NULL)
+CREATE VIEW user_permission_grants AS
+    SELECT ur.user_id, rp.permission_id, ur.role_id, NULL::UUID AS group_id
+    FROM user_roles ur
+    JOIN role_permissions rp ON rp.role_id = ur.role_id
+    UNION ALL
+    SELECT gm.user_id, rp.permission_id, gr.role_id, gm.group_id
+    FROM group_members gm
+    JOIN group_roles gr ON gr.group_id = gm.group_id
+    JOIN role_permissions rp ON rp.role_id = gr.role_id
+    UNION ALL
+    SELECT gm.user_id, gp.permission_id, NULL::UUID AS role_id, gm.group_id
+    FROM group_members gm
+    JOIN group_permissions gp ON gp.group_id = gm.group_id;
+
+-- Comentários para documentação
+COMMENT ON TABLE groups IS 'Grupos de usuários que recebem roles e permissões em conjunto';
+COMMENT ON TABLE group_members IS 'Usuários de cada grupo (muitos-para-muitos)';
+COMMENT ON TABLE group_roles IS 'Roles concedidas a todos os membros do grupo';
+COMMENT ON TABLE group_permissions IS 'Permissões concedidas a todos os membros do grupo, sem role intermediária';
+COMMENT ON VIEW user_permission_grants IS 'Origens das permissões efetivas de cada usuário (roles diretas e grupos)';
@@ -0,0 +1,5 @@
+-- Remover login via OpenID Connect
+-- Usuários criados pelo provedor continuam existindo, sem o vínculo externo.
+
+DROP TABLE IF EXISTS user_identities;
+DROP TABLE IF EXISTS oidc_login_states;
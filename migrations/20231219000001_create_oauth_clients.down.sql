@@ -0,0 +1,6 @@
+-- Remover o servidor de autorização OAuth 2.0
+-- Tokens já emitidos para os clientes deixam de ser aceitos, pois o cliente não existe mais.
+
+DROP TABLE IF EXISTS oauth_authorization_codes;
+DROP TABLE IF EXISTS oauth_clients;
+DROP TYPE IF EXISTS oauth_client_type;
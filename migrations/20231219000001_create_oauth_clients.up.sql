@@ -0,0 +1,42 @@
+-- Servidor de autorização OAuth 2.0 (client_credentials e authorization code + PKCE)
+
+CREATE TYPE oauth_client_type AS ENUM ('confidential', 'public');
+
+CREATE TABLE oauth_clients (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    client_id VARCHAR(64) UNIQUE NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    client_type oauth_client_type NOT NULL,
+    secret_hash VARCHAR(64),
+    redirect_uris TEXT[] NOT NULL DEFAULT '{}',
+    grant_types TEXT[] NOT NULL DEFAULT '{}',
+    scopes TEXT[] NOT NULL DEFAULT '{}',
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    CHECK ((client_type = 'confidential') = (secret_hash IS NOT NULL))
+);
+
+CREATE TABLE oauth_authorization_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    code_hash VARCHAR(64) UNIQUE NOT NULL,
+    client_id UUID NOT NULL REFERENCES oauth_clients(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    organization_id UUID REFERENCES organizations(id) ON DELETE CASCADE,
+    redirect_uri TEXT NOT NULL,
+    scopes TEXT[] NOT NULL,
+    code_challenge VARCHAR(128) NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_oauth_authorization_codes_expires_at ON oauth_authorization_codes(expires_at);
+
+-- Comentários para documentação
+COMMENT ON TABLE oauth_clients IS 'Clientes OAuth 2.0 registrados por administradores';
+COMMENT ON COLUMN oauth_clients.client_id IS 'Identificador público do cliente (client_id)';
+COMMENT ON COLUMN oauth_clients.secret_hash IS 'SHA-256 do client_secret; ausente em clientes públicos';
+COMMENT ON COLUMN oauth_clients.scopes IS 'Permissões máximas concedidas aos tokens do cliente';
+COMMENT ON COLUMN oauth_clients.user_id IS 'Conta em nome da qual o grant client_credentials age';
+COMMENT ON TABLE oauth_authorization_codes IS 'Códigos de autorização de uso único, removidos ao serem trocados por um token';
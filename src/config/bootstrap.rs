@@ -0,0 +1,37 @@
+use anyhow::{bail, Context, Result};
+use std::env;
+
+use crate::services::bootstrap_service::BootstrapAdmin;
+
+// Lê o administrador inicial de `BOOTSTRAP_ADMIN_EMAIL`, `BOOTSTRAP_ADMIN_PASSWORD_FILE`
+// (arquivo com a senha, ex.: secret do Docker/Kubernetes) e `BOOTSTRAP_ADMIN_NOME`.
+// Sem email configurado, o bootstrap fica desativado.
+pub fn bootstrap_admin_from_env() -> Result<Option<BootstrapAdmin>> {
+    let email = match env::var("BOOTSTRAP_ADMIN_EMAIL") {
+        Ok(email) if !email.trim().is_empty() => email.trim().to_string(),
+        _ => return Ok(None),
+    };
+
+    if !email.contains('@') {
+        bail!("BOOTSTRAP_ADMIN_EMAIL deve ser um email válido");
+    }
+
+    let path = env::var("BOOTSTRAP_ADMIN_PASSWORD_FILE")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .context("BOOTSTRAP_ADMIN_PASSWORD_FILE deve estar definida com BOOTSTRAP_ADMIN_EMAIL")?;
+
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Falha ao ler a senha do admin inicial em {}", path))?;
+    // Editores e `echo` costumam deixar uma quebra de linha no final
+    let password = contents.trim_end_matches(['\r', '\n']).to_string();
+    if password.is_empty() {
+        bail!("O arquivo {} está vazio", path);
+    }
+
+    Ok(Some(BootstrapAdmin {
+        nome: env::var("BOOTSTRAP_ADMIN_NOME").unwrap_or_else(|_| "Administrador".to_string()),
+        email,
+        password,
+    }))
+}
@@ -0,0 +1,208 @@
+use anyhow::{anyhow, bail, Context, Result};
+use jsonwebtoken::Algorithm;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::models::{JwtKey, JwtKeyRing};
+
+// Arquivo do diretório de chaves que indica o `kid` da chave ativa
+pub const ACTIVE_KID_FILE: &str = "ACTIVE";
+
+const PRIVATE_KEY_SUFFIX: &str = ".key.pem";
+const PUBLIC_KEY_SUFFIX: &str = ".pub.pem";
+
+// Carrega as chaves de assinatura a partir das variáveis de ambiente.
+//
+// Com `JWT_KEYS_DIR`, as chaves vêm do diretório (ver `load_keys_dir`). Caso contrário:
+// - HS256: `JWT_SECRET` assina; `JWT_PREVIOUS_SECRETS` (`kid:segredo,...`) só verifica
+// - RS256/ES256/EdDSA: `JWT_PRIVATE_KEY_PATH`/`JWT_PUBLIC_KEY_PATH` assinam;
+//   `JWT_VERIFICATION_KEY_PATHS` (`[kid=]arquivo.pem,...`) só verificam
+pub fn load_key_ring() -> Result<(JwtKeyRing, Option<PathBuf>)> {
+    if let Ok(dir) = env::var("JWT_KEYS_DIR") {
+        let dir = PathBuf::from(dir);
+        let ring = load_keys_dir(&dir)?;
+        return Ok((ring, Some(dir)));
+    }
+
+    let kid = env::var("JWT_KEY_ID").ok();
+    let jwt_algorithm = env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string());
+
+    let ring = if jwt_algorithm == "HS256" {
+        let secret = env::var("JWT_SECRET")
+            .unwrap_or_else(|_| "your-secret-key-change-this-in-production".to_string());
+        let mut ring = JwtKeyRing::new(JwtKey::hmac(secret.as_bytes(), kid));
+
+        for entry in list_var("JWT_PREVIOUS_SECRETS") {
+            let (kid, secret) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("JWT_PREVIOUS_SECRETS deve usar o formato kid:segredo"))?;
+            ring.add(JwtKey::hmac(secret.as_bytes(), Some(kid.to_string())));
+        }
+
+        ring
+    } else {
+        let algorithm = jwt_algorithm
+            .parse::<Algorithm>()
+            .map_err(|_| anyhow!("JWT_ALGORITHM deve ser HS256, RS256, ES256 ou EdDSA"))?;
+        let private_key_path = env::var("JWT_PRIVATE_KEY_PATH")
+            .context("JWT_PRIVATE_KEY_PATH deve estar definida para chaves assimétricas")?;
+        let public_key_path = env::var("JWT_PUBLIC_KEY_PATH")
+            .context("JWT_PUBLIC_KEY_PATH deve estar definida para chaves assimétricas")?;
+
+        let private_pem = fs::read(&private_key_path)
+            .with_context(|| format!("falha ao ler {}", private_key_path))?;
+        let public_pem = fs::read(&public_key_path)
+            .with_context(|| format!("falha ao ler {}", public_key_path))?;
+
+        let mut ring =
+            JwtKeyRing::new(JwtKey::from_pem(algorithm, &private_pem, &public_pem, kid)?);
+
+        for entry in list_var("JWT_VERIFICATION_KEY_PATHS") {
+            let (kid, path) = match entry.split_once('=') {
+                Some((kid, path)) => (Some(kid.to_string()), path.to_string()),
+                None => (None, entry),
+            };
+            let public_pem = fs::read(&path).with_context(|| format!("falha ao ler {}", path))?;
+            ring.add(JwtKey::from_public_pem(&public_pem, kid)?);
+        }
+
+        ring
+    };
+
+    Ok((ring, None))
+}
+
+// Carrega as chaves de um diretório no formato:
+//   <kid>.key.pem + <kid>.pub.pem  -> chave que pode assinar
+//   <kid>.pub.pem                  -> chave apenas de verificação
+// A chave ativa é `JWT_ACTIVE_KID`, o conteúdo do arquivo `ACTIVE` ou, na falta
+// de ambos, a única chave com parte privada.
+pub fn load_keys_dir(dir: &Path) -> Result<JwtKeyRing> {
+    let mut kids: Vec<String> = fs::read_dir(dir)
+        .with_context(|| format!("falha ao ler o diretório de chaves {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(PUBLIC_KEY_SUFFIX))
+                .map(str::to_string)
+        })
+        .collect();
+    kids.sort();
+
+    let mut keys = Vec::with_capacity(kids.len());
+    for kid in kids {
+        let public_pem = fs::read(dir.join(format!("{}{}", kid, PUBLIC_KEY_SUFFIX)))?;
+        let private_path = dir.join(format!("{}{}", kid, PRIVATE_KEY_SUFFIX));
+
+        let key = if private_path.exists() {
+            let private_pem = fs::read(&private_path)?;
+            let public_key = JwtKey::from_public_pem(&public_pem, Some(kid.clone()))?;
+            JwtKey::from_pem(
+                public_key.algorithm,
+                &private_pem,
+                &public_pem,
+                Some(kid.clone()),
+            )
+        } else {
+            JwtKey::from_public_pem(&public_pem, Some(kid.clone()))
+        }
+        .with_context(|| format!("chave {} inválida", kid))?;
+
+        keys.push(key);
+    }
+
+    let active_kid = match env::var("JWT_ACTIVE_KID") {
+        Ok(kid) => Some(kid),
+        Err(_) => fs::read_to_string(dir.join(ACTIVE_KID_FILE))
+            .ok()
+            .map(|kid| kid.trim().to_string()),
+    };
+
+    build_key_ring(keys, active_kid.as_deref())
+}
+
+// Registra a chave ativa no diretório, para que sobreviva a reinícios e
+// seja vista pelas demais instâncias ao recarregar
+pub fn write_active_kid(dir: &Path, kid: &str) -> Result<()> {
+    let path = dir.join(ACTIVE_KID_FILE);
+    let tmp = dir.join(format!(".{}.tmp", ACTIVE_KID_FILE));
+    fs::write(&tmp, format!("{}\n", kid))?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+fn build_key_ring(keys: Vec<JwtKey>, active_kid: Option<&str>) -> Result<JwtKeyRing> {
+    let active_index = match active_kid {
+        Some(kid) => keys
+            .iter()
+            .position(|key| key.kid.as_deref() == Some(kid))
+            .ok_or_else(|| anyhow!("chave ativa {} não encontrada", kid))?,
+        None => {
+            let mut signing = keys.iter().enumerate().filter(|(_, key)| key.can_sign());
+            match (signing.next(), signing.next()) {
+                (Some((index, _)), None) => index,
+                (None, _) => bail!("nenhuma chave privada encontrada"),
+                _ => bail!("várias chaves privadas: defina a chave ativa"),
+            }
+        }
+    };
+
+    let mut keys = keys;
+    let active = keys.remove(active_index);
+    if !active.can_sign() {
+        bail!(
+            "chave ativa {} não possui parte privada",
+            active.kid.as_deref().unwrap_or("-")
+        );
+    }
+
+    Ok(keys
+        .into_iter()
+        .fold(JwtKeyRing::new(active), JwtKeyRing::with_key))
+}
+
+fn list_var(name: &str) -> Vec<String> {
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::jwt_key::tests::{ED_PRIVATE_PEM, ED_PUBLIC_PEM, RSA_PUBLIC_PEM};
+
+    #[test]
+    fn test_key_dir_with_active_file() {
+        let dir = env::temp_dir().join(format!("jwt-keys-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("ed-2.key.pem"), ED_PRIVATE_PEM).unwrap();
+        fs::write(dir.join("ed-2.pub.pem"), ED_PUBLIC_PEM).unwrap();
+        fs::write(dir.join("rsa-1.pub.pem"), RSA_PUBLIC_PEM).unwrap();
+        write_active_kid(&dir, "ed-2").unwrap();
+
+        let ring = load_keys_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(ring.active().kid.as_deref(), Some("ed-2"));
+        assert_eq!(ring.keys().len(), 2);
+        assert_eq!(ring.jwks().keys.len(), 2);
+    }
+
+    #[test]
+    fn test_verification_only_key_cannot_be_active() {
+        let public_key = JwtKey::from_public_pem(RSA_PUBLIC_PEM.as_bytes(), None).unwrap();
+        let kid = public_key.kid.clone().unwrap();
+
+        assert!(build_key_ring(vec![public_key.clone()], Some(&kid)).is_err());
+        assert!(build_key_ring(vec![public_key], None).is_err());
+    }
+}
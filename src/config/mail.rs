@@ -0,0 +1,56 @@
+use anyhow::{anyhow, bail, Context, Result};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, Tokio1Executor};
+use std::{env, sync::Arc};
+
+use crate::mailer::{FileMailer, LogMailer, Mailer, SmtpMailer};
+
+// Cria o transporte de emails a partir de `MAIL_TRANSPORT`:
+// - `log` (padrão): emails apenas registrados no log, para desenvolvimento e testes
+// - `file`: cada email gravado como .eml em `MAIL_DIR`
+// - `smtp`: `SMTP_HOST`, `SMTP_PORT`, `SMTP_USERNAME`, `SMTP_PASSWORD` e
+//   `SMTP_TLS` (`starttls`, `tls` ou `none`)
+// O remetente vem de `MAIL_FROM`.
+pub fn mailer_from_env() -> Result<Arc<dyn Mailer>> {
+    let transport = env::var("MAIL_TRANSPORT").unwrap_or_else(|_| "log".to_string());
+
+    match transport.as_str() {
+        "log" => Ok(Arc::new(LogMailer)),
+        "file" => {
+            let dir = env::var("MAIL_DIR").unwrap_or_else(|_| "./mail".to_string());
+            Ok(Arc::new(FileMailer::new(mail_from()?, dir.into())))
+        }
+        "smtp" => {
+            let host = env::var("SMTP_HOST").context("SMTP_HOST deve estar definida")?;
+            let tls = env::var("SMTP_TLS").unwrap_or_else(|_| "starttls".to_string());
+
+            let mut builder = match tls.as_str() {
+                "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)?,
+                "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)?,
+                "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+                other => bail!("SMTP_TLS inválido: {} (use starttls, tls ou none)", other),
+            };
+
+            if let Ok(port) = env::var("SMTP_PORT") {
+                builder = builder.port(port.parse().context("SMTP_PORT deve ser um número")?);
+            }
+
+            if let (Ok(username), Ok(password)) =
+                (env::var("SMTP_USERNAME"), env::var("SMTP_PASSWORD"))
+            {
+                builder = builder.credentials(Credentials::new(username, password));
+            }
+
+            Ok(Arc::new(SmtpMailer::new(mail_from()?, builder.build())))
+        }
+        other => bail!("MAIL_TRANSPORT inválido: {} (use log, file ou smtp)", other),
+    }
+}
+
+fn mail_from() -> Result<Mailbox> {
+    env::var("MAIL_FROM")
+        .unwrap_or_else(|_| "API Rust <no-reply@localhost>".to_string())
+        .parse()
+        .map_err(|e| anyhow!("MAIL_FROM inválido: {}", e))
+}
@@ -1 +1,6 @@
+pub mod bootstrap;
 pub mod database;
+pub mod jwt;
+pub mod mail;
+pub mod oidc;
+pub mod password;
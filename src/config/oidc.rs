@@ -0,0 +1,91 @@
+use anyhow::{bail, Context, Result};
+use std::env;
+use url::Url;
+
+use crate::models::OidcConfig;
+
+// Lê o provedor OpenID Connect de `OIDC_ISSUER`, `OIDC_CLIENT_ID`, `OIDC_REDIRECT_URI` e,
+// para clientes confidenciais, `OIDC_CLIENT_SECRET` ou `OIDC_CLIENT_SECRET_FILE`.
+// Opcionais: `OIDC_SCOPES`, `OIDC_ALLOWED_DOMAINS` (separados por vírgula),
+// `OIDC_AUTO_PROVISION` e `OIDC_LOGIN_EXPIRATION`. Sem issuer, o login OIDC fica desativado.
+pub fn oidc_config_from_env() -> Result<Option<OidcConfig>> {
+    let issuer = match env::var("OIDC_ISSUER") {
+        Ok(issuer) if !issuer.trim().is_empty() => issuer.trim().to_string(),
+        _ => return Ok(None),
+    };
+    Url::parse(&issuer).context("OIDC_ISSUER deve ser uma URL válida")?;
+
+    let client_id = required("OIDC_CLIENT_ID")?;
+    let redirect_uri = required("OIDC_REDIRECT_URI")?;
+    Url::parse(&redirect_uri).context("OIDC_REDIRECT_URI deve ser uma URL válida")?;
+
+    // O escopo `openid` é o que faz o provedor emitir o ID token
+    let mut scopes: Vec<String> = env::var("OIDC_SCOPES")
+        .unwrap_or_else(|_| "openid email profile".to_string())
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    if !scopes.iter().any(|scope| scope == "openid") {
+        scopes.insert(0, "openid".to_string());
+    }
+
+    let allowed_domains = env::var("OIDC_ALLOWED_DOMAINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+        .filter(|domain| !domain.is_empty())
+        .collect();
+
+    let auto_provision = match env::var("OIDC_AUTO_PROVISION") {
+        Ok(value) => value
+            .parse()
+            .context("OIDC_AUTO_PROVISION deve ser true ou false")?,
+        Err(_) => true,
+    };
+
+    let login_ttl_seconds = match env::var("OIDC_LOGIN_EXPIRATION") {
+        Ok(value) => value
+            .parse()
+            .context("OIDC_LOGIN_EXPIRATION deve ser um número válido")?,
+        Err(_) => 600,
+    };
+
+    Ok(Some(OidcConfig {
+        issuer,
+        client_id,
+        client_secret: client_secret()?,
+        redirect_uri,
+        scopes: scopes.join(" "),
+        allowed_domains,
+        auto_provision,
+        login_ttl_seconds,
+    }))
+}
+
+fn required(name: &str) -> Result<String> {
+    env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .with_context(|| format!("{} deve estar definida com OIDC_ISSUER", name))
+}
+
+// O segredo pode vir de um arquivo (secret do Docker/Kubernetes) em vez da variável
+fn client_secret() -> Result<Option<String>> {
+    if let Some(path) = env::var("OIDC_CLIENT_SECRET_FILE")
+        .ok()
+        .filter(|value| !value.is_empty())
+    {
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Falha ao ler o segredo OIDC em {}", path))?;
+        let secret = contents.trim_end_matches(['\r', '\n']).to_string();
+        if secret.is_empty() {
+            bail!("O arquivo {} está vazio", path);
+        }
+        return Ok(Some(secret));
+    }
+
+    Ok(env::var("OIDC_CLIENT_SECRET")
+        .ok()
+        .filter(|value| !value.is_empty()))
+}
@@ -0,0 +1,69 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use std::env;
+use std::str::FromStr;
+
+use crate::password::PasswordPolicy;
+
+// Monta a política de senhas a partir de `PASSWORD_*`. A lista de senhas comuns vem de
+// `PASSWORD_COMMON_LIST_FILE` (uma por linha); vazia desativa a verificação.
+pub fn password_policy_from_env() -> Result<PasswordPolicy> {
+    let defaults = PasswordPolicy::default();
+
+    let policy = PasswordPolicy {
+        min_length: number("PASSWORD_MIN_LENGTH", defaults.min_length)?,
+        max_length: number("PASSWORD_MAX_LENGTH", defaults.max_length)?,
+        require_lowercase: flag("PASSWORD_REQUIRE_LOWERCASE", defaults.require_lowercase)?,
+        require_uppercase: flag("PASSWORD_REQUIRE_UPPERCASE", defaults.require_uppercase)?,
+        require_digit: flag("PASSWORD_REQUIRE_DIGIT", defaults.require_digit)?,
+        require_symbol: flag("PASSWORD_REQUIRE_SYMBOL", defaults.require_symbol)?,
+        forbid_personal_info: flag(
+            "PASSWORD_FORBID_PERSONAL_INFO",
+            defaults.forbid_personal_info,
+        )?,
+        common_passwords: common_passwords()?,
+        history_size: number("PASSWORD_HISTORY_SIZE", defaults.history_size)?,
+        max_age_days: number("PASSWORD_MAX_AGE_DAYS", defaults.max_age_days)?,
+    };
+
+    if policy.min_length > policy.max_length {
+        bail!("PASSWORD_MIN_LENGTH não pode ser maior que PASSWORD_MAX_LENGTH");
+    }
+
+    Ok(policy)
+}
+
+fn number<T>(name: &str, default: T) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .with_context(|| format!("{} deve ser um número válido", name)),
+        Err(_) => Ok(default),
+    }
+}
+
+fn flag(name: &str, default: bool) -> Result<bool> {
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .with_context(|| format!("{} deve ser true ou false", name)),
+        Err(_) => Ok(default),
+    }
+}
+
+fn common_passwords() -> Result<HashSet<String>> {
+    let path = env::var("PASSWORD_COMMON_LIST_FILE")
+        .unwrap_or_else(|_| "./data/common-passwords.txt".to_string());
+
+    if path.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Falha ao ler a lista de senhas comuns em {}", path))?;
+    Ok(PasswordPolicy::parse_common_passwords(&contents))
+}
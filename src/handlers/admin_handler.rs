@@ -0,0 +1,164 @@
+use actix_web::{web, HttpResponse, Result};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::jwt::{load_keys_dir, write_active_kid};
+use crate::middleware::{bad_request_error, internal_server_error, not_found_error};
+use crate::models::{JwtConfig, JwtKeyRing};
+use crate::services::login_throttle_service;
+
+#[derive(Debug, Serialize)]
+pub struct JwtKeyResponse {
+    pub kid: Option<String>,
+    pub algorithm: String,
+    pub active: bool,
+    pub can_sign: bool,
+}
+
+fn key_list(ring: &JwtKeyRing) -> Vec<JwtKeyResponse> {
+    let active_kid = ring.active().kid.clone();
+
+    ring.keys()
+        .iter()
+        .map(|key| JwtKeyResponse {
+            kid: key.kid.clone(),
+            algorithm: format!("{:?}", key.algorithm),
+            active: key.kid == active_kid,
+            can_sign: key.can_sign(),
+        })
+        .collect()
+}
+
+// Lista as chaves de assinatura/verificação de JWT (requer admin)
+pub async fn list_jwt_keys(jwt_config: web::Data<JwtConfig>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(key_list(&jwt_config.key_ring())))
+}
+
+// Relê o diretório de chaves (JWT_KEYS_DIR), publicando chaves novas e
+// removendo chaves retiradas sem reiniciar o servidor
+pub async fn reload_jwt_keys(jwt_config: web::Data<JwtConfig>) -> Result<HttpResponse> {
+    let keys_dir = match &jwt_config.keys_dir {
+        Some(dir) => dir.clone(),
+        None => {
+            return Ok(bad_request_error(
+                "Chaves não foram carregadas de um diretório (JWT_KEYS_DIR)",
+                "KEYS_DIR_NOT_CONFIGURED",
+            ))
+        }
+    };
+
+    match web::block(move || load_keys_dir(&keys_dir)).await {
+        Ok(Ok(ring)) => {
+            let keys = key_list(&ring);
+            jwt_config.replace_keys(ring);
+            Ok(HttpResponse::Ok().json(keys))
+        }
+        Ok(Err(e)) => Ok(bad_request_error(
+            &format!("Falha ao carregar chaves: {:#}", e),
+            "INVALID_KEYS",
+        )),
+        Err(e) => {
+            eprintln!("Erro ao recarregar chaves JWT: {:?}", e);
+            Ok(internal_server_error(
+                "Erro interno do servidor",
+                "KEY_STORAGE_ERROR",
+            ))
+        }
+    }
+}
+
+// Passa a assinar novos tokens com a chave `kid`. Tokens assinados com a chave
+// anterior continuam válidos até expirarem, enquanto ela estiver no conjunto.
+pub async fn promote_jwt_key(
+    jwt_config: web::Data<JwtConfig>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let kid = path.into_inner();
+
+    let ring = jwt_config.key_ring();
+    let key = match ring
+        .keys()
+        .iter()
+        .find(|key| key.kid.as_deref() == Some(kid.as_str()))
+    {
+        Some(key) => key,
+        None => return Ok(not_found_error("Chave não encontrada", "KEY_NOT_FOUND")),
+    };
+
+    if !key.can_sign() {
+        return Ok(bad_request_error(
+            "Chave não possui parte privada e só pode ser usada para verificação",
+            "KEY_CANNOT_SIGN",
+        ));
+    }
+
+    // Persiste a chave ativa antes de promovê-la, para sobreviver a reinícios
+    if let Some(keys_dir) = &jwt_config.keys_dir {
+        if let Err(e) = write_active_kid(keys_dir, &kid) {
+            eprintln!("Erro ao gravar chave ativa: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro ao gravar chave ativa",
+                "KEY_STORAGE_ERROR",
+            ));
+        }
+    }
+
+    if let Err(e) = jwt_config.promote_key(&kid) {
+        return Ok(bad_request_error(&e.to_string(), "KEY_CANNOT_SIGN"));
+    }
+
+    Ok(HttpResponse::Ok().json(key_list(&jwt_config.key_ring())))
+}
+
+// Desbloqueia a conta e zera as falhas de login registradas para ela
+pub async fn unlock_user(pool: web::Data<PgPool>, path: web::Path<Uuid>) -> Result<HttpResponse> {
+    let user_id = path.into_inner();
+
+    let email = sqlx::query_scalar::<_, String>("SELECT email FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool.get_ref())
+        .await;
+
+    let email = match email {
+        Ok(Some(email)) => email,
+        Ok(None) => return Ok(not_found_error("Usuário não encontrado", "USER_NOT_FOUND")),
+        Err(e) => {
+            eprintln!("Erro ao buscar usuário: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    match login_throttle_service::unlock(pool.get_ref(), &email).await {
+        Ok(had_failures) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Conta desbloqueada",
+            "had_failed_attempts": had_failures
+        }))),
+        Err(e) => {
+            eprintln!("Erro ao desbloquear conta: {:?}", e);
+            Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ))
+        }
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    use crate::middleware::admin_required;
+    use actix_web_httpauth::middleware::HttpAuthentication;
+
+    cfg.service(
+        web::scope("/admin")
+            .wrap(HttpAuthentication::with_fn(admin_required))
+            .route("/jwt-keys", web::get().to(list_jwt_keys))
+            .route("/jwt-keys/reload", web::post().to(reload_jwt_keys))
+            .route("/jwt-keys/{kid}/promote", web::post().to(promote_jwt_key))
+            .route("/users/{id}/unlock", web::post().to(unlock_user))
+            .configure(crate::handlers::rbac_handler::config)
+            .service(web::scope("/oauth").configure(crate::handlers::oauth_handler::admin_config)),
+    );
+}
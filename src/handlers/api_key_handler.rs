@@ -0,0 +1,191 @@
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::middleware::{
+    bad_request_error, forbidden_error, get_claims_from_http_request, internal_server_error,
+    not_found_error, unauthorized_error,
+};
+use crate::models::{ApiKeyResponse, Claims, CreateApiKeyRequest, CreatedApiKeyResponse};
+use crate::services::{api_key_service, rbac_service};
+
+const MAX_NAME_LENGTH: usize = 100;
+// Validade máxima de uma chave (10 anos)
+const MAX_EXPIRATION_DAYS: i64 = 3650;
+
+// Claims da requisição e o ID do usuário autenticado
+fn current_claims(req: &HttpRequest) -> Option<(Claims, Uuid)> {
+    let claims = get_claims_from_http_request(req)?;
+    let user_id = claims.get_user_id().ok()?;
+    Some((claims, user_id))
+}
+
+fn token_missing_error() -> HttpResponse {
+    unauthorized_error("Token JWT não encontrado", "TOKEN_MISSING")
+}
+
+fn database_error(context: &str, e: sqlx::Error) -> HttpResponse {
+    eprintln!("{}: {:?}", context, e);
+    internal_server_error("Erro interno do servidor", "DATABASE_ERROR")
+}
+
+// Expiração da chave a partir de `now`; `None` se `days` está fora de 1..=MAX_EXPIRATION_DAYS
+fn expiration(now: DateTime<Utc>, days: i64) -> Option<DateTime<Utc>> {
+    if !(1..=MAX_EXPIRATION_DAYS).contains(&days) {
+        return None;
+    }
+    now.checked_add_signed(Duration::try_days(days)?)
+}
+
+// Chaves de API do usuário autenticado
+pub async fn list_api_keys(pool: web::Data<PgPool>, req: HttpRequest) -> Result<HttpResponse> {
+    let Some((_, user_id)) = current_claims(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    match api_key_service::list_for_user(pool.get_ref(), user_id).await {
+        Ok(api_keys) => Ok(HttpResponse::Ok().json(
+            api_keys
+                .into_iter()
+                .map(ApiKeyResponse::from)
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => Ok(database_error("Erro ao listar chaves de API", e)),
+    }
+}
+
+// Cria uma chave de API. A chave completa só é exibida nesta resposta.
+pub async fn create_api_key(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    key_data: web::Json<CreateApiKeyRequest>,
+) -> Result<HttpResponse> {
+    let Some((claims, user_id)) = current_claims(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    // Uma credencial com escopos limitados não pode emitir outra sem essas limitações
+    if claims.is_delegated() {
+        return Ok(forbidden_error(
+            "Chaves de API só podem ser criadas com login por senha",
+            "API_KEY_NOT_ALLOWED",
+        ));
+    }
+
+    let name = key_data.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Ok(bad_request_error(
+            &format!("O nome deve ter entre 1 e {} caracteres", MAX_NAME_LENGTH),
+            "INVALID_API_KEY_NAME",
+        ));
+    }
+
+    let expires_at = match key_data.expires_in_days {
+        Some(days) => match expiration(Utc::now(), days) {
+            Some(expires_at) => Some(expires_at),
+            None => {
+                return Ok(bad_request_error(
+                    &format!(
+                        "expires_in_days deve estar entre 1 e {}",
+                        MAX_EXPIRATION_DAYS
+                    ),
+                    "INVALID_EXPIRATION",
+                ));
+            }
+        },
+        None => None,
+    };
+
+    // Escopos são nomes de permissões; os que o usuário não tem ficam sem efeito
+    let mut scopes: Vec<String> = Vec::new();
+    for scope in &key_data.scopes {
+        if !scopes.contains(scope) {
+            scopes.push(scope.clone());
+        }
+    }
+
+    match rbac_service::unknown_permissions(pool.get_ref(), &scopes).await {
+        Ok(unknown) if !unknown.is_empty() => {
+            return Ok(bad_request_error(
+                &format!("Escopo desconhecido: {}", unknown.join(", ")),
+                "INVALID_SCOPE",
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => return Ok(database_error("Erro ao validar escopos", e)),
+    }
+
+    // A chave age na organização ativa do token que a criou
+    let organization_id = claims.org_role.and_then(|_| claims.get_organization_id());
+
+    match api_key_service::create(
+        pool.get_ref(),
+        user_id,
+        organization_id,
+        name,
+        &scopes,
+        expires_at,
+    )
+    .await
+    {
+        Ok((key, api_key)) => Ok(HttpResponse::Created().json(CreatedApiKeyResponse {
+            key,
+            api_key: api_key.into(),
+        })),
+        Err(e) => Ok(database_error("Erro ao criar chave de API", e)),
+    }
+}
+
+// Revoga uma chave do usuário autenticado; requisições com ela passam a receber 401
+pub async fn revoke_api_key(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let Some((_, user_id)) = current_claims(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    match api_key_service::revoke(pool.get_ref(), user_id, path.into_inner()).await {
+        Ok(true) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Chave de API revogada com sucesso"
+        }))),
+        Ok(false) => Ok(not_found_error(
+            "Chave de API não encontrada ou já revogada",
+            "API_KEY_NOT_FOUND",
+        )),
+        Err(e) => Ok(database_error("Erro ao revogar chave de API", e)),
+    }
+}
+
+// Rotas registradas em `/users/me/api-keys` (ver `user_handler::config`)
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("", web::get().to(list_api_keys))
+        .route("", web::post().to(create_api_key))
+        .route("/{id}", web::delete().to(revoke_api_key));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiration() {
+        let now = Utc::now();
+
+        assert_eq!(expiration(now, 1), Some(now + Duration::days(1)));
+        assert_eq!(
+            expiration(now, MAX_EXPIRATION_DAYS),
+            Some(now + Duration::days(MAX_EXPIRATION_DAYS))
+        );
+        assert_eq!(expiration(now, 0), None);
+        assert_eq!(expiration(now, -1), None);
+        assert_eq!(expiration(now, MAX_EXPIRATION_DAYS + 1), None);
+
+        // Valores que estourariam `Duration::days` e a soma com a data atual
+        assert_eq!(expiration(now, i64::MAX), None);
+        assert_eq!(expiration(now, i64::MIN), None);
+        assert_eq!(expiration(DateTime::<Utc>::MAX_UTC, 1), None);
+    }
+}
@@ -1,16 +1,54 @@
-use actix_web::{web, HttpResponse, Result};
-use bcrypt::verify;
+use actix_web::{web, HttpRequest, HttpResponse, Result};
 use chrono::{Duration, Utc};
 use sqlx::PgPool;
+use uuid::Uuid;
 
-use crate::middleware::{internal_server_error, unauthorized_error};
-use crate::models::{Claims, JwtConfig, LoginRequest, LoginResponse, User, UserResponse};
+use crate::mailer::{self, Email, Locale, Mailer, MessageKind};
+use crate::middleware::{
+    bad_request_error, client_ip, forbidden_error, get_claims_from_http_request,
+    internal_server_error, password_error, password_policy_error, retry_after_error,
+    unauthorized_error, TokenError,
+};
+use crate::models::{
+    Claims, EmailVerificationConfig, EmailVerificationMode, ExpiredPasswordChangeRequest,
+    ForgotPasswordRequest, JwtConfig, LoginBlock, LoginRequest, LoginResponse, LoginThrottleConfig,
+    LogoutRequest, OrganizationRole, PasswordResetConfig, RefreshTokenRequest,
+    RefreshTokenResponse, ResendVerificationRequest, ResetPasswordRequest,
+    SwitchOrganizationRequest, ThrottleScope, TwoFactorChallengeResponse, TwoFactorConfig,
+    TwoFactorLoginRequest, User, UserResponse, VerifyEmailRequest,
+};
+use crate::password::{PasswordCheck, PasswordError, PasswordHasher, PasswordPolicy};
+use crate::services::email_verification_service::{self, VerifyOutcome};
+use crate::services::login_throttle_service;
+use crate::services::organization_service;
+use crate::services::password_history_service;
+use crate::services::password_reset_service::{self, ResetOutcome};
+use crate::services::rbac_service::{self, PermissionStore};
+use crate::services::refresh_token_service::{self, RotationOutcome};
+use crate::services::revocation_service::RevocationStore;
+use crate::services::two_factor_service::{self, ChallengeOutcome};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn login(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
+    hasher: web::Data<PasswordHasher>,
+    policy: web::Data<PasswordPolicy>,
     jwt_config: web::Data<JwtConfig>,
+    email_config: web::Data<EmailVerificationConfig>,
+    two_factor_config: web::Data<TwoFactorConfig>,
+    throttle_config: web::Data<LoginThrottleConfig>,
     login_data: web::Json<LoginRequest>,
 ) -> Result<HttpResponse> {
+    let ip = client_ip(&req).map(|ip| ip.to_string());
+
+    // Recusar tentativas de contas/IPs bloqueados ou em atraso progressivo
+    if let Some(response) =
+        check_login_throttle(&pool, &throttle_config, &login_data.email, ip.as_deref()).await
+    {
+        return Ok(response);
+    }
+
     // Buscar usuário por email
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
         .bind(&login_data.email)
@@ -20,6 +58,7 @@ pub async fn login(
     let user = match user {
         Ok(Some(user)) => user,
         Ok(None) => {
+            record_login_failure(&pool, &throttle_config, &login_data.email, ip.as_deref()).await;
             return Ok(unauthorized_error(
                 "Credenciais inválidas",
                 "INVALID_CREDENTIALS",
@@ -35,38 +74,287 @@ pub async fn login(
     };
 
     // Verificar senha
-    let password_valid = match verify(&login_data.senha, &user.senha) {
-        Ok(valid) => valid,
+    let password_check = match hasher.verify(&login_data.senha, &user.senha).await {
+        Ok(check) => check,
+        Err(e) => return Ok(password_error(&e, "PASSWORD_VERIFICATION_ERROR")),
+    };
+
+    if password_check == PasswordCheck::ValidNeedsRehash {
+        rehash_password(&pool, &hasher, &user, &login_data.senha).await;
+    }
+
+    if !password_check.is_valid() {
+        record_login_failure(&pool, &throttle_config, &login_data.email, ip.as_deref()).await;
+        return Ok(unauthorized_error(
+            "Credenciais inválidas",
+            "INVALID_CREDENTIALS",
+        ));
+    }
+
+    if policy.is_expired(user.password_changed_at, Utc::now()) {
+        return Ok(password_expired_error());
+    }
+
+    if email_config.mode == EmailVerificationMode::Required && user.email_verified_at.is_none() {
+        return Ok(forbidden_error(
+            "Confirme seu email antes de fazer login",
+            "EMAIL_NOT_VERIFIED",
+        ));
+    }
+
+    if user.totp_enabled_at.is_some() {
+        return Ok(
+            two_factor_challenge(pool.get_ref(), two_factor_config.get_ref(), user.id).await,
+        );
+    }
+
+    record_login_success(&pool, &user.email).await;
+
+    match build_login_response(
+        pool.get_ref(),
+        jwt_config.get_ref(),
+        email_config.get_ref(),
+        user,
+    )
+    .await
+    {
+        Ok(login_response) => Ok(HttpResponse::Ok().json(login_response)),
+        Err(response) => Ok(response),
+    }
+}
+
+// Com 2FA ativo, o login (por senha ou OIDC) só é concluído em /auth/login/2fa
+pub async fn two_factor_challenge(
+    pool: &PgPool,
+    two_factor_config: &TwoFactorConfig,
+    user_id: Uuid,
+) -> HttpResponse {
+    match two_factor_service::issue_challenge(
+        pool,
+        user_id,
+        two_factor_config.challenge_ttl_seconds,
+    )
+    .await
+    {
+        Ok((challenge_token, expires_at)) => HttpResponse::Ok().json(TwoFactorChallengeResponse {
+            two_factor_required: true,
+            challenge_token,
+            expires_at,
+        }),
+        Err(e) => {
+            eprintln!("Erro ao emitir desafio 2FA: {:?}", e);
+            internal_server_error("Erro interno do servidor", "DATABASE_ERROR")
+        }
+    }
+}
+
+// Segunda etapa do login: troca o desafio pelo código do autenticador ou de recuperação
+pub async fn login_two_factor(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    jwt_config: web::Data<JwtConfig>,
+    email_config: web::Data<EmailVerificationConfig>,
+    two_factor_config: web::Data<TwoFactorConfig>,
+    throttle_config: web::Data<LoginThrottleConfig>,
+    login_data: web::Json<TwoFactorLoginRequest>,
+) -> Result<HttpResponse> {
+    let outcome = two_factor_service::verify_challenge(
+        pool.get_ref(),
+        &login_data.challenge_token,
+        &login_data.code,
+        two_factor_config.max_attempts,
+    )
+    .await;
+
+    let user = match outcome {
+        Ok(ChallengeOutcome::Verified(user)) => user,
+        Ok(ChallengeOutcome::Invalid) => {
+            return Ok(unauthorized_error(
+                "Desafio de login inválido",
+                "INVALID_TWO_FACTOR_CHALLENGE",
+            ));
+        }
+        Ok(ChallengeOutcome::Expired) => {
+            return Ok(unauthorized_error(
+                "Desafio de login expirado. Faça login novamente",
+                "TWO_FACTOR_CHALLENGE_EXPIRED",
+            ));
+        }
+        Ok(ChallengeOutcome::InvalidCode { email }) => {
+            let ip = client_ip(&req).map(|ip| ip.to_string());
+            record_login_failure(&pool, &throttle_config, &email, ip.as_deref()).await;
+            return Ok(unauthorized_error(
+                "Código de autenticação inválido",
+                "INVALID_TWO_FACTOR_CODE",
+            ));
+        }
         Err(e) => {
-            eprintln!("Erro ao verificar senha: {:?}", e);
+            eprintln!("Erro ao verificar desafio 2FA: {:?}", e);
             return Ok(internal_server_error(
                 "Erro interno do servidor",
-                "PASSWORD_VERIFICATION_ERROR",
+                "DATABASE_ERROR",
             ));
         }
     };
 
-    if !password_valid {
-        return Ok(unauthorized_error(
-            "Credenciais inválidas",
-            "INVALID_CREDENTIALS",
-        ));
+    record_login_success(&pool, &user.email).await;
+
+    match build_login_response(
+        pool.get_ref(),
+        jwt_config.get_ref(),
+        email_config.get_ref(),
+        user,
+    )
+    .await
+    {
+        Ok(login_response) => Ok(HttpResponse::Ok().json(login_response)),
+        Err(response) => Ok(response),
     }
+}
 
-    // Gerar JWT token
-    let claims = Claims::new(
-        user.id,
-        user.email.clone(),
-        user.nome.clone(),
-        user.role.clone(),
-        jwt_config.expires_in_seconds,
-    );
+// Atualiza para o algoritmo/parâmetros atuais o hash de uma senha recém-verificada.
+// Falhas são apenas registradas: o login continua válido com o hash antigo.
+async fn rehash_password(pool: &PgPool, hasher: &PasswordHasher, user: &User, password: &str) {
+    let new_hash = match hasher.hash(password).await {
+        Ok(hash) => hash,
+        // Sob carga, a atualização fica para um próximo login
+        Err(PasswordError::Busy { .. }) => return,
+        Err(e) => {
+            eprintln!("Erro ao atualizar hash da senha: {:?}", e);
+            return;
+        }
+    };
+
+    // A condição sobre o hash antigo evita sobrescrever uma troca de senha concorrente
+    let result = sqlx::query("UPDATE users SET senha = $1 WHERE id = $2 AND senha = $3")
+        .bind(&new_hash)
+        .bind(user.id)
+        .bind(&user.senha)
+        .execute(pool)
+        .await;
+
+    if let Err(e) = result {
+        eprintln!("Erro ao atualizar hash da senha: {:?}", e);
+    }
+}
+
+// Resposta de bloqueio quando a conta ou o IP não podem tentar login agora
+async fn check_login_throttle(
+    pool: &PgPool,
+    throttle_config: &LoginThrottleConfig,
+    email: &str,
+    ip: Option<&str>,
+) -> Option<HttpResponse> {
+    match login_throttle_service::check(pool, throttle_config, email, ip).await {
+        Ok(Some(block)) => Some(login_blocked_error(&block)),
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Erro ao verificar tentativas de login: {:?}", e);
+            Some(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ))
+        }
+    }
+}
+
+// Falhas no registro de tentativas não impedem a resposta do login
+async fn record_login_failure(
+    pool: &PgPool,
+    throttle_config: &LoginThrottleConfig,
+    email: &str,
+    ip: Option<&str>,
+) {
+    if let Err(e) = login_throttle_service::record_failure(pool, throttle_config, email, ip).await {
+        eprintln!("Erro ao registrar falha de login: {:?}", e);
+    }
+}
+
+async fn record_login_success(pool: &PgPool, email: &str) {
+    if let Err(e) = login_throttle_service::record_success(pool, email).await {
+        eprintln!("Erro ao limpar falhas de login: {:?}", e);
+    }
+}
+
+fn login_blocked_error(block: &LoginBlock) -> HttpResponse {
+    let retry_after = block.retry_after_seconds(Utc::now());
+
+    match block {
+        LoginBlock::Locked {
+            scope: ThrottleScope::Account,
+            ..
+        } => retry_after_error(
+            423,
+            "Locked",
+            &format!(
+                "Conta bloqueada temporariamente por excesso de tentativas de login. Tente novamente em {} segundos",
+                retry_after
+            ),
+            "ACCOUNT_LOCKED",
+            retry_after,
+        ),
+        _ => retry_after_error(
+            429,
+            "Too Many Requests",
+            &format!(
+                "Muitas tentativas de login. Tente novamente em {} segundos",
+                retry_after
+            ),
+            "TOO_MANY_LOGIN_ATTEMPTS",
+            retry_after,
+        ),
+    }
+}
+
+// Gera o access token JWT e inicia uma nova família de refresh tokens, com a
+// organização mais antiga do usuário como organização ativa
+pub async fn build_login_response(
+    pool: &PgPool,
+    jwt_config: &JwtConfig,
+    email_config: &EmailVerificationConfig,
+    user: User,
+) -> std::result::Result<LoginResponse, HttpResponse> {
+    let membership = match organization_service::default_membership(pool, user.id).await {
+        Ok(membership) => membership,
+        Err(e) => {
+            eprintln!("Erro ao buscar organizações do usuário: {:?}", e);
+            return Err(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    start_session(pool, jwt_config, email_config, user, membership).await
+}
+
+// Access token e nova família de refresh tokens com a organização ativa informada
+async fn start_session(
+    pool: &PgPool,
+    jwt_config: &JwtConfig,
+    email_config: &EmailVerificationConfig,
+    user: User,
+    membership: Option<(Uuid, OrganizationRole)>,
+) -> std::result::Result<LoginResponse, HttpResponse> {
+    let roles = match rbac_service::roles_for_user(pool, user.id).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            eprintln!("Erro ao buscar roles do usuário: {:?}", e);
+            return Err(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    let claims =
+        user_claims(&user, roles.clone(), jwt_config, email_config).with_organization(membership);
 
     let token = match jwt_config.generate_token(&claims) {
         Ok(token) => token,
         Err(e) => {
             eprintln!("Erro ao gerar token: {:?}", e);
-            return Ok(internal_server_error(
+            return Err(internal_server_error(
                 "Erro interno do servidor",
                 "TOKEN_GENERATION_ERROR",
             ));
@@ -76,28 +364,187 @@ pub async fn login(
     // Calcular data de expiração
     let expires_at = Utc::now() + Duration::seconds(jwt_config.expires_in_seconds);
 
+    let organization_id = membership.map(|(id, _)| id);
+    let (refresh_token, refresh_record) = match refresh_token_service::issue(
+        pool,
+        user.id,
+        organization_id,
+        None,
+        jwt_config.refresh_expires_in_seconds,
+    )
+    .await
+    {
+        Ok(issued) => issued,
+        Err(e) => {
+            eprintln!("Erro ao gerar refresh token: {:?}", e);
+            return Err(internal_server_error(
+                "Erro interno do servidor",
+                "TOKEN_GENERATION_ERROR",
+            ));
+        }
+    };
+
     // Preparar resposta (sem a senha)
-    let user_response = UserResponse::from(user);
-    let login_response = LoginResponse {
-        user: user_response,
+    Ok(LoginResponse {
+        user: UserResponse::new(user, roles),
         token,
         expires_at,
+        refresh_token,
+        refresh_expires_at: refresh_record.expires_at,
+        organization_id,
+    })
+}
+
+// Claims do access token. Com `EmailVerificationMode::Restricted`, contas com
+// email não verificado recebem um token restrito.
+pub fn user_claims(
+    user: &User,
+    roles: Vec<String>,
+    jwt_config: &JwtConfig,
+    email_config: &EmailVerificationConfig,
+) -> Claims {
+    let mut claims = Claims::new(
+        user.id,
+        user.email.clone(),
+        user.nome.clone(),
+        roles,
+        user.token_version,
+        jwt_config.expires_in_seconds,
+    );
+    claims.email_verified =
+        email_config.mode == EmailVerificationMode::Off || user.email_verified_at.is_some();
+    claims.must_change_password = user.must_change_password;
+    claims
+}
+
+// Organização da sessão renovada; sem vínculo com ela, volta para a organização padrão
+pub async fn session_membership(
+    pool: &PgPool,
+    user_id: Uuid,
+    organization_id: Option<Uuid>,
+) -> std::result::Result<Option<(Uuid, OrganizationRole)>, sqlx::Error> {
+    if let Some(organization_id) = organization_id {
+        if let Some(role) = organization_service::role_of(pool, organization_id, user_id).await? {
+            return Ok(Some((organization_id, role)));
+        }
+    }
+    organization_service::default_membership(pool, user_id).await
+}
+
+// Troca a organização ativa: emite um novo access token e uma nova sessão de refresh
+pub async fn switch_organization(
+    pool: web::Data<PgPool>,
+    jwt_config: web::Data<JwtConfig>,
+    email_config: web::Data<EmailVerificationConfig>,
+    switch_data: web::Json<SwitchOrganizationRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(claims) = get_claims_from_http_request(&req) else {
+        return Ok(unauthorized_error(
+            "Token JWT não encontrado",
+            "TOKEN_MISSING",
+        ));
+    };
+
+    // Chaves de API e tokens OAuth ficam presos à organização em que foram emitidos
+    if claims.is_delegated() {
+        return Ok(forbidden_error(
+            "Chaves de API e tokens OAuth não podem trocar de organização",
+            "API_KEY_NOT_ALLOWED",
+        ));
+    }
+
+    let user_id = claims.get_user_id().unwrap_or_default();
+    let organization_id = switch_data.organization_id;
+
+    let role = match organization_service::role_of(pool.get_ref(), organization_id, user_id).await {
+        Ok(Some(role)) => role,
+        Ok(None) => {
+            return Ok(forbidden_error(
+                "Você não participa desta organização",
+                "NOT_ORGANIZATION_MEMBER",
+            ));
+        }
+        Err(e) => {
+            eprintln!("Erro ao buscar vínculo com a organização: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
     };
 
-    Ok(HttpResponse::Ok().json(login_response))
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool.get_ref())
+        .await;
+
+    let user = match user {
+        Ok(Some(user)) => user,
+        Ok(None) => return Ok(unauthorized_error("Token inválido", "INVALID_TOKEN")),
+        Err(e) => {
+            eprintln!("Erro ao buscar usuário: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    match start_session(
+        pool.get_ref(),
+        jwt_config.get_ref(),
+        email_config.get_ref(),
+        user,
+        Some((organization_id, role)),
+    )
+    .await
+    {
+        Ok(login_response) => Ok(HttpResponse::Ok().json(login_response)),
+        Err(response) => Ok(response),
+    }
 }
 
 // Endpoint para verificar token (opcional)
 pub async fn verify_token(
     jwt_config: web::Data<JwtConfig>,
+    revocation_store: web::Data<RevocationStore>,
+    permission_store: web::Data<PermissionStore>,
     token: web::Path<String>,
 ) -> Result<HttpResponse> {
     let token = token.into_inner();
 
     match jwt_config.verify_token(&token) {
-        Ok(claims) => {
-            if claims.is_expired() {
-                return Ok(unauthorized_error("Token expirado", "TOKEN_EXPIRED"));
+        Ok(mut claims) => {
+            match revocation_store.is_revoked(&claims).await {
+                Ok(false) => {}
+                Ok(true) => return Ok(unauthorized_error("Token revogado", "TOKEN_REVOKED")),
+                Err(e) => {
+                    eprintln!("Erro ao verificar revogação do token: {:?}", e);
+                    return Ok(internal_server_error(
+                        "Erro interno do servidor",
+                        "DATABASE_ERROR",
+                    ));
+                }
+            }
+
+            // Roles e permissões atuais, como vistas pelo middleware
+            let user_id = claims.get_user_id().unwrap_or_default();
+            match permission_store.access(user_id).await {
+                Ok(access) => {
+                    claims.roles = access.roles.clone();
+                    claims.permissions = access.permissions.clone();
+                    claims.org_role = claims.get_organization_id().and_then(|organization_id| {
+                        access.organizations.get(&organization_id).copied()
+                    });
+                }
+                Err(e) => {
+                    eprintln!("Erro ao carregar permissões do usuário: {:?}", e);
+                    return Ok(internal_server_error(
+                        "Erro interno do servidor",
+                        "DATABASE_ERROR",
+                    ));
+                }
             }
 
             Ok(HttpResponse::Ok().json(serde_json::json!({
@@ -105,68 +552,661 @@ pub async fn verify_token(
                 "user_id": claims.sub,
                 "email": claims.email,
                 "nome": claims.nome,
-                "role": claims.role,
+                "roles": claims.roles,
+                "permissions": claims.effective_permissions(),
+                "organization_id": claims.org_id,
+                "organization_role": claims.org_role,
                 "expires_at": claims.exp
             })))
         }
         Err(e) => {
             eprintln!("Erro ao verificar token: {:?}", e);
-            Ok(unauthorized_error("Token inválido", "INVALID_TOKEN"))
+            let error = TokenError::from(&e);
+            Ok(unauthorized_error(error.message(), error.code()))
         }
     }
 }
 
-// Endpoint para refresh token (opcional)
+// Troca um refresh token por um novo access token, rotacionando o refresh token
 pub async fn refresh_token(
+    pool: web::Data<PgPool>,
     jwt_config: web::Data<JwtConfig>,
-    old_token: web::Path<String>,
+    email_config: web::Data<EmailVerificationConfig>,
+    refresh_data: web::Json<RefreshTokenRequest>,
 ) -> Result<HttpResponse> {
-    let token = old_token.into_inner();
+    let outcome = refresh_token_service::rotate(
+        pool.get_ref(),
+        &refresh_data.refresh_token,
+        jwt_config.refresh_expires_in_seconds,
+    )
+    .await;
 
-    match jwt_config.verify_token(&token) {
-        Ok(claims) => {
-            // Gerar novo token com os mesmos dados mas nova expiração
-            let new_claims = Claims::new(
-                claims.get_user_id().unwrap_or_default(),
-                claims.email,
-                claims.nome,
-                claims.role,
-                jwt_config.expires_in_seconds,
-            );
-
-            match jwt_config.generate_token(&new_claims) {
-                Ok(new_token) => {
-                    let expires_at = Utc::now() + Duration::seconds(jwt_config.expires_in_seconds);
-
-                    Ok(HttpResponse::Ok().json(serde_json::json!({
-                        "token": new_token,
-                        "expires_at": expires_at
-                    })))
-                }
-                Err(e) => {
-                    eprintln!("Erro ao gerar novo token: {:?}", e);
-                    Ok(internal_server_error(
-                        "Erro interno do servidor",
-                        "TOKEN_GENERATION_ERROR",
-                    ))
-                }
-            }
+    let (user_id, organization_id, refresh_token, refresh_expires_at) = match outcome {
+        Ok(RotationOutcome::Rotated {
+            user_id,
+            organization_id,
+            token,
+            expires_at,
+        }) => (user_id, organization_id, token, expires_at),
+        Ok(RotationOutcome::Expired) => {
+            return Ok(unauthorized_error(
+                "Refresh token expirado",
+                "REFRESH_TOKEN_EXPIRED",
+            ));
+        }
+        Ok(RotationOutcome::Reused) => {
+            eprintln!("Reuso de refresh token detectado; família revogada");
+            return Ok(unauthorized_error(
+                "Refresh token já utilizado. Todas as sessões derivadas foram revogadas.",
+                "REFRESH_TOKEN_REUSED",
+            ));
+        }
+        Ok(RotationOutcome::NotFound) | Ok(RotationOutcome::Revoked) => {
+            return Ok(unauthorized_error(
+                "Token inválido para refresh",
+                "INVALID_REFRESH_TOKEN",
+            ));
         }
         Err(e) => {
-            eprintln!("Erro ao verificar token para refresh: {:?}", e);
-            Ok(unauthorized_error(
+            eprintln!("Erro ao rotacionar refresh token: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    // Buscar dados atuais do usuário para o novo access token
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool.get_ref())
+        .await;
+
+    let user = match user {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return Ok(unauthorized_error(
                 "Token inválido para refresh",
                 "INVALID_REFRESH_TOKEN",
+            ));
+        }
+        Err(e) => {
+            eprintln!("Erro ao buscar usuário: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    let roles = match rbac_service::roles_for_user(pool.get_ref(), user.id).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            eprintln!("Erro ao buscar roles do usuário: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    // Mantém a organização da sessão enquanto o usuário participar dela
+    let membership = match session_membership(pool.get_ref(), user.id, organization_id).await {
+        Ok(membership) => membership,
+        Err(e) => {
+            eprintln!("Erro ao buscar organizações do usuário: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    let claims = user_claims(&user, roles, jwt_config.get_ref(), email_config.get_ref())
+        .with_organization(membership);
+
+    match jwt_config.generate_token(&claims) {
+        Ok(token) => {
+            let expires_at = Utc::now() + Duration::seconds(jwt_config.expires_in_seconds);
+
+            Ok(HttpResponse::Ok().json(RefreshTokenResponse {
+                token,
+                expires_at,
+                refresh_token,
+                refresh_expires_at,
+                organization_id: membership.map(|(id, _)| id),
+            }))
+        }
+        Err(e) => {
+            eprintln!("Erro ao gerar novo token: {:?}", e);
+            Ok(internal_server_error(
+                "Erro interno do servidor",
+                "TOKEN_GENERATION_ERROR",
+            ))
+        }
+    }
+}
+
+// Logout da sessão atual: revoga o JWT apresentado e, se informado, o refresh token
+pub async fn logout(
+    pool: web::Data<PgPool>,
+    revocation_store: web::Data<RevocationStore>,
+    logout_data: Option<web::Json<LogoutRequest>>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let claims = match get_claims_from_http_request(&req) {
+        Some(claims) => claims,
+        None => {
+            return Ok(unauthorized_error(
+                "Token JWT não encontrado",
+                "TOKEN_MISSING",
+            ));
+        }
+    };
+
+    // Uma chave de API não é uma sessão: ela é revogada em `/users/me/api-keys`
+    if claims.api_key_id.is_some() {
+        return Ok(forbidden_error(
+            "Chaves de API não fazem logout; revogue a chave em /users/me/api-keys",
+            "API_KEY_NOT_ALLOWED",
+        ));
+    }
+
+    if let Err(e) = revocation_store.revoke_token(&claims).await {
+        eprintln!("Erro ao revogar token: {:?}", e);
+        return Ok(internal_server_error(
+            "Erro interno do servidor",
+            "DATABASE_ERROR",
+        ));
+    }
+
+    if let Some(refresh_token) = logout_data.and_then(|data| data.into_inner().refresh_token) {
+        let user_id = claims.get_user_id().unwrap_or_default();
+        if let Err(e) =
+            refresh_token_service::revoke_family(pool.get_ref(), user_id, &refresh_token).await
+        {
+            eprintln!("Erro ao revogar refresh token: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Logout realizado com sucesso"
+    })))
+}
+
+// Logout de todas as sessões do usuário logado
+pub async fn logout_all(
+    revocation_store: web::Data<RevocationStore>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    // Encerrar as sessões do usuário exige o login dele, não uma credencial delegada
+    if get_claims_from_http_request(&req).is_some_and(|claims| claims.is_delegated()) {
+        return Ok(forbidden_error(
+            "Chaves de API e tokens OAuth não podem encerrar as sessões do usuário",
+            "API_KEY_NOT_ALLOWED",
+        ));
+    }
+
+    let user_id = match get_claims_from_http_request(&req).map(|claims| claims.get_user_id()) {
+        Some(Ok(user_id)) => user_id,
+        Some(Err(_)) => {
+            return Ok(bad_request_error(
+                "ID de usuário inválido no token",
+                "INVALID_USER_ID",
+            ));
+        }
+        None => {
+            return Ok(unauthorized_error(
+                "Token JWT não encontrado",
+                "TOKEN_MISSING",
+            ));
+        }
+    };
+
+    match revocation_store.revoke_all_for_user(user_id).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Todas as sessões foram encerradas"
+        }))),
+        Err(e) => {
+            eprintln!("Erro ao revogar sessões: {:?}", e);
+            Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
             ))
         }
     }
 }
 
+// Solicita a redefinição de senha. A resposta é sempre a mesma e o envio ocorre
+// em segundo plano, para não revelar (nem pelo tempo de resposta) quais emails existem.
+pub async fn forgot_password(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    reset_config: web::Data<PasswordResetConfig>,
+    mailer: web::Data<dyn Mailer>,
+    forgot_data: web::Json<ForgotPasswordRequest>,
+) -> Result<HttpResponse> {
+    let pool = pool.get_ref().clone();
+    let reset_config = reset_config.get_ref().clone();
+    let mailer = mailer.into_inner();
+    let email = forgot_data.into_inner().email;
+    let locale = Locale::from_request(&req);
+
+    actix_web::rt::spawn(async move {
+        let issued =
+            password_reset_service::issue(&pool, &email, reset_config.token_ttl_seconds).await;
+
+        match issued {
+            Ok(Some((user, token))) => {
+                let message = password_reset_email(&user, token, &reset_config, locale);
+                if let Err(e) = mailer.send(message).await {
+                    eprintln!("Erro ao enviar email de redefinição de senha: {:?}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Erro ao emitir token de redefinição de senha: {:?}", e),
+        }
+    });
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Se o email estiver cadastrado, você receberá as instruções para redefinir a senha"
+    })))
+}
+
+fn password_reset_email(
+    user: &User,
+    token: String,
+    reset_config: &PasswordResetConfig,
+    locale: Locale,
+) -> Email {
+    let action = reset_config.reset_link(&token).unwrap_or(token);
+    let expires_in = locale.format_duration(reset_config.token_ttl_seconds);
+
+    mailer::render(
+        MessageKind::PasswordReset,
+        locale,
+        &user.email,
+        &[
+            ("nome", &user.nome),
+            ("action", &action),
+            ("expires_in", &expires_in),
+        ],
+    )
+}
+
+// Define uma nova senha a partir do token recebido por email e encerra todas as sessões
+pub async fn reset_password(
+    pool: web::Data<PgPool>,
+    hasher: web::Data<PasswordHasher>,
+    policy: web::Data<PasswordPolicy>,
+    revocation_store: web::Data<RevocationStore>,
+    reset_data: web::Json<ResetPasswordRequest>,
+) -> Result<HttpResponse> {
+    // Token inválido ou expirado segue adiante e é recusado por `reset`
+    let user = match password_reset_service::find_user(pool.get_ref(), &reset_data.token).await {
+        Ok(user) => user,
+        Err(e) => {
+            eprintln!("Erro ao buscar token de redefinição: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    if let Some(ref user) = user {
+        if let Err(response) =
+            check_new_password(&pool, &hasher, &policy, user, &reset_data.senha_nova).await
+        {
+            return Ok(response);
+        }
+    }
+
+    let password_hash = match hasher.hash(&reset_data.senha_nova).await {
+        Ok(hashed) => hashed,
+        Err(e) => return Ok(password_error(&e, "PASSWORD_HASH_ERROR")),
+    };
+
+    let outcome =
+        password_reset_service::reset(pool.get_ref(), &policy, &reset_data.token, &password_hash)
+            .await;
+
+    let (user_id, token_version) = match outcome {
+        Ok(ResetOutcome::Reset {
+            user_id,
+            token_version,
+        }) => (user_id, token_version),
+        Ok(ResetOutcome::Invalid) => {
+            return Ok(bad_request_error(
+                "Token de redefinição inválido ou já utilizado",
+                "INVALID_RESET_TOKEN",
+            ))
+        }
+        Ok(ResetOutcome::Expired) => {
+            return Ok(bad_request_error(
+                "Token de redefinição expirado",
+                "RESET_TOKEN_EXPIRED",
+            ))
+        }
+        Err(e) => {
+            eprintln!("Erro ao redefinir senha: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    // Sem a revogação, as sessões abertas com a senha antiga continuariam válidas
+    if let Err(e) = revocation_store
+        .token_version_changed(user_id, token_version)
+        .await
+    {
+        eprintln!("Erro ao revogar sessões: {:?}", e);
+        return Ok(internal_server_error(
+            "Erro interno do servidor",
+            "DATABASE_ERROR",
+        ));
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Senha redefinida com sucesso. Faça login novamente."
+    })))
+}
+
+fn password_expired_error() -> HttpResponse {
+    forbidden_error(
+        "Senha expirada. Defina uma nova senha em /api/v1/auth/password/expired",
+        "PASSWORD_EXPIRED",
+    )
+}
+
+// Valida a nova senha contra a política e o histórico do usuário
+async fn check_new_password(
+    pool: &PgPool,
+    hasher: &PasswordHasher,
+    policy: &PasswordPolicy,
+    user: &User,
+    password: &str,
+) -> std::result::Result<(), HttpResponse> {
+    match password_history_service::validate_new_password(
+        pool,
+        hasher,
+        policy,
+        user,
+        password,
+        &user.nome,
+        &user.email,
+    )
+    .await
+    {
+        Ok(violations) if violations.is_empty() => Ok(()),
+        Ok(violations) => Err(password_policy_error(&violations)),
+        Err(e) => Err(password_error(&e, "PASSWORD_VERIFICATION_ERROR")),
+    }
+}
+
+// Troca uma senha expirada sem token de acesso. Exige as mesmas credenciais do
+// login (e o código 2FA, se ativo); falhas contam para o bloqueio de login.
+#[allow(clippy::too_many_arguments)]
+pub async fn change_expired_password(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    hasher: web::Data<PasswordHasher>,
+    policy: web::Data<PasswordPolicy>,
+    revocation_store: web::Data<RevocationStore>,
+    throttle_config: web::Data<LoginThrottleConfig>,
+    change_data: web::Json<ExpiredPasswordChangeRequest>,
+) -> Result<HttpResponse> {
+    let ip = client_ip(&req).map(|ip| ip.to_string());
+
+    if let Some(response) =
+        check_login_throttle(&pool, &throttle_config, &change_data.email, ip.as_deref()).await
+    {
+        return Ok(response);
+    }
+
+    let user = match sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
+        .bind(&change_data.email)
+        .fetch_optional(pool.get_ref())
+        .await
+    {
+        Ok(user) => user,
+        Err(e) => {
+            eprintln!("Erro ao buscar usuário: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    let password_valid = match &user {
+        Some(user) => match hasher.verify(&change_data.senha_atual, &user.senha).await {
+            Ok(check) => check.is_valid(),
+            Err(e) => return Ok(password_error(&e, "PASSWORD_VERIFICATION_ERROR")),
+        },
+        None => false,
+    };
+
+    let user = match user {
+        Some(user) if password_valid => user,
+        _ => {
+            record_login_failure(&pool, &throttle_config, &change_data.email, ip.as_deref()).await;
+            return Ok(unauthorized_error(
+                "Credenciais inválidas",
+                "INVALID_CREDENTIALS",
+            ));
+        }
+    };
+
+    if !policy.is_expired(user.password_changed_at, Utc::now()) {
+        return Ok(bad_request_error(
+            "A senha não está expirada. Use /api/v1/users/{id}/change-password",
+            "PASSWORD_NOT_EXPIRED",
+        ));
+    }
+
+    if user.totp_enabled_at.is_some() {
+        let code = match change_data.code.as_deref() {
+            Some(code) => code,
+            None => {
+                return Ok(bad_request_error(
+                    "Informe o código do autenticador ou de recuperação",
+                    "TWO_FACTOR_CODE_REQUIRED",
+                ));
+            }
+        };
+
+        match two_factor_service::verify_code(pool.get_ref(), user.id, code).await {
+            Ok(true) => {}
+            Ok(false) => {
+                record_login_failure(&pool, &throttle_config, &user.email, ip.as_deref()).await;
+                return Ok(unauthorized_error(
+                    "Código de autenticação inválido",
+                    "INVALID_TWO_FACTOR_CODE",
+                ));
+            }
+            Err(e) => {
+                eprintln!("Erro ao verificar código 2FA: {:?}", e);
+                return Ok(internal_server_error(
+                    "Erro interno do servidor",
+                    "DATABASE_ERROR",
+                ));
+            }
+        }
+    }
+
+    if let Err(response) =
+        check_new_password(&pool, &hasher, &policy, &user, &change_data.senha_nova).await
+    {
+        return Ok(response);
+    }
+
+    let password_hash = match hasher.hash(&change_data.senha_nova).await {
+        Ok(hashed) => hashed,
+        Err(e) => return Ok(password_error(&e, "PASSWORD_HASH_ERROR")),
+    };
+
+    let result =
+        password_history_service::change_password(&pool, &policy, user.id, &password_hash).await;
+
+    let token_version = match result {
+        Ok(token_version) => token_version,
+        Err(e) => {
+            eprintln!("Erro ao alterar senha expirada: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro ao alterar senha",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    if let Err(e) = revocation_store
+        .token_version_changed(user.id, token_version)
+        .await
+    {
+        eprintln!("Erro ao revogar sessões: {:?}", e);
+        return Ok(internal_server_error(
+            "Erro interno do servidor",
+            "DATABASE_ERROR",
+        ));
+    }
+
+    record_login_success(&pool, &user.email).await;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Senha alterada com sucesso. Faça login com a nova senha."
+    })))
+}
+
+// Confirma o email com o token enviado no cadastro
+pub async fn verify_email(
+    pool: web::Data<PgPool>,
+    verify_data: web::Json<VerifyEmailRequest>,
+) -> Result<HttpResponse> {
+    match email_verification_service::verify(pool.get_ref(), &verify_data.token).await {
+        Ok(VerifyOutcome::Verified) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Email verificado com sucesso. Renove o token em /auth/refresh para acesso completo."
+        }))),
+        Ok(VerifyOutcome::Invalid) => Ok(bad_request_error(
+            "Token de verificação inválido ou já utilizado",
+            "INVALID_VERIFICATION_TOKEN",
+        )),
+        Ok(VerifyOutcome::Expired) => Ok(bad_request_error(
+            "Token de verificação expirado",
+            "VERIFICATION_TOKEN_EXPIRED",
+        )),
+        Err(e) => {
+            eprintln!("Erro ao verificar email: {:?}", e);
+            Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ))
+        }
+    }
+}
+
+// Reenvia o email de verificação. Assim como `forgot_password`, responde sempre
+// da mesma forma e envia em segundo plano.
+pub async fn resend_verification(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    email_config: web::Data<EmailVerificationConfig>,
+    mailer: web::Data<dyn Mailer>,
+    resend_data: web::Json<ResendVerificationRequest>,
+) -> Result<HttpResponse> {
+    let pool = pool.get_ref().clone();
+    let email_config = email_config.get_ref().clone();
+    let mailer = mailer.into_inner();
+    let email = resend_data.into_inner().email;
+    let locale = Locale::from_request(&req);
+
+    actix_web::rt::spawn(async move {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE email = $1 AND email_verified_at IS NULL",
+        )
+        .bind(&email)
+        .fetch_optional(&pool)
+        .await;
+
+        match user {
+            Ok(Some(user)) => {
+                if let Err(e) = email_verification_service::send_verification_email(
+                    &pool,
+                    mailer.as_ref(),
+                    &email_config,
+                    &user,
+                    locale,
+                )
+                .await
+                {
+                    eprintln!("Erro ao enviar email de verificação: {:?}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Erro ao buscar usuário: {:?}", e),
+        }
+    });
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Se o email estiver cadastrado e não verificado, você receberá um novo link de confirmação"
+    })))
+}
+
+// Chaves públicas para validação dos JWTs por outros serviços
+pub async fn jwks(jwt_config: web::Data<JwtConfig>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(jwt_config.jwks()))
+}
+
+pub fn well_known_config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/.well-known/jwks.json", web::get().to(jwks));
+}
+
 pub fn config(cfg: &mut web::ServiceConfig) {
+    use crate::handlers::oidc_handler;
+    use crate::middleware::{jwt_validator, unverified_jwt_validator};
+    use actix_web_httpauth::middleware::HttpAuthentication;
+
     cfg.service(
         web::scope("/auth")
             .route("/login", web::post().to(login))
+            .route("/login/2fa", web::post().to(login_two_factor))
+            .route("/oidc/login", web::get().to(oidc_handler::oidc_login))
+            .route(
+                "/oidc/callback",
+                web::post().to(oidc_handler::oidc_callback),
+            )
             .route("/verify/{token}", web::get().to(verify_token))
-            .route("/refresh/{token}", web::post().to(refresh_token)),
+            .route("/refresh", web::post().to(refresh_token))
+            .route("/password/forgot", web::post().to(forgot_password))
+            .route("/password/reset", web::post().to(reset_password))
+            .route("/password/expired", web::post().to(change_expired_password))
+            .route("/email/verify", web::post().to(verify_email))
+            .route("/email/resend", web::post().to(resend_verification))
+            .route(
+                "/logout",
+                web::post()
+                    .to(logout)
+                    .wrap(HttpAuthentication::with_fn(unverified_jwt_validator)),
+            )
+            .route(
+                "/switch-organization",
+                web::post()
+                    .to(switch_organization)
+                    .wrap(HttpAuthentication::with_fn(jwt_validator)),
+            )
+            .route(
+                "/logout-all",
+                web::post()
+                    .to(logout_all)
+                    .wrap(HttpAuthentication::with_fn(unverified_jwt_validator)),
+            ),
     );
 }
@@ -1,2 +1,9 @@
+pub mod admin_handler;
+pub mod api_key_handler;
 pub mod auth_handler;
+pub mod oauth_handler;
+pub mod oidc_handler;
+pub mod organization_handler;
+pub mod rbac_handler;
+pub mod two_factor_handler;
 pub mod user_handler;
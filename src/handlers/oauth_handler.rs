@@ -0,0 +1,746 @@
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use actix_web_httpauth::extractors::basic::BasicAuth;
+use sqlx::PgPool;
+use url::Url;
+use uuid::Uuid;
+
+use crate::handlers::auth_handler::{session_membership, user_claims};
+use crate::middleware::{
+    bad_request_error, forbidden_error, get_claims_from_http_request, internal_server_error,
+    not_found_error, unauthorized_error,
+};
+use crate::models::{
+    valid_code_challenge, valid_code_verifier, valid_redirect_uri, AuthorizeDecisionRequest,
+    AuthorizeRedirectResponse, AuthorizeRequest, AuthorizeResponse, CreateOAuthClientRequest,
+    CreatedOAuthClientResponse, EmailVerificationConfig, JwtConfig, OAuthClient,
+    OAuthClientResponse, OAuthClientType, OAuthConfig, OrganizationRole, TokenRequest,
+    TokenResponse, User, GRANT_AUTHORIZATION_CODE, GRANT_CLIENT_CREDENTIALS, GRANT_TYPES,
+    PERMISSION_USERS_ADMIN,
+};
+use crate::services::oauth_service::{
+    self, AuthorizationGrant, CodeOutcome, NewClient, OAuthClientStore,
+};
+use crate::services::{organization_service, rbac_service};
+
+const MAX_NAME_LENGTH: usize = 100;
+
+fn database_error(context: &str, e: sqlx::Error) -> HttpResponse {
+    eprintln!("{}: {:?}", context, e);
+    internal_server_error("Erro interno do servidor", "DATABASE_ERROR")
+}
+
+// Chaves de API e tokens OAuth não podem registrar clientes nem conceder autorizações
+fn delegated_credential_error(message: &str) -> HttpResponse {
+    forbidden_error(message, "API_KEY_NOT_ALLOWED")
+}
+
+// Clientes OAuth registrados, incluindo os revogados (requer admin)
+pub async fn list_clients(pool: web::Data<PgPool>) -> Result<HttpResponse> {
+    match oauth_service::list_clients(pool.get_ref()).await {
+        Ok(clients) => Ok(HttpResponse::Ok().json(
+            clients
+                .into_iter()
+                .map(OAuthClientResponse::from)
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => Ok(database_error("Erro ao listar clientes OAuth", e)),
+    }
+}
+
+// Registra um cliente OAuth (requer admin). O client_secret de clientes
+// confidenciais só é exibido nesta resposta.
+pub async fn create_client(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    client_data: web::Json<CreateOAuthClientRequest>,
+) -> Result<HttpResponse> {
+    let Some((claims, admin_id)) = get_claims_from_http_request(&req)
+        .and_then(|claims| claims.get_user_id().ok().map(|id| (claims, id)))
+    else {
+        return Ok(unauthorized_error(
+            "Token JWT não encontrado",
+            "TOKEN_MISSING",
+        ));
+    };
+
+    if claims.is_delegated() {
+        return Ok(delegated_credential_error(
+            "Clientes OAuth só podem ser registrados com login por senha",
+        ));
+    }
+
+    let client_data = client_data.into_inner();
+
+    let name = client_data.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Ok(bad_request_error(
+            &format!("O nome deve ter entre 1 e {} caracteres", MAX_NAME_LENGTH),
+            "INVALID_CLIENT_NAME",
+        ));
+    }
+
+    let grant_types = dedup(client_data.grant_types);
+    if grant_types.is_empty() {
+        return Ok(bad_request_error(
+            "Informe ao menos um grant_type",
+            "INVALID_GRANT_TYPE",
+        ));
+    }
+    if let Some(unknown) = grant_types
+        .iter()
+        .find(|grant| !GRANT_TYPES.contains(&grant.as_str()))
+    {
+        return Ok(bad_request_error(
+            &format!(
+                "grant_type não suportado: {} (use {})",
+                unknown,
+                GRANT_TYPES.join(" ou ")
+            ),
+            "INVALID_GRANT_TYPE",
+        ));
+    }
+
+    let uses_client_credentials = grant_types.iter().any(|g| g == GRANT_CLIENT_CREDENTIALS);
+    let uses_authorization_code = grant_types.iter().any(|g| g == GRANT_AUTHORIZATION_CODE);
+
+    // Sem um segredo, qualquer um com o client_id obteria tokens em nome da conta do cliente
+    if uses_client_credentials && client_data.client_type == OAuthClientType::Public {
+        return Ok(bad_request_error(
+            "Clientes públicos não podem usar o grant client_credentials",
+            "INVALID_GRANT_TYPE",
+        ));
+    }
+
+    let redirect_uris = dedup(client_data.redirect_uris);
+    if uses_authorization_code && redirect_uris.is_empty() {
+        return Ok(bad_request_error(
+            "O grant authorization_code exige ao menos um redirect_uri",
+            "INVALID_REDIRECT_URI",
+        ));
+    }
+    if let Some(invalid) = redirect_uris.iter().find(|uri| !valid_redirect_uri(uri)) {
+        return Ok(bad_request_error(
+            &format!(
+                "redirect_uri deve ser uma URL absoluta sem fragmento: {}",
+                invalid
+            ),
+            "INVALID_REDIRECT_URI",
+        ));
+    }
+
+    // Escopos são nomes de permissões e limitam todos os tokens do cliente
+    let scopes = dedup(client_data.scopes);
+    if scopes.is_empty() {
+        return Ok(bad_request_error(
+            "Informe ao menos um escopo",
+            "INVALID_SCOPE",
+        ));
+    }
+    match rbac_service::unknown_permissions(pool.get_ref(), &scopes).await {
+        Ok(unknown) if !unknown.is_empty() => {
+            return Ok(bad_request_error(
+                &format!("Escopo desconhecido: {}", unknown.join(", ")),
+                "INVALID_SCOPE",
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => return Ok(database_error("Erro ao validar escopos", e)),
+    }
+
+    // Conta de serviço usada pelo client_credentials; sem esse grant, o cliente age
+    // sempre em nome do usuário que concedeu a autorização
+    let user_id = if uses_client_credentials {
+        let Some(user_id) = client_data.user_id else {
+            return Ok(bad_request_error(
+                "O grant client_credentials exige o user_id de uma conta de serviço",
+                "SERVICE_ACCOUNT_REQUIRED",
+            ));
+        };
+
+        let exists =
+            sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+                .bind(user_id)
+                .fetch_one(pool.get_ref())
+                .await;
+
+        match exists {
+            Ok(true) => {}
+            Ok(false) => {
+                return Ok(bad_request_error(
+                    &format!("Usuários desconhecidos: {}", user_id),
+                    "UNKNOWN_USER",
+                ));
+            }
+            Err(e) => return Ok(database_error("Erro ao buscar usuário", e)),
+        }
+
+        // O cliente age sem a presença do dono da conta: contas de administração são
+        // recusadas e os escopos ficam limitados às permissões da conta
+        let access = match rbac_service::load_access(pool.get_ref(), user_id).await {
+            Ok(access) => access,
+            Err(e) => return Ok(database_error("Erro ao buscar permissões do usuário", e)),
+        };
+        if access
+            .permissions
+            .iter()
+            .any(|permission| permission == PERMISSION_USERS_ADMIN)
+        {
+            return Ok(bad_request_error(
+                "A conta de serviço não pode ter permissões de administração",
+                "INVALID_SERVICE_ACCOUNT",
+            ));
+        }
+
+        let outside: Vec<&str> = scopes
+            .iter()
+            .filter(|scope| !access.permissions.contains(scope))
+            .map(String::as_str)
+            .collect();
+        if !outside.is_empty() {
+            return Ok(bad_request_error(
+                &format!(
+                    "Escopos não concedidos à conta de serviço: {}",
+                    outside.join(", ")
+                ),
+                "INVALID_SCOPE",
+            ));
+        }
+
+        Some(user_id)
+    } else {
+        None
+    };
+
+    let new_client = NewClient {
+        name: name.to_string(),
+        client_type: client_data.client_type,
+        redirect_uris,
+        grant_types,
+        scopes,
+        user_id,
+    };
+
+    match oauth_service::create_client(pool.get_ref(), &new_client, admin_id).await {
+        Ok((client_secret, client)) => {
+            Ok(HttpResponse::Created().json(CreatedOAuthClientResponse {
+                client_secret,
+                client: client.into(),
+            }))
+        }
+        Err(e) => Ok(database_error("Erro ao registrar cliente OAuth", e)),
+    }
+}
+
+// Revoga o cliente (requer admin); os tokens já emitidos para ele passam a receber 401
+pub async fn revoke_client(
+    pool: web::Data<PgPool>,
+    client_store: web::Data<OAuthClientStore>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    match oauth_service::revoke_client(pool.get_ref(), path.into_inner()).await {
+        Ok(Some(client_id)) => {
+            client_store.invalidate_client(&client_id);
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "message": "Cliente OAuth revogado com sucesso"
+            })))
+        }
+        Ok(None) => Ok(not_found_error(
+            "Cliente OAuth não encontrado ou já revogado",
+            "OAUTH_CLIENT_NOT_FOUND",
+        )),
+        Err(e) => Ok(database_error("Erro ao revogar cliente OAuth", e)),
+    }
+}
+
+fn dedup(values: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::new();
+    for value in values {
+        let value = value.trim().to_string();
+        if !value.is_empty() && !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+    unique
+}
+
+// Valida um pedido de autorização. Erros de cliente ou de `redirect_uri` nunca
+// redirecionam o navegador, para não entregar nada a uma URL não registrada.
+async fn validate_authorization(
+    pool: &PgPool,
+    request: &AuthorizeRequest,
+) -> std::result::Result<(OAuthClient, AuthorizationGrant), HttpResponse> {
+    let client = match request.client_id.as_deref() {
+        Some(client_id) => oauth_service::find_active_client(pool, client_id)
+            .await
+            .map_err(|e| database_error("Erro ao buscar cliente OAuth", e))?,
+        None => None,
+    };
+    let Some(client) = client else {
+        return Err(bad_request_error(
+            "Cliente OAuth inválido ou revogado",
+            "INVALID_CLIENT",
+        ));
+    };
+
+    let Some(redirect_uri) = request
+        .redirect_uri
+        .as_deref()
+        .filter(|uri| client.allows_redirect_uri(uri))
+    else {
+        return Err(bad_request_error(
+            "redirect_uri ausente ou não registrado para o cliente",
+            "INVALID_REDIRECT_URI",
+        ));
+    };
+
+    if request.response_type.as_deref() != Some("code") {
+        return Err(bad_request_error(
+            "Apenas response_type=code é suportado",
+            "UNSUPPORTED_RESPONSE_TYPE",
+        ));
+    }
+
+    if !client.allows_grant(GRANT_AUTHORIZATION_CODE) {
+        return Err(bad_request_error(
+            "O cliente não está autorizado a usar o grant authorization_code",
+            "UNAUTHORIZED_CLIENT",
+        ));
+    }
+
+    // PKCE obrigatório para todos os clientes, apenas com S256
+    let Some(code_challenge) = request
+        .code_challenge
+        .as_deref()
+        .filter(|challenge| valid_code_challenge(challenge))
+        .filter(|_| request.code_challenge_method.as_deref() == Some("S256"))
+    else {
+        return Err(bad_request_error(
+            "Informe code_challenge com code_challenge_method=S256",
+            "PKCE_REQUIRED",
+        ));
+    };
+
+    let Some(scopes) = client.granted_scopes(request.scope.as_deref()) else {
+        return Err(bad_request_error(
+            "Escopo não permitido para o cliente",
+            "INVALID_SCOPE",
+        ));
+    };
+
+    let grant = AuthorizationGrant {
+        redirect_uri: redirect_uri.to_string(),
+        scopes,
+        code_challenge: code_challenge.to_string(),
+    };
+
+    Ok((client, grant))
+}
+
+// Valida o pedido e retorna os dados da tela de consentimento
+pub async fn authorize(
+    pool: web::Data<PgPool>,
+    request: web::Query<AuthorizeRequest>,
+) -> Result<HttpResponse> {
+    match validate_authorization(pool.get_ref(), &request).await {
+        Ok((client, grant)) => Ok(HttpResponse::Ok().json(AuthorizeResponse {
+            client_id: client.client_id,
+            client_name: client.name,
+            redirect_uri: grant.redirect_uri,
+            scopes: grant.scopes,
+            state: request.into_inner().state,
+        })),
+        Err(response) => Ok(response),
+    }
+}
+
+// Registra a decisão do usuário autenticado e retorna a URL de retorno ao
+// cliente, com `code` (aprovado) ou `error=access_denied` (negado)
+pub async fn authorize_decision(
+    pool: web::Data<PgPool>,
+    oauth_config: web::Data<OAuthConfig>,
+    req: HttpRequest,
+    decision: web::Json<AuthorizeDecisionRequest>,
+) -> Result<HttpResponse> {
+    let Some((claims, user_id)) = get_claims_from_http_request(&req)
+        .and_then(|claims| claims.get_user_id().ok().map(|id| (claims, id)))
+    else {
+        return Ok(unauthorized_error(
+            "Token JWT não encontrado",
+            "TOKEN_MISSING",
+        ));
+    };
+
+    if claims.is_delegated() {
+        return Ok(delegated_credential_error(
+            "Autorizações OAuth só podem ser concedidas com login por senha",
+        ));
+    }
+
+    let decision = decision.into_inner();
+    let (client, grant) = match validate_authorization(pool.get_ref(), &decision.request).await {
+        Ok(validated) => validated,
+        Err(response) => return Ok(response),
+    };
+
+    let mut redirect_to = match Url::parse(&grant.redirect_uri) {
+        Ok(url) => url,
+        Err(_) => {
+            return Ok(bad_request_error(
+                "redirect_uri registrado inválido",
+                "INVALID_REDIRECT_URI",
+            ));
+        }
+    };
+
+    if decision.approve {
+        // O cliente age na organização ativa de quem concedeu a autorização
+        let organization_id = claims.org_role.and_then(|_| claims.get_organization_id());
+
+        let code = match oauth_service::create_code(
+            pool.get_ref(),
+            &client,
+            user_id,
+            organization_id,
+            &grant,
+            oauth_config.code_ttl_seconds,
+        )
+        .await
+        {
+            Ok(code) => code,
+            Err(e) => return Ok(database_error("Erro ao gerar código de autorização", e)),
+        };
+
+        redirect_to.query_pairs_mut().append_pair("code", &code);
+    } else {
+        redirect_to
+            .query_pairs_mut()
+            .append_pair("error", "access_denied");
+    }
+
+    if let Some(state) = &decision.request.state {
+        redirect_to.query_pairs_mut().append_pair("state", state);
+    }
+
+    Ok(HttpResponse::Ok().json(AuthorizeRedirectResponse {
+        redirect_to: redirect_to.into(),
+    }))
+}
+
+// Erro no formato da RFC 6749 (seção 5.2), esperado pelas bibliotecas OAuth
+fn token_error(status: StatusCode, error: &str, description: &str) -> HttpResponse {
+    let mut response = HttpResponse::build(status);
+    response
+        .insert_header(("Cache-Control", "no-store"))
+        .insert_header(("Pragma", "no-cache"));
+
+    if error == "invalid_client" {
+        response.insert_header(("WWW-Authenticate", "Basic realm=\"oauth\""));
+    }
+
+    response.json(serde_json::json!({
+        "error": error,
+        "error_description": description
+    }))
+}
+
+fn token_server_error(context: &str, e: sqlx::Error) -> HttpResponse {
+    eprintln!("{}: {:?}", context, e);
+    token_error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "server_error",
+        "Erro interno do servidor",
+    )
+}
+
+// Autentica o cliente por HTTP Basic ou por `client_id`/`client_secret` no corpo,
+// nunca pelos dois ao mesmo tempo
+async fn authenticate_client(
+    pool: &PgPool,
+    basic: Option<&BasicAuth>,
+    form: &TokenRequest,
+) -> std::result::Result<OAuthClient, HttpResponse> {
+    let (client_id, client_secret) = match basic {
+        Some(_) if form.client_secret.is_some() => {
+            return Err(token_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+                "Use apenas um método de autenticação do cliente",
+            ));
+        }
+        Some(basic) => (
+            Some(basic.user_id().to_string()),
+            basic.password().map(|secret| secret.to_string()),
+        ),
+        None => (form.client_id.clone(), form.client_secret.clone()),
+    };
+
+    let invalid_client = || {
+        token_error(
+            StatusCode::UNAUTHORIZED,
+            "invalid_client",
+            "Cliente OAuth inválido",
+        )
+    };
+
+    let Some(client_id) = client_id else {
+        return Err(invalid_client());
+    };
+
+    // Com Basic, o client_id do corpo (se enviado) deve ser o mesmo
+    if form
+        .client_id
+        .as_ref()
+        .is_some_and(|form_client_id| *form_client_id != client_id)
+    {
+        return Err(invalid_client());
+    }
+
+    let client = oauth_service::find_active_client(pool, &client_id)
+        .await
+        .map_err(|e| token_server_error("Erro ao buscar cliente OAuth", e))?;
+
+    match client {
+        Some(client)
+            if oauth_service::verify_client_secret(
+                &client,
+                client_secret.as_deref().filter(|secret| !secret.is_empty()),
+            ) =>
+        {
+            Ok(client)
+        }
+        _ => Err(invalid_client()),
+    }
+}
+
+// Emite um access token para o cliente, agindo como o usuário e limitado aos escopos
+pub async fn token(
+    pool: web::Data<PgPool>,
+    jwt_config: web::Data<JwtConfig>,
+    email_config: web::Data<EmailVerificationConfig>,
+    basic: Option<BasicAuth>,
+    form: web::Form<TokenRequest>,
+) -> Result<HttpResponse> {
+    let Some(grant_type) = form.grant_type.as_deref() else {
+        return Ok(token_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "grant_type é obrigatório",
+        ));
+    };
+
+    if !GRANT_TYPES.contains(&grant_type) {
+        return Ok(token_error(
+            StatusCode::BAD_REQUEST,
+            "unsupported_grant_type",
+            "Use authorization_code ou client_credentials",
+        ));
+    }
+
+    let client = match authenticate_client(pool.get_ref(), basic.as_ref(), &form).await {
+        Ok(client) => client,
+        Err(response) => return Ok(response),
+    };
+
+    if !client.allows_grant(grant_type) {
+        return Ok(token_error(
+            StatusCode::BAD_REQUEST,
+            "unauthorized_client",
+            "O cliente não está autorizado a usar este grant_type",
+        ));
+    }
+
+    let grant = if grant_type == GRANT_CLIENT_CREDENTIALS {
+        client_credentials_grant(pool.get_ref(), &client, &form).await
+    } else {
+        authorization_code_grant(pool.get_ref(), &client, &form).await
+    };
+
+    let (user_id, scopes, membership) = match grant {
+        Ok(grant) => grant,
+        Err(response) => return Ok(response),
+    };
+
+    issue_token(
+        pool.get_ref(),
+        jwt_config.get_ref(),
+        email_config.get_ref(),
+        &client,
+        user_id,
+        scopes,
+        membership,
+    )
+    .await
+}
+
+// Usuário, escopos e organização do token emitido
+type TokenGrant = (Uuid, Vec<String>, Option<(Uuid, OrganizationRole)>);
+
+// O cliente age como a própria conta de serviço, na organização padrão dela
+async fn client_credentials_grant(
+    pool: &PgPool,
+    client: &OAuthClient,
+    form: &TokenRequest,
+) -> std::result::Result<TokenGrant, HttpResponse> {
+    let Some(user_id) = client.user_id else {
+        return Err(token_error(
+            StatusCode::BAD_REQUEST,
+            "unauthorized_client",
+            "O cliente não possui uma conta para o grant client_credentials",
+        ));
+    };
+
+    let Some(scopes) = client.granted_scopes(form.scope.as_deref()) else {
+        return Err(token_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_scope",
+            "Escopo não permitido para o cliente",
+        ));
+    };
+
+    let membership = organization_service::default_membership(pool, user_id)
+        .await
+        .map_err(|e| token_server_error("Erro ao buscar organizações do usuário", e))?;
+
+    Ok((user_id, scopes, membership))
+}
+
+// Troca o código de autorização, conferindo cliente, `redirect_uri` e verificador PKCE
+async fn authorization_code_grant(
+    pool: &PgPool,
+    client: &OAuthClient,
+    form: &TokenRequest,
+) -> std::result::Result<TokenGrant, HttpResponse> {
+    let (Some(code), Some(redirect_uri), Some(code_verifier)) = (
+        form.code.as_deref(),
+        form.redirect_uri.as_deref(),
+        form.code_verifier.as_deref(),
+    ) else {
+        return Err(token_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "code, redirect_uri e code_verifier são obrigatórios",
+        ));
+    };
+
+    let invalid_grant =
+        |description: &str| token_error(StatusCode::BAD_REQUEST, "invalid_grant", description);
+
+    let code = match oauth_service::consume_code(pool, client, code).await {
+        Ok(CodeOutcome::Valid(code)) => code,
+        Ok(CodeOutcome::Invalid) => {
+            return Err(invalid_grant(
+                "Código de autorização inválido ou já utilizado",
+            ));
+        }
+        Ok(CodeOutcome::Expired) => return Err(invalid_grant("Código de autorização expirado")),
+        Err(e) => {
+            return Err(token_server_error(
+                "Erro ao buscar código de autorização",
+                e,
+            ))
+        }
+    };
+
+    if code.redirect_uri != redirect_uri {
+        return Err(invalid_grant(
+            "O código não foi emitido para este redirect_uri",
+        ));
+    }
+
+    if !valid_code_verifier(code_verifier)
+        || !oauth_service::verify_code_verifier(code_verifier, &code.code_challenge)
+    {
+        return Err(invalid_grant(
+            "code_verifier não corresponde ao code_challenge",
+        ));
+    }
+
+    // Sem vínculo com a organização da autorização, volta para a organização padrão
+    let membership = session_membership(pool, code.user_id, code.organization_id)
+        .await
+        .map_err(|e| token_server_error("Erro ao buscar organizações do usuário", e))?;
+
+    Ok((code.user_id, code.scopes, membership))
+}
+
+async fn issue_token(
+    pool: &PgPool,
+    jwt_config: &JwtConfig,
+    email_config: &EmailVerificationConfig,
+    client: &OAuthClient,
+    user_id: Uuid,
+    scopes: Vec<String>,
+    membership: Option<(Uuid, OrganizationRole)>,
+) -> Result<HttpResponse> {
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await;
+
+    let user = match user {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return Ok(token_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_grant",
+                "A conta autorizada não existe mais",
+            ));
+        }
+        Err(e) => return Ok(token_server_error("Erro ao buscar usuário", e)),
+    };
+
+    let roles = match rbac_service::roles_for_user(pool, user.id).await {
+        Ok(roles) => roles,
+        Err(e) => return Ok(token_server_error("Erro ao buscar roles do usuário", e)),
+    };
+
+    let mut claims =
+        user_claims(&user, roles, jwt_config, email_config).with_organization(membership);
+    claims.scopes = Some(scopes.clone());
+    claims.client_id = Some(client.client_id.clone());
+
+    match jwt_config.generate_token(&claims) {
+        Ok(access_token) => Ok(HttpResponse::Ok()
+            .insert_header(("Cache-Control", "no-store"))
+            .insert_header(("Pragma", "no-cache"))
+            .json(TokenResponse {
+                access_token,
+                token_type: "Bearer".to_string(),
+                expires_in: jwt_config.expires_in_seconds,
+                scope: scopes.join(" "),
+            })),
+        Err(e) => {
+            eprintln!("Erro ao gerar token OAuth: {:?}", e);
+            Ok(token_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "server_error",
+                "Erro ao gerar token",
+            ))
+        }
+    }
+}
+
+// Rotas registradas em `/admin/oauth` (ver `admin_handler::config`)
+pub fn admin_config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/clients", web::get().to(list_clients))
+        .route("/clients", web::post().to(create_client))
+        .route("/clients/{id}", web::delete().to(revoke_client));
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    use crate::middleware::jwt_validator;
+    use actix_web_httpauth::middleware::HttpAuthentication;
+
+    cfg.service(
+        web::scope("/oauth")
+            .route("/authorize", web::get().to(authorize))
+            .route(
+                "/authorize",
+                web::post()
+                    .to(authorize_decision)
+                    .wrap(HttpAuthentication::with_fn(jwt_validator)),
+            )
+            .route("/token", web::post().to(token)),
+    );
+}
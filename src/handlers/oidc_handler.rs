@@ -0,0 +1,286 @@
+use actix_web::{web, HttpResponse, Result};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::handlers::auth_handler::{build_login_response, two_factor_challenge};
+use crate::handlers::user_handler::insert_user;
+use crate::middleware::{
+    bad_gateway_error, bad_request_error, conflict_error, forbidden_error, internal_server_error,
+    not_found_error, password_error, unauthorized_error,
+};
+use crate::models::{
+    EmailVerificationConfig, IdTokenClaims, JwtConfig, OidcAuthorizationResponse,
+    OidcCallbackRequest, TwoFactorConfig, User, PERMISSION_USERS_ADMIN,
+};
+use crate::password::PasswordHasher;
+use crate::services::oidc_service::{self, OidcClient, OidcError, StateOutcome};
+use crate::services::rbac_service;
+use crate::services::refresh_token_service::generate_token;
+
+fn not_configured_error() -> HttpResponse {
+    not_found_error(
+        "Login via provedor OIDC não está configurado",
+        "OIDC_NOT_CONFIGURED",
+    )
+}
+
+fn oidc_error(error: OidcError) -> HttpResponse {
+    match error {
+        OidcError::CodeRejected(reason) => {
+            eprintln!("Código OIDC recusado pelo provedor: {}", reason);
+            bad_request_error(
+                "Código de autorização inválido ou expirado. Inicie o login novamente",
+                "INVALID_OIDC_CODE",
+            )
+        }
+        OidcError::InvalidIdToken(reason) => {
+            eprintln!("ID token OIDC inválido: {}", reason);
+            unauthorized_error("ID token do provedor inválido", "INVALID_ID_TOKEN")
+        }
+        OidcError::Provider(reason) => {
+            eprintln!("Erro ao comunicar com o provedor OIDC: {}", reason);
+            bad_gateway_error(
+                "Falha ao comunicar com o provedor de identidade",
+                "OIDC_PROVIDER_ERROR",
+            )
+        }
+    }
+}
+
+// Inicia o login: registra state, nonce e verificador PKCE e retorna a URL do provedor
+pub async fn oidc_login(
+    pool: web::Data<PgPool>,
+    oidc: Option<web::Data<OidcClient>>,
+) -> Result<HttpResponse> {
+    let Some(oidc) = oidc else {
+        return Ok(not_configured_error());
+    };
+
+    let login =
+        match oidc_service::start_login(pool.get_ref(), oidc.config().login_ttl_seconds).await {
+            Ok(login) => login,
+            Err(e) => {
+                eprintln!("Erro ao iniciar login OIDC: {:?}", e);
+                return Ok(internal_server_error(
+                    "Erro interno do servidor",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+    match oidc.authorization_url(&login).await {
+        Ok(authorization_url) => Ok(HttpResponse::Ok().json(OidcAuthorizationResponse {
+            authorization_url,
+            state: login.state,
+            expires_at: login.expires_at,
+        })),
+        Err(e) => Ok(oidc_error(e)),
+    }
+}
+
+// Conclui o login com o código devolvido ao `redirect_uri`: valida o ID token,
+// localiza (ou cria) o usuário local e emite os tokens da API
+pub async fn oidc_callback(
+    pool: web::Data<PgPool>,
+    hasher: web::Data<PasswordHasher>,
+    jwt_config: web::Data<JwtConfig>,
+    email_config: web::Data<EmailVerificationConfig>,
+    two_factor_config: web::Data<TwoFactorConfig>,
+    oidc: Option<web::Data<OidcClient>>,
+    callback: web::Json<OidcCallbackRequest>,
+) -> Result<HttpResponse> {
+    let Some(oidc) = oidc else {
+        return Ok(not_configured_error());
+    };
+
+    let (nonce, code_verifier) = match oidc_service::consume_state(&pool, &callback.state).await {
+        Ok(StateOutcome::Valid {
+            nonce,
+            code_verifier,
+        }) => (nonce, code_verifier),
+        Ok(StateOutcome::Invalid) => {
+            return Ok(bad_request_error(
+                "Login OIDC inválido ou já concluído",
+                "INVALID_OIDC_STATE",
+            ));
+        }
+        Ok(StateOutcome::Expired) => {
+            return Ok(bad_request_error(
+                "Login OIDC expirado. Inicie o login novamente",
+                "OIDC_STATE_EXPIRED",
+            ));
+        }
+        Err(e) => {
+            eprintln!("Erro ao buscar login OIDC: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    let id_token = match oidc.exchange_code(&callback.code, &code_verifier).await {
+        Ok(id_token) => id_token,
+        Err(e) => return Ok(oidc_error(e)),
+    };
+
+    let claims = match oidc.validate_id_token(&id_token, &nonce).await {
+        Ok(claims) => claims,
+        Err(e) => return Ok(oidc_error(e)),
+    };
+
+    let Some(email) = claims.email.clone() else {
+        return Ok(forbidden_error(
+            "O provedor não informou o email da conta",
+            "OIDC_EMAIL_MISSING",
+        ));
+    };
+
+    if !oidc.config().email_allowed(&email) {
+        return Ok(forbidden_error(
+            "O domínio do email não tem acesso a esta aplicação",
+            "OIDC_DOMAIN_NOT_ALLOWED",
+        ));
+    }
+
+    let user = match resolve_user(&pool, &hasher, &oidc, &claims, &email).await {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    // O provedor substitui apenas a senha: o segundo fator continua exigido
+    if user.totp_enabled_at.is_some() {
+        return Ok(
+            two_factor_challenge(pool.get_ref(), two_factor_config.get_ref(), user.id).await,
+        );
+    }
+
+    match build_login_response(
+        pool.get_ref(),
+        jwt_config.get_ref(),
+        email_config.get_ref(),
+        user,
+    )
+    .await
+    {
+        Ok(login_response) => Ok(HttpResponse::Ok().json(login_response)),
+        Err(response) => Ok(response),
+    }
+}
+
+// Conta local a vincular à identidade
+enum LocalAccount {
+    Existing(Uuid),
+    New { password_hash: String },
+}
+
+// Usuário local da identidade: já vinculado, vinculado agora pelo email
+// verificado ou criado no primeiro login
+async fn resolve_user(
+    pool: &PgPool,
+    hasher: &PasswordHasher,
+    oidc: &OidcClient,
+    claims: &IdTokenClaims,
+    email: &str,
+) -> std::result::Result<User, HttpResponse> {
+    let database_error = |e: sqlx::Error| {
+        eprintln!("Erro ao vincular identidade OIDC: {:?}", e);
+        internal_server_error("Erro interno do servidor", "DATABASE_ERROR")
+    };
+
+    if let Some(user) = oidc_service::find_linked_user(pool, claims)
+        .await
+        .map_err(database_error)?
+    {
+        return Ok(user);
+    }
+
+    // Sem email confirmado pelo provedor, qualquer um poderia assumir a conta
+    // local de mesmo email
+    if claims.email_verified != Some(true) {
+        return Err(forbidden_error(
+            "O provedor não confirmou o email da conta",
+            "OIDC_EMAIL_NOT_VERIFIED",
+        ));
+    }
+
+    let existing = oidc_service::find_user_by_email(pool, email)
+        .await
+        .map_err(database_error)?;
+
+    // Contas com 2FA ou permissões de administração não são vinculadas pelo email:
+    // o provedor passaria a dar acesso a elas sem o segundo fator ou sem a senha
+    if let Some(user) = &existing {
+        let is_admin = rbac_service::load_access(pool, user.id)
+            .await
+            .map_err(database_error)?
+            .permissions
+            .iter()
+            .any(|permission| permission == PERMISSION_USERS_ADMIN);
+
+        if user.totp_enabled_at.is_some() || is_admin {
+            return Err(forbidden_error(
+                "Esta conta não pode ser vinculada automaticamente ao provedor. Entre com email e senha",
+                "OIDC_LINK_NOT_ALLOWED",
+            ));
+        }
+    }
+
+    // Contas criadas pelo provedor recebem uma senha aleatória; o usuário pode
+    // definir uma senha própria por "esqueci minha senha"
+    let account = match existing {
+        Some(user) => LocalAccount::Existing(user.id),
+        None if !oidc.config().auto_provision => {
+            return Err(forbidden_error(
+                "Nenhuma conta corresponde a esta identidade. Solicite acesso a um administrador",
+                "OIDC_ACCOUNT_NOT_FOUND",
+            ));
+        }
+        None => LocalAccount::New {
+            password_hash: hasher
+                .hash(&generate_token())
+                .await
+                .map_err(|e| password_error(&e, "PASSWORD_HASH_ERROR"))?,
+        },
+    };
+
+    match link_account(pool, account, claims, email).await {
+        Ok(user) => Ok(user),
+        // Outro primeiro login simultâneo criou a conta antes; a identidade já está
+        // vinculada a ela, a menos que a conta tenha sido criada por cadastro
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            match oidc_service::find_linked_user(pool, claims).await {
+                Ok(Some(user)) => Ok(user),
+                Ok(None) => Err(conflict_error(
+                    "A conta foi criada por outra requisição. Inicie o login novamente",
+                    "OIDC_ACCOUNT_CONFLICT",
+                )),
+                Err(e) => Err(database_error(e)),
+            }
+        }
+        Err(e) => Err(database_error(e)),
+    }
+}
+
+async fn link_account(
+    pool: &PgPool,
+    account: LocalAccount,
+    claims: &IdTokenClaims,
+    email: &str,
+) -> std::result::Result<User, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let user_id = match account {
+        LocalAccount::Existing(user_id) => user_id,
+        LocalAccount::New { password_hash } => {
+            insert_user(&mut tx, &claims.display_name(), email, &password_hash)
+                .await?
+                .id
+        }
+    };
+
+    let user = oidc_service::link_identity(&mut tx, user_id, claims).await?;
+    tx.commit().await?;
+
+    Ok(user)
+}
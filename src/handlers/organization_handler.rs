@@ -0,0 +1,730 @@
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::handlers::user_handler;
+use crate::mailer::{self, Locale, Mailer, MessageKind};
+use crate::middleware::{
+    bad_request_error, conflict_error, forbidden_error, get_claims_from_http_request,
+    internal_server_error, not_found_error, unauthorized_error,
+};
+use crate::models::{
+    normalize_organization_name, AcceptInvitationRequest, CreateInvitationRequest,
+    CreateOrganizationRequest, InvitationConfig, InvitationResponse, InvitationStatus,
+    InvitationTokenRequest, OrganizationMemberListResponse, OrganizationRole, UpdateMemberRequest,
+    UpdateOrganizationRequest, User, UserQueryParams, UserResponse, ROLE_USER,
+};
+use crate::password::{PasswordHasher, PasswordPolicy};
+use crate::services::invitation_service::{self, ClaimOutcome};
+use crate::services::organization_service::{self, OrganizationError};
+use crate::services::rbac_service::{self, PermissionStore};
+
+// Usuário autenticado, organização ativa e papel atual dele nela (verificados pela rota)
+#[derive(Debug, Clone, Copy)]
+struct ActiveMembership {
+    user_id: Uuid,
+    organization_id: Uuid,
+    role: OrganizationRole,
+}
+
+fn active_membership(req: &HttpRequest) -> Option<ActiveMembership> {
+    let claims = get_claims_from_http_request(req)?;
+    Some(ActiveMembership {
+        user_id: claims.get_user_id().ok()?,
+        organization_id: claims.get_organization_id()?,
+        role: claims.org_role?,
+    })
+}
+
+fn token_missing_error() -> HttpResponse {
+    unauthorized_error("Token JWT não encontrado", "TOKEN_MISSING")
+}
+
+fn database_error(context: &str, e: sqlx::Error) -> HttpResponse {
+    eprintln!("{}: {:?}", context, e);
+    internal_server_error("Erro interno do servidor", "DATABASE_ERROR")
+}
+
+fn organization_error(error: OrganizationError) -> HttpResponse {
+    match error {
+        OrganizationError::Database(e) => database_error("Erro ao gerenciar organização", e),
+        OrganizationError::NotFound => {
+            not_found_error("Organização não encontrada", "ORGANIZATION_NOT_FOUND")
+        }
+        OrganizationError::MemberNotFound => {
+            not_found_error("Membro não encontrado na organização", "MEMBER_NOT_FOUND")
+        }
+        OrganizationError::AlreadyMember => {
+            conflict_error("O usuário já participa da organização", "ALREADY_MEMBER")
+        }
+        OrganizationError::LastOwner => conflict_error(
+            "A alteração deixaria a organização sem nenhum owner",
+            "LAST_OWNER",
+        ),
+    }
+}
+
+fn invalid_organization_name() -> HttpResponse {
+    bad_request_error(
+        "O nome da organização deve ter entre 1 e 100 caracteres",
+        "INVALID_ORGANIZATION_NAME",
+    )
+}
+
+fn cannot_manage(role: OrganizationRole) -> HttpResponse {
+    forbidden_error(
+        &format!(
+            "Acesso negado. Seu papel na organização não permite gerenciar o papel {}.",
+            role
+        ),
+        "ORGANIZATION_ROLE_REQUIRED",
+    )
+}
+
+// Organizações das quais o usuário autenticado participa
+pub async fn list_organizations(pool: web::Data<PgPool>, req: HttpRequest) -> Result<HttpResponse> {
+    let Some(user_id) =
+        get_claims_from_http_request(&req).and_then(|claims| claims.get_user_id().ok())
+    else {
+        return Ok(token_missing_error());
+    };
+
+    match organization_service::list_for_user(pool.get_ref(), user_id).await {
+        Ok(organizations) => Ok(HttpResponse::Ok().json(organizations)),
+        Err(e) => Ok(database_error("Erro ao listar organizações", e)),
+    }
+}
+
+// Cria uma organização com o usuário autenticado como owner
+pub async fn create_organization(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    organization_data: web::Json<CreateOrganizationRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(user_id) =
+        get_claims_from_http_request(&req).and_then(|claims| claims.get_user_id().ok())
+    else {
+        return Ok(token_missing_error());
+    };
+
+    let Some(name) = normalize_organization_name(&organization_data.name) else {
+        return Ok(invalid_organization_name());
+    };
+
+    match organization_service::create(pool.get_ref(), user_id, &name).await {
+        Ok(organization) => {
+            permission_store.invalidate_user(user_id);
+            Ok(HttpResponse::Created().json(organization))
+        }
+        Err(e) => Ok(database_error("Erro ao criar organização", e)),
+    }
+}
+
+// Organização ativa, com o papel do usuário nela
+pub async fn get_current_organization(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    match organization_service::find_for_user(
+        pool.get_ref(),
+        active.organization_id,
+        active.user_id,
+    )
+    .await
+    {
+        Ok(Some(organization)) => Ok(HttpResponse::Ok().json(organization)),
+        Ok(None) => Ok(organization_error(OrganizationError::NotFound)),
+        Err(e) => Ok(database_error("Erro ao buscar organização", e)),
+    }
+}
+
+pub async fn update_current_organization(
+    pool: web::Data<PgPool>,
+    organization_data: web::Json<UpdateOrganizationRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    let Some(name) = normalize_organization_name(&organization_data.name) else {
+        return Ok(invalid_organization_name());
+    };
+
+    match organization_service::rename(pool.get_ref(), active.organization_id, &name).await {
+        Ok(Some(organization)) => Ok(HttpResponse::Ok().json(organization)),
+        Ok(None) => Ok(organization_error(OrganizationError::NotFound)),
+        Err(e) => Ok(database_error("Erro ao atualizar organização", e)),
+    }
+}
+
+// Remove a organização ativa com todos os vínculos e chaves de API dela
+pub async fn delete_current_organization(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    match organization_service::delete(pool.get_ref(), active.organization_id).await {
+        Ok(true) => {
+            permission_store.invalidate_all();
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "message": "Organização removida com sucesso"
+            })))
+        }
+        Ok(false) => Ok(organization_error(OrganizationError::NotFound)),
+        Err(e) => Ok(database_error("Erro ao remover organização", e)),
+    }
+}
+
+// Membros da organização ativa, com paginação e busca por nome ou email
+pub async fn list_members(
+    pool: web::Data<PgPool>,
+    query: web::Query<UserQueryParams>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(10).clamp(1, 100);
+    let offset = (page - 1) * per_page;
+
+    match organization_service::list_members(
+        pool.get_ref(),
+        active.organization_id,
+        query.search.as_deref(),
+        per_page,
+        offset,
+    )
+    .await
+    {
+        Ok((members, total)) => Ok(HttpResponse::Ok().json(OrganizationMemberListResponse {
+            members,
+            total,
+            page,
+            per_page,
+            total_pages: (total as f64 / per_page as f64).ceil() as i64,
+        })),
+        Err(e) => Ok(database_error("Erro ao listar membros", e)),
+    }
+}
+
+pub async fn get_member(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    match organization_service::find_member(pool.get_ref(), active.organization_id, *path).await {
+        Ok(Some(member)) => Ok(HttpResponse::Ok().json(member)),
+        Ok(None) => Ok(organization_error(OrganizationError::MemberNotFound)),
+        Err(e) => Ok(database_error("Erro ao buscar membro", e)),
+    }
+}
+
+// Altera o papel de um membro. Admins não concedem nem alteram o papel owner.
+pub async fn update_member(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<Uuid>,
+    member_data: web::Json<UpdateMemberRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+    let member_id = path.into_inner();
+
+    let current =
+        match organization_service::find_member(pool.get_ref(), active.organization_id, member_id)
+            .await
+        {
+            Ok(Some(member)) => member,
+            Ok(None) => return Ok(organization_error(OrganizationError::MemberNotFound)),
+            Err(e) => return Ok(database_error("Erro ao buscar membro", e)),
+        };
+
+    for role in [current.role, member_data.role] {
+        if !active.role.can_manage(role) {
+            return Ok(cannot_manage(role));
+        }
+    }
+
+    match organization_service::set_member_role(
+        pool.get_ref(),
+        active.organization_id,
+        member_id,
+        member_data.role,
+    )
+    .await
+    {
+        Ok(member) => {
+            permission_store.invalidate_user(member_id);
+            Ok(HttpResponse::Ok().json(member))
+        }
+        Err(e) => Ok(organization_error(e)),
+    }
+}
+
+// Remove um membro da organização ativa; qualquer membro pode sair dela
+pub async fn remove_member(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+    let member_id = path.into_inner();
+
+    if member_id != active.user_id {
+        match organization_service::role_of(pool.get_ref(), active.organization_id, member_id).await
+        {
+            Ok(Some(role)) if !active.role.can_manage(role) => return Ok(cannot_manage(role)),
+            Ok(Some(_)) => {}
+            Ok(None) => return Ok(organization_error(OrganizationError::MemberNotFound)),
+            Err(e) => return Ok(database_error("Erro ao buscar membro", e)),
+        }
+    }
+
+    match organization_service::remove_member(pool.get_ref(), active.organization_id, member_id)
+        .await
+    {
+        Ok(()) => {
+            permission_store.invalidate_user(member_id);
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "message": "Membro removido da organização"
+            })))
+        }
+        Err(e) => Ok(organization_error(e)),
+    }
+}
+
+// Convida um email para a organização ativa. O token segue apenas no email,
+// enviado em segundo plano; um novo convite substitui o pendente para o mesmo email.
+pub async fn create_invitation(
+    pool: web::Data<PgPool>,
+    invitation_config: web::Data<InvitationConfig>,
+    mailer: web::Data<dyn Mailer>,
+    invitation_data: web::Json<CreateInvitationRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+    let Some(claims) = get_claims_from_http_request(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    if !active.role.can_manage(invitation_data.role) {
+        return Ok(cannot_manage(invitation_data.role));
+    }
+
+    let email = invitation_data.email.trim();
+    if email.parse::<lettre::Address>().is_err() {
+        return Ok(bad_request_error("Email inválido", "INVALID_EMAIL"));
+    }
+
+    let organization = match organization_service::find_for_user(
+        pool.get_ref(),
+        active.organization_id,
+        active.user_id,
+    )
+    .await
+    {
+        Ok(Some(organization)) => organization,
+        Ok(None) => return Ok(organization_error(OrganizationError::NotFound)),
+        Err(e) => return Ok(database_error("Erro ao buscar organização", e)),
+    };
+
+    let (invitation, token) = match invitation_service::create(
+        pool.get_ref(),
+        active.organization_id,
+        active.user_id,
+        email,
+        invitation_data.role,
+        invitation_config.token_ttl_seconds,
+    )
+    .await
+    {
+        Ok(created) => created,
+        Err(e) => return Ok(organization_error(e)),
+    };
+
+    let action = invitation_config.accept_link(&token).unwrap_or(token);
+    let expires_in =
+        Locale::from_request(&req).format_duration(invitation_config.token_ttl_seconds);
+    let message = mailer::render(
+        MessageKind::OrganizationInvitation,
+        Locale::from_request(&req),
+        &invitation.email,
+        &[
+            ("nome", &claims.nome),
+            ("organizacao", &organization.name),
+            ("papel", &invitation.role.to_string()),
+            ("action", &action),
+            ("expires_in", &expires_in),
+        ],
+    );
+    let mailer = mailer.into_inner();
+
+    actix_web::rt::spawn(async move {
+        if let Err(e) = mailer.send(message).await {
+            eprintln!("Erro ao enviar convite para organização: {:?}", e);
+        }
+    });
+
+    Ok(HttpResponse::Created().json(InvitationResponse::from(invitation)))
+}
+
+// Convites da organização ativa, com a situação de cada um
+pub async fn list_invitations(pool: web::Data<PgPool>, req: HttpRequest) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+
+    match invitation_service::list(pool.get_ref(), active.organization_id).await {
+        Ok(invitations) => Ok(HttpResponse::Ok().json(
+            invitations
+                .into_iter()
+                .map(InvitationResponse::from)
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => Ok(database_error("Erro ao listar convites", e)),
+    }
+}
+
+// Revoga um convite pendente; o link enviado deixa de valer
+pub async fn revoke_invitation(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let Some(active) = active_membership(&req) else {
+        return Ok(token_missing_error());
+    };
+    let invitation_id = path.into_inner();
+
+    let invitation =
+        match invitation_service::find(pool.get_ref(), active.organization_id, invitation_id).await
+        {
+            Ok(Some(invitation)) => invitation,
+            Ok(None) => return Ok(invitation_not_found()),
+            Err(e) => return Ok(database_error("Erro ao buscar convite", e)),
+        };
+
+    if !active.role.can_manage(invitation.role) {
+        return Ok(cannot_manage(invitation.role));
+    }
+
+    match invitation_service::revoke(pool.get_ref(), active.organization_id, invitation_id).await {
+        Ok(true) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Convite revogado com sucesso"
+        }))),
+        Ok(false) => Ok(invitation_not_found()),
+        Err(e) => Ok(database_error("Erro ao revogar convite", e)),
+    }
+}
+
+fn invitation_not_found() -> HttpResponse {
+    not_found_error(
+        "Convite não encontrado ou não está mais pendente",
+        "INVITATION_NOT_FOUND",
+    )
+}
+
+fn invalid_invitation(status: Option<InvitationStatus>) -> HttpResponse {
+    match status {
+        Some(InvitationStatus::Expired) => {
+            bad_request_error("Convite expirado", "INVITATION_EXPIRED")
+        }
+        _ => bad_request_error(
+            "Convite inválido, revogado ou já utilizado",
+            "INVALID_INVITATION",
+        ),
+    }
+}
+
+// Dados de um convite pendente, para o frontend decidir se pede nome e senha
+pub async fn lookup_invitation(
+    pool: web::Data<PgPool>,
+    lookup_data: web::Json<InvitationTokenRequest>,
+) -> Result<HttpResponse> {
+    let invitation =
+        match invitation_service::find_by_token(pool.get_ref(), &lookup_data.token).await {
+            Ok(Some(invitation)) if invitation.status() == InvitationStatus::Pending => invitation,
+            Ok(invitation) => return Ok(invalid_invitation(invitation.map(|i| i.status()))),
+            Err(e) => return Ok(database_error("Erro ao buscar convite", e)),
+        };
+
+    let lookup = async {
+        let organization =
+            organization_service::find(pool.get_ref(), invitation.organization_id).await?;
+        let account_exists =
+            sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)")
+                .bind(&invitation.email)
+                .fetch_one(pool.get_ref())
+                .await?;
+        Ok::<_, sqlx::Error>((organization, account_exists))
+    }
+    .await;
+
+    match lookup {
+        Ok((Some(organization), account_exists)) => {
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "organization": { "id": organization.id, "name": organization.name },
+                "email": invitation.email,
+                "role": invitation.role,
+                "expires_at": invitation.expires_at,
+                "account_exists": account_exists
+            })))
+        }
+        Ok((None, _)) => Ok(invalid_invitation(None)),
+        Err(e) => Ok(database_error("Erro ao buscar convite", e)),
+    }
+}
+
+// Conta que recebe o convite
+enum Invitee<'a> {
+    Existing(User),
+    New {
+        nome: &'a str,
+        password_hash: String,
+    },
+}
+
+// Aceita o convite. Se o email já possui conta, ela é vinculada à organização;
+// caso contrário, a conta é criada como em `register_user` (com `nome` e `senha`).
+// O email convidado passa a contar como verificado.
+pub async fn accept_invitation(
+    pool: web::Data<PgPool>,
+    hasher: web::Data<PasswordHasher>,
+    policy: web::Data<PasswordPolicy>,
+    permission_store: web::Data<PermissionStore>,
+    accept_data: web::Json<AcceptInvitationRequest>,
+) -> Result<HttpResponse> {
+    let invitation =
+        match invitation_service::find_by_token(pool.get_ref(), &accept_data.token).await {
+            Ok(Some(invitation)) if invitation.status() == InvitationStatus::Pending => invitation,
+            Ok(invitation) => return Ok(invalid_invitation(invitation.map(|i| i.status()))),
+            Err(e) => return Ok(database_error("Erro ao buscar convite", e)),
+        };
+
+    let existing_user = match sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
+        .bind(&invitation.email)
+        .fetch_optional(pool.get_ref())
+        .await
+    {
+        Ok(user) => user,
+        Err(e) => return Ok(database_error("Erro ao buscar usuário", e)),
+    };
+
+    // Nova conta: mesmas validações do cadastro
+    let invitee = match (existing_user, &accept_data.nome, &accept_data.senha) {
+        (Some(user), _, _) => Invitee::Existing(user),
+        (None, Some(nome), Some(senha)) if !nome.trim().is_empty() => {
+            match user_handler::validate_registration(
+                pool.get_ref(),
+                &hasher,
+                &policy,
+                nome.trim(),
+                &invitation.email,
+                senha,
+            )
+            .await
+            {
+                Ok(password_hash) => Invitee::New {
+                    nome: nome.trim(),
+                    password_hash,
+                },
+                Err(response) => return Ok(response),
+            }
+        }
+        (None, _, _) => {
+            return Ok(bad_request_error(
+                "Informe nome e senha para criar a conta",
+                "ACCOUNT_DATA_REQUIRED",
+            ));
+        }
+    };
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        let invitation = match invitation_service::claim(&mut tx, &accept_data.token).await? {
+            ClaimOutcome::Pending(invitation) => invitation,
+            ClaimOutcome::Expired => return Ok(Err(Some(InvitationStatus::Expired))),
+            ClaimOutcome::Invalid => return Ok(Err(None)),
+        };
+
+        let (user, created) = match invitee {
+            Invitee::Existing(user) => (user, false),
+            Invitee::New {
+                nome,
+                password_hash,
+            } => (
+                user_handler::insert_user(&mut tx, nome, &invitation.email, &password_hash).await?,
+                true,
+            ),
+        };
+
+        let joined = invitation_service::accept(&mut tx, &invitation, user.id).await?;
+        // Recarregado com o email já verificado
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(user.id)
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok::<_, sqlx::Error>(Ok((invitation, user, created, joined)))
+    }
+    .await;
+
+    let (invitation, user, created, joined) = match result {
+        Ok(Ok(accepted)) => accepted,
+        Ok(Err(status)) => return Ok(invalid_invitation(status)),
+        Err(e) => return Ok(database_error("Erro ao aceitar convite", e)),
+    };
+
+    permission_store.invalidate_user(user.id);
+
+    if !joined {
+        return Ok(organization_error(OrganizationError::AlreadyMember));
+    }
+
+    let roles = if created {
+        vec![ROLE_USER.to_string()]
+    } else {
+        match rbac_service::roles_for_user(pool.get_ref(), user.id).await {
+            Ok(roles) => roles,
+            Err(e) => return Ok(database_error("Erro ao buscar roles do usuário", e)),
+        }
+    };
+
+    let body = serde_json::json!({
+        "message": "Convite aceito com sucesso",
+        "user": UserResponse::new(user, roles),
+        "organization_id": invitation.organization_id,
+        "role": invitation.role
+    });
+
+    if created {
+        Ok(HttpResponse::Created().json(body))
+    } else {
+        Ok(HttpResponse::Ok().json(body))
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    use crate::middleware::require_permissions;
+    use crate::models::{PERMISSION_USERS_READ, PERMISSION_USERS_WRITE};
+
+    let read = require_permissions(&[PERMISSION_USERS_READ]);
+    let write = require_permissions(&[PERMISSION_USERS_WRITE]);
+
+    cfg.service(
+        web::scope("/organizations")
+            // Organizações do usuário autenticado
+            .route(
+                "",
+                web::get().to(list_organizations).wrap(read.middleware()),
+            )
+            .route(
+                "",
+                web::post().to(create_organization).wrap(write.middleware()),
+            )
+            // Organização ativa do token (ver `POST /auth/switch-organization`)
+            .route(
+                "/current",
+                web::get().to(get_current_organization).wrap(
+                    read.organization_role(OrganizationRole::Member)
+                        .middleware(),
+                ),
+            )
+            .route(
+                "/current",
+                web::put().to(update_current_organization).wrap(
+                    write
+                        .organization_role(OrganizationRole::Admin)
+                        .middleware(),
+                ),
+            )
+            .route(
+                "/current",
+                web::delete().to(delete_current_organization).wrap(
+                    write
+                        .organization_role(OrganizationRole::Owner)
+                        .middleware(),
+                ),
+            )
+            .route(
+                "/current/members",
+                web::get().to(list_members).wrap(
+                    read.organization_role(OrganizationRole::Member)
+                        .middleware(),
+                ),
+            )
+            .route(
+                "/current/members/{user_id}",
+                web::get().to(get_member).wrap(
+                    read.organization_role(OrganizationRole::Member)
+                        .middleware(),
+                ),
+            )
+            .route(
+                "/current/members/{user_id}",
+                web::put().to(update_member).wrap(
+                    write
+                        .organization_role(OrganizationRole::Admin)
+                        .middleware(),
+                ),
+            )
+            .route(
+                "/current/members/{user_id}",
+                web::delete().to(remove_member).wrap(
+                    write
+                        .organization_role(OrganizationRole::Member)
+                        .middleware(),
+                ),
+            )
+            .route(
+                "/current/invitations",
+                web::get()
+                    .to(list_invitations)
+                    .wrap(read.organization_role(OrganizationRole::Admin).middleware()),
+            )
+            .route(
+                "/current/invitations",
+                web::post().to(create_invitation).wrap(
+                    write
+                        .organization_role(OrganizationRole::Admin)
+                        .middleware(),
+                ),
+            )
+            .route(
+                "/current/invitations/{id}",
+                web::delete().to(revoke_invitation).wrap(
+                    write
+                        .organization_role(OrganizationRole::Admin)
+                        .middleware(),
+                ),
+            )
+            // Públicas: o token do convite identifica o convidado
+            .route("/invitations/lookup", web::post().to(lookup_invitation))
+            .route("/invitations/accept", web::post().to(accept_invitation)),
+    );
+}
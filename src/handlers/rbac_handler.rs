@@ -0,0 +1,525 @@
+use actix_web::{web, HttpResponse, Result};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::middleware::{
+    bad_request_error, conflict_error, forbidden_error, internal_server_error, not_found_error,
+};
+use crate::models::{
+    is_valid_permission_name, normalize_group_name, normalize_role_name, AddGroupMembersRequest,
+    AssignRolesRequest, CreateGroupRequest, CreatePermissionRequest, CreateRoleRequest,
+    UpdateGroupRequest, UpdateRoleRequest,
+};
+use crate::services::rbac_service::{self, PermissionStore, RbacError};
+
+// Recurso afetado, para os códigos de erro 404/409
+#[derive(Debug, Clone, Copy)]
+enum Subject {
+    Permission,
+    Role,
+    Group,
+    User,
+}
+
+fn rbac_error(error: RbacError, subject: Subject) -> HttpResponse {
+    match error {
+        RbacError::Database(e) => {
+            eprintln!("Erro ao gerenciar roles e permissões: {:?}", e);
+            internal_server_error("Erro interno do servidor", "DATABASE_ERROR")
+        }
+        RbacError::NotFound => match subject {
+            Subject::Permission => {
+                not_found_error("Permissão não encontrada", "PERMISSION_NOT_FOUND")
+            }
+            Subject::Role => not_found_error("Role não encontrada", "ROLE_NOT_FOUND"),
+            Subject::Group => not_found_error("Grupo não encontrado", "GROUP_NOT_FOUND"),
+            Subject::User => not_found_error("Usuário não encontrado", "USER_NOT_FOUND"),
+        },
+        RbacError::AlreadyExists => match subject {
+            Subject::Permission => conflict_error(
+                "Já existe uma permissão com este nome",
+                "PERMISSION_ALREADY_EXISTS",
+            ),
+            Subject::Group => {
+                conflict_error("Já existe um grupo com este nome", "GROUP_ALREADY_EXISTS")
+            }
+            _ => conflict_error("Já existe uma role com este nome", "ROLE_ALREADY_EXISTS"),
+        },
+        RbacError::SystemRole => forbidden_error(
+            "Roles padrão não podem ser renomeadas nem removidas",
+            "SYSTEM_ROLE",
+        ),
+        RbacError::SystemPermission => forbidden_error(
+            "Permissões verificadas pela API não podem ser removidas",
+            "SYSTEM_PERMISSION",
+        ),
+        RbacError::UnknownPermissions(names) => bad_request_error(
+            &format!("Permissões desconhecidas: {}", names.join(", ")),
+            "UNKNOWN_PERMISSION",
+        ),
+        RbacError::UnknownRoles(names) => bad_request_error(
+            &format!("Roles desconhecidas: {}", names.join(", ")),
+            "UNKNOWN_ROLE",
+        ),
+        RbacError::UnknownUsers(ids) => bad_request_error(
+            &format!(
+                "Usuários desconhecidos: {}",
+                ids.iter()
+                    .map(Uuid::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            "UNKNOWN_USER",
+        ),
+        RbacError::NotMember => {
+            not_found_error("O usuário não faz parte do grupo", "GROUP_MEMBER_NOT_FOUND")
+        }
+        RbacError::LastAdmin => conflict_error(
+            "A alteração deixaria o sistema sem nenhum administrador",
+            "LAST_ADMIN",
+        ),
+    }
+}
+
+fn invalid_role_name() -> HttpResponse {
+    bad_request_error(
+        "O nome da role deve ter até 50 caracteres: letras, dígitos e _, começando por letra",
+        "INVALID_ROLE_NAME",
+    )
+}
+
+fn invalid_group_name() -> HttpResponse {
+    bad_request_error(
+        "O nome do grupo deve ter entre 1 e 100 caracteres",
+        "INVALID_GROUP_NAME",
+    )
+}
+
+// Nomes inválidos não correspondem a nenhuma role e são informados como desconhecidos
+fn role_names(roles: &[String]) -> Vec<String> {
+    let roles: Vec<String> = roles
+        .iter()
+        .map(|role| normalize_role_name(role).unwrap_or_else(|| role.clone()))
+        .collect();
+    dedup(&roles)
+}
+
+fn description(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn dedup(values: &[String]) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::new();
+    for value in values {
+        if !unique.contains(value) {
+            unique.push(value.clone());
+        }
+    }
+    unique
+}
+
+pub async fn list_permissions(pool: web::Data<PgPool>) -> Result<HttpResponse> {
+    match rbac_service::list_permissions(pool.get_ref()).await {
+        Ok(permissions) => Ok(HttpResponse::Ok().json(permissions)),
+        Err(e) => Ok(rbac_error(e.into(), Subject::Permission)),
+    }
+}
+
+pub async fn create_permission(
+    pool: web::Data<PgPool>,
+    permission_data: web::Json<CreatePermissionRequest>,
+) -> Result<HttpResponse> {
+    let name = permission_data.name.trim();
+    if !is_valid_permission_name(name) {
+        return Ok(bad_request_error(
+            "O nome da permissão deve seguir o formato recurso:ação (letras minúsculas, dígitos, _ e -)",
+            "INVALID_PERMISSION_NAME",
+        ));
+    }
+
+    match rbac_service::create_permission(
+        pool.get_ref(),
+        name,
+        description(&permission_data.description),
+    )
+    .await
+    {
+        Ok(permission) => Ok(HttpResponse::Created().json(permission)),
+        Err(e) => Ok(rbac_error(e, Subject::Permission)),
+    }
+}
+
+// Remove a permissão de todas as roles
+pub async fn delete_permission(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    match rbac_service::delete_permission(pool.get_ref(), path.into_inner()).await {
+        Ok(()) => {
+            permission_store.invalidate_all();
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "message": "Permissão removida com sucesso"
+            })))
+        }
+        Err(e) => Ok(rbac_error(e, Subject::Permission)),
+    }
+}
+
+pub async fn list_roles(pool: web::Data<PgPool>) -> Result<HttpResponse> {
+    match rbac_service::list_roles(pool.get_ref()).await {
+        Ok(roles) => Ok(HttpResponse::Ok().json(roles)),
+        Err(e) => Ok(rbac_error(e.into(), Subject::Role)),
+    }
+}
+
+pub async fn get_role(pool: web::Data<PgPool>, path: web::Path<Uuid>) -> Result<HttpResponse> {
+    match rbac_service::find_role(pool.get_ref(), path.into_inner()).await {
+        Ok(Some(role)) => Ok(HttpResponse::Ok().json(role)),
+        Ok(None) => Ok(rbac_error(RbacError::NotFound, Subject::Role)),
+        Err(e) => Ok(rbac_error(e.into(), Subject::Role)),
+    }
+}
+
+pub async fn create_role(
+    pool: web::Data<PgPool>,
+    role_data: web::Json<CreateRoleRequest>,
+) -> Result<HttpResponse> {
+    let Some(name) = normalize_role_name(&role_data.name) else {
+        return Ok(invalid_role_name());
+    };
+
+    match rbac_service::create_role(
+        pool.get_ref(),
+        &name,
+        description(&role_data.description),
+        &dedup(&role_data.permissions),
+    )
+    .await
+    {
+        Ok(role) => Ok(HttpResponse::Created().json(role)),
+        Err(e) => Ok(rbac_error(e, Subject::Role)),
+    }
+}
+
+// Altera a role; as permissões dos usuários que a possuem mudam imediatamente
+pub async fn update_role(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<Uuid>,
+    role_data: web::Json<UpdateRoleRequest>,
+) -> Result<HttpResponse> {
+    let name = match &role_data.name {
+        Some(name) => match normalize_role_name(name) {
+            Some(name) => Some(name),
+            None => return Ok(invalid_role_name()),
+        },
+        None => None,
+    };
+    let permissions = role_data.permissions.as_deref().map(dedup);
+
+    match rbac_service::update_role(
+        pool.get_ref(),
+        path.into_inner(),
+        name.as_deref(),
+        description(&role_data.description),
+        permissions.as_deref(),
+    )
+    .await
+    {
+        Ok(role) => {
+            permission_store.invalidate_all();
+            Ok(HttpResponse::Ok().json(role))
+        }
+        Err(e) => Ok(rbac_error(e, Subject::Role)),
+    }
+}
+
+// Remove a role e a retira de todos os usuários
+pub async fn delete_role(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    match rbac_service::delete_role(pool.get_ref(), path.into_inner()).await {
+        Ok(()) => {
+            permission_store.invalidate_all();
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "message": "Role removida com sucesso"
+            })))
+        }
+        Err(e) => Ok(rbac_error(e, Subject::Role)),
+    }
+}
+
+async fn user_access_response(pool: &PgPool, user_id: Uuid) -> HttpResponse {
+    match rbac_service::load_access(pool, user_id).await {
+        Ok(access) => HttpResponse::Ok().json(serde_json::json!({
+            "user_id": user_id,
+            "roles": access.roles,
+            "groups": access.groups,
+            "permissions": access.permissions
+        })),
+        Err(e) => rbac_error(e.into(), Subject::User),
+    }
+}
+
+// Roles diretas e grupos do usuário e as permissões efetivas que eles concedem
+pub async fn get_user_roles(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let user_id = path.into_inner();
+
+    let user_exists =
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id)
+            .fetch_one(pool.get_ref())
+            .await;
+
+    match user_exists {
+        Ok(true) => Ok(user_access_response(pool.get_ref(), user_id).await),
+        Ok(false) => Ok(rbac_error(RbacError::NotFound, Subject::User)),
+        Err(e) => Ok(rbac_error(e.into(), Subject::User)),
+    }
+}
+
+// Substitui as roles do usuário
+pub async fn set_user_roles(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<Uuid>,
+    roles_data: web::Json<AssignRolesRequest>,
+) -> Result<HttpResponse> {
+    let user_id = path.into_inner();
+
+    match rbac_service::set_user_roles(pool.get_ref(), user_id, &role_names(&roles_data.roles))
+        .await
+    {
+        Ok(_) => {
+            permission_store.invalidate_user(user_id);
+            Ok(user_access_response(pool.get_ref(), user_id).await)
+        }
+        Err(e) => Ok(rbac_error(e, Subject::User)),
+    }
+}
+
+pub async fn list_groups(pool: web::Data<PgPool>) -> Result<HttpResponse> {
+    match rbac_service::list_groups(pool.get_ref()).await {
+        Ok(groups) => Ok(HttpResponse::Ok().json(groups)),
+        Err(e) => Ok(rbac_error(e.into(), Subject::Group)),
+    }
+}
+
+pub async fn get_group(pool: web::Data<PgPool>, path: web::Path<Uuid>) -> Result<HttpResponse> {
+    match rbac_service::find_group(pool.get_ref(), path.into_inner()).await {
+        Ok(Some(group)) => Ok(HttpResponse::Ok().json(group)),
+        Ok(None) => Ok(rbac_error(RbacError::NotFound, Subject::Group)),
+        Err(e) => Ok(rbac_error(e.into(), Subject::Group)),
+    }
+}
+
+pub async fn create_group(
+    pool: web::Data<PgPool>,
+    group_data: web::Json<CreateGroupRequest>,
+) -> Result<HttpResponse> {
+    let Some(name) = normalize_group_name(&group_data.name) else {
+        return Ok(invalid_group_name());
+    };
+
+    match rbac_service::create_group(
+        pool.get_ref(),
+        &name,
+        description(&group_data.description),
+        &role_names(&group_data.roles),
+        &dedup(&group_data.permissions),
+    )
+    .await
+    {
+        Ok(group) => Ok(HttpResponse::Created().json(group)),
+        Err(e) => Ok(rbac_error(e, Subject::Group)),
+    }
+}
+
+// Altera o grupo; as permissões dos membros mudam imediatamente
+pub async fn update_group(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<Uuid>,
+    group_data: web::Json<UpdateGroupRequest>,
+) -> Result<HttpResponse> {
+    let name = match &group_data.name {
+        Some(name) => match normalize_group_name(name) {
+            Some(name) => Some(name),
+            None => return Ok(invalid_group_name()),
+        },
+        None => None,
+    };
+    let roles = group_data.roles.as_deref().map(role_names);
+    let permissions = group_data.permissions.as_deref().map(dedup);
+
+    match rbac_service::update_group(
+        pool.get_ref(),
+        path.into_inner(),
+        name.as_deref(),
+        description(&group_data.description),
+        roles.as_deref(),
+        permissions.as_deref(),
+    )
+    .await
+    {
+        Ok(group) => {
+            permission_store.invalidate_all();
+            Ok(HttpResponse::Ok().json(group))
+        }
+        Err(e) => Ok(rbac_error(e, Subject::Group)),
+    }
+}
+
+pub async fn delete_group(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    match rbac_service::delete_group(pool.get_ref(), path.into_inner()).await {
+        Ok(()) => {
+            permission_store.invalidate_all();
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "message": "Grupo removido com sucesso"
+            })))
+        }
+        Err(e) => Ok(rbac_error(e, Subject::Group)),
+    }
+}
+
+pub async fn list_group_members(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let group_id = path.into_inner();
+
+    match rbac_service::find_group(pool.get_ref(), group_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Ok(rbac_error(RbacError::NotFound, Subject::Group)),
+        Err(e) => return Ok(rbac_error(e.into(), Subject::Group)),
+    }
+
+    match rbac_service::list_group_members(pool.get_ref(), group_id).await {
+        Ok(members) => Ok(HttpResponse::Ok().json(members)),
+        Err(e) => Ok(rbac_error(e.into(), Subject::Group)),
+    }
+}
+
+// Adiciona vários usuários ao grupo de uma vez
+pub async fn add_group_members(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<Uuid>,
+    members_data: web::Json<AddGroupMembersRequest>,
+) -> Result<HttpResponse> {
+    let group_id = path.into_inner();
+    let mut user_ids = members_data.user_ids.clone();
+    user_ids.sort();
+    user_ids.dedup();
+
+    match rbac_service::add_group_members(pool.get_ref(), group_id, &user_ids).await {
+        Ok(added) => {
+            for user_id in &added {
+                permission_store.invalidate_user(*user_id);
+            }
+            match rbac_service::list_group_members(pool.get_ref(), group_id).await {
+                Ok(members) => Ok(HttpResponse::Ok().json(serde_json::json!({
+                    "added": added.len(),
+                    "members": members
+                }))),
+                Err(e) => Ok(rbac_error(e.into(), Subject::Group)),
+            }
+        }
+        Err(e) => Ok(rbac_error(e, Subject::Group)),
+    }
+}
+
+pub async fn remove_group_member(
+    pool: web::Data<PgPool>,
+    permission_store: web::Data<PermissionStore>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse> {
+    let (group_id, user_id) = path.into_inner();
+
+    match rbac_service::remove_group_member(pool.get_ref(), group_id, user_id).await {
+        Ok(()) => {
+            permission_store.invalidate_user(user_id);
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "message": "Usuário removido do grupo"
+            })))
+        }
+        Err(e) => Ok(rbac_error(e, Subject::Group)),
+    }
+}
+
+// Permissões efetivas do usuário com a origem de cada uma, para investigar
+// problemas de acesso (rota `/users/{id}/effective-permissions`)
+pub async fn get_effective_permissions(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let user_id = path.into_inner();
+
+    let user_exists =
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id)
+            .fetch_one(pool.get_ref())
+            .await;
+    match user_exists {
+        Ok(true) => {}
+        Ok(false) => return Ok(rbac_error(RbacError::NotFound, Subject::User)),
+        Err(e) => return Ok(rbac_error(e.into(), Subject::User)),
+    }
+
+    let result = async {
+        let roles = rbac_service::roles_for_user(pool.get_ref(), user_id).await?;
+        let groups = rbac_service::groups_for_user(pool.get_ref(), user_id).await?;
+        let permissions =
+            rbac_service::effective_permissions_for_user(pool.get_ref(), user_id).await?;
+        Ok::<_, sqlx::Error>((roles, groups, permissions))
+    }
+    .await;
+
+    match result {
+        Ok((roles, groups, permissions)) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "user_id": user_id,
+            "roles": roles,
+            "groups": groups,
+            "permissions": permissions
+        }))),
+        Err(e) => Ok(rbac_error(e.into(), Subject::User)),
+    }
+}
+
+// Rotas registradas no escopo `/admin` (ver `admin_handler::config`)
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/permissions", web::get().to(list_permissions))
+        .route("/permissions", web::post().to(create_permission))
+        .route("/permissions/{id}", web::delete().to(delete_permission))
+        .route("/roles", web::get().to(list_roles))
+        .route("/roles", web::post().to(create_role))
+        .route("/roles/{id}", web::get().to(get_role))
+        .route("/roles/{id}", web::put().to(update_role))
+        .route("/roles/{id}", web::delete().to(delete_role))
+        .route("/users/{id}/roles", web::get().to(get_user_roles))
+        .route("/users/{id}/roles", web::put().to(set_user_roles))
+        .route("/groups", web::get().to(list_groups))
+        .route("/groups", web::post().to(create_group))
+        .route("/groups/{id}", web::get().to(get_group))
+        .route("/groups/{id}", web::put().to(update_group))
+        .route("/groups/{id}", web::delete().to(delete_group))
+        .route("/groups/{id}/members", web::get().to(list_group_members))
+        .route("/groups/{id}/members", web::post().to(add_group_members))
+        .route(
+            "/groups/{id}/members/{user_id}",
+            web::delete().to(remove_group_member),
+        );
+}
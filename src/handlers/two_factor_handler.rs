@@ -0,0 +1,201 @@
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use sqlx::PgPool;
+
+use crate::middleware::{
+    bad_request_error, get_claims_from_http_request, internal_server_error, not_found_error,
+    password_error, unauthorized_error,
+};
+use crate::models::{
+    DisableTwoFactorRequest, RecoveryCodesResponse, TwoFactorCodeRequest, TwoFactorConfig,
+    TwoFactorSetupResponse, TwoFactorStatusResponse, User,
+};
+use crate::password::PasswordHasher;
+use crate::services::two_factor_service::{self, ConfirmOutcome};
+
+// Busca o usuário autenticado a partir das claims do token
+async fn current_user(pool: &PgPool, req: &HttpRequest) -> std::result::Result<User, HttpResponse> {
+    let claims = get_claims_from_http_request(req)
+        .ok_or_else(|| unauthorized_error("Token JWT não encontrado", "TOKEN_MISSING"))?;
+    let user_id = claims
+        .get_user_id()
+        .map_err(|_| bad_request_error("ID de usuário inválido no token", "INVALID_USER_ID"))?;
+
+    match sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err(not_found_error("Usuário não encontrado", "USER_NOT_FOUND")),
+        Err(e) => {
+            eprintln!("Erro ao buscar usuário atual: {:?}", e);
+            Err(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ))
+        }
+    }
+}
+
+fn invalid_code_error() -> HttpResponse {
+    bad_request_error("Código de autenticação inválido", "INVALID_TWO_FACTOR_CODE")
+}
+
+fn database_error(context: &str, e: sqlx::Error) -> HttpResponse {
+    eprintln!("{}: {:?}", context, e);
+    internal_server_error("Erro interno do servidor", "DATABASE_ERROR")
+}
+
+// Situação do 2FA do usuário autenticado
+pub async fn get_status(pool: web::Data<PgPool>, req: HttpRequest) -> Result<HttpResponse> {
+    let user = match current_user(pool.get_ref(), &req).await {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    match two_factor_service::recovery_codes_remaining(pool.get_ref(), user.id).await {
+        Ok(remaining) => Ok(HttpResponse::Ok().json(TwoFactorStatusResponse {
+            enabled: user.totp_enabled_at.is_some(),
+            pending_confirmation: user.totp_enabled_at.is_none() && user.totp_secret.is_some(),
+            recovery_codes_remaining: remaining,
+        })),
+        Err(e) => Ok(database_error("Erro ao contar códigos de recuperação", e)),
+    }
+}
+
+// Gera um novo segredo TOTP. O 2FA só é ativado após `/confirm`.
+pub async fn setup(
+    pool: web::Data<PgPool>,
+    two_factor_config: web::Data<TwoFactorConfig>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let user = match current_user(pool.get_ref(), &req).await {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    let secret = match two_factor_service::begin_setup(pool.get_ref(), user.id).await {
+        Ok(Some(secret)) => secret,
+        Ok(None) => {
+            return Ok(bad_request_error(
+                "Autenticação em dois fatores já está ativada",
+                "TWO_FACTOR_ALREADY_ENABLED",
+            ));
+        }
+        Err(e) => return Ok(database_error("Erro ao iniciar 2FA", e)),
+    };
+
+    match two_factor_service::otpauth_uri(&secret, &two_factor_config.issuer, &user.email) {
+        Some(otpauth_uri) => Ok(HttpResponse::Ok().json(TwoFactorSetupResponse {
+            secret,
+            otpauth_uri,
+        })),
+        None => Ok(internal_server_error(
+            "Erro interno do servidor",
+            "TWO_FACTOR_SETUP_ERROR",
+        )),
+    }
+}
+
+// Confirma o cadastro com o primeiro código do autenticador e devolve os
+// códigos de recuperação (exibidos apenas uma vez)
+pub async fn confirm(
+    pool: web::Data<PgPool>,
+    code_data: web::Json<TwoFactorCodeRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let user = match current_user(pool.get_ref(), &req).await {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    match two_factor_service::confirm(pool.get_ref(), user.id, &code_data.code).await {
+        Ok(ConfirmOutcome::Enabled { recovery_codes }) => {
+            Ok(HttpResponse::Ok().json(RecoveryCodesResponse { recovery_codes }))
+        }
+        Ok(ConfirmOutcome::NotSetUp) => Ok(bad_request_error(
+            "Inicie o cadastro do autenticador em /users/me/2fa/setup",
+            "TWO_FACTOR_NOT_SETUP",
+        )),
+        Ok(ConfirmOutcome::AlreadyEnabled) => Ok(bad_request_error(
+            "Autenticação em dois fatores já está ativada",
+            "TWO_FACTOR_ALREADY_ENABLED",
+        )),
+        Ok(ConfirmOutcome::InvalidCode) => Ok(invalid_code_error()),
+        Err(e) => Ok(database_error("Erro ao confirmar 2FA", e)),
+    }
+}
+
+// Desativa o 2FA; exige a senha e um código TOTP ou de recuperação
+pub async fn disable(
+    pool: web::Data<PgPool>,
+    hasher: web::Data<PasswordHasher>,
+    disable_data: web::Json<DisableTwoFactorRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let user = match current_user(pool.get_ref(), &req).await {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    if user.totp_enabled_at.is_none() {
+        return Ok(bad_request_error(
+            "Autenticação em dois fatores não está ativada",
+            "TWO_FACTOR_NOT_ENABLED",
+        ));
+    }
+
+    match hasher.verify(&disable_data.senha, &user.senha).await {
+        Ok(check) if check.is_valid() => {}
+        Ok(_) => {
+            return Ok(bad_request_error("Senha incorreta", "INVALID_PASSWORD"));
+        }
+        Err(e) => return Ok(password_error(&e, "PASSWORD_VERIFICATION_ERROR")),
+    }
+
+    match two_factor_service::disable(pool.get_ref(), user.id, &disable_data.code).await {
+        Ok(true) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Autenticação em dois fatores desativada"
+        }))),
+        Ok(false) => Ok(invalid_code_error()),
+        Err(e) => Ok(database_error("Erro ao desativar 2FA", e)),
+    }
+}
+
+// Substitui os códigos de recuperação; exige um código TOTP
+pub async fn regenerate_recovery_codes(
+    pool: web::Data<PgPool>,
+    code_data: web::Json<TwoFactorCodeRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let user = match current_user(pool.get_ref(), &req).await {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    if user.totp_enabled_at.is_none() {
+        return Ok(bad_request_error(
+            "Autenticação em dois fatores não está ativada",
+            "TWO_FACTOR_NOT_ENABLED",
+        ));
+    }
+
+    match two_factor_service::regenerate_recovery_codes(pool.get_ref(), user.id, &code_data.code)
+        .await
+    {
+        Ok(Some(recovery_codes)) => {
+            Ok(HttpResponse::Ok().json(RecoveryCodesResponse { recovery_codes }))
+        }
+        Ok(None) => Ok(invalid_code_error()),
+        Err(e) => Ok(database_error("Erro ao gerar códigos de recuperação", e)),
+    }
+}
+
+// Rotas registradas em `/users/me/2fa` (ver `user_handler::config`)
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("", web::get().to(get_status))
+        .route("/setup", web::post().to(setup))
+        .route("/confirm", web::post().to(confirm))
+        .route("/disable", web::post().to(disable))
+        .route("/recovery-codes", web::post().to(regenerate_recovery_codes));
+}
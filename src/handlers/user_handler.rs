@@ -1,31 +1,124 @@
 use actix_web::{web, HttpRequest, HttpResponse, Result};
-use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::Utc;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
+use crate::mailer::{Locale, Mailer};
 use crate::middleware::{
     bad_request_error, forbidden_error, get_claims_from_http_request, internal_server_error,
-    not_found_error, unauthorized_error,
+    not_found_error, password_error, password_policy_error, unauthorized_error,
 };
 use crate::models::{
-    ChangePasswordRequest, CreateUserRequest, UpdateUserRequest, User, UserListResponse,
-    UserQueryParams, UserResponse, UserRole,
+    normalize_organization_name, ChangePasswordRequest, Claims, CreateUserRequest,
+    EmailVerificationConfig, UpdateUserRequest, User, UserListResponse, UserQueryParams,
+    UserResponse, ROLE_USER,
 };
-
+use crate::password::{PasswordHasher, PasswordPolicy};
+use crate::services::email_verification_service;
+use crate::services::organization_service;
+use crate::services::password_history_service;
+use crate::services::rbac_service::{self, PermissionStore};
+use crate::services::revocation_service::RevocationStore;
+
+#[allow(clippy::too_many_arguments)]
 pub async fn register_user(
     pool: web::Data<PgPool>,
+    hasher: web::Data<PasswordHasher>,
+    policy: web::Data<PasswordPolicy>,
+    email_config: web::Data<EmailVerificationConfig>,
+    mailer: web::Data<dyn Mailer>,
     user_data: web::Json<CreateUserRequest>,
+    req: HttpRequest,
 ) -> Result<HttpResponse> {
+    let organization_name = match user_data.organizacao.as_deref() {
+        Some(name) => match normalize_organization_name(name) {
+            Some(name) => Some(name),
+            None => {
+                return Ok(bad_request_error(
+                    "O nome da organização deve ter entre 1 e 100 caracteres",
+                    "INVALID_ORGANIZATION_NAME",
+                ));
+            }
+        },
+        None => None,
+    };
+
+    let hashed_password = match validate_registration(
+        pool.get_ref(),
+        &hasher,
+        &policy,
+        &user_data.nome,
+        &user_data.email,
+        &user_data.senha,
+    )
+    .await
+    {
+        Ok(hashed) => hashed,
+        Err(response) => return Ok(response),
+    };
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        let user =
+            insert_user(&mut tx, &user_data.nome, &user_data.email, &hashed_password).await?;
+        let organization = match &organization_name {
+            Some(name) => {
+                Some(organization_service::create_with_owner(&mut tx, user.id, name).await?)
+            }
+            None => None,
+        };
+        tx.commit().await?;
+
+        Ok::<_, sqlx::Error>((user, organization))
+    }
+    .await;
+
+    match result {
+        Ok((user, organization)) => {
+            spawn_verification_email(&pool, &email_config, &mailer, &user, &req);
+
+            let user_response = UserResponse::new(user, vec![ROLE_USER.to_string()]);
+            Ok(HttpResponse::Created().json(serde_json::json!({
+                "message": "Usuário criado com sucesso",
+                "user": user_response,
+                "organization": organization
+            })))
+        }
+        Err(e) => {
+            eprintln!("Erro ao criar usuário: {:?}", e);
+            Ok(internal_server_error(
+                "Erro ao criar usuário",
+                "USER_CREATION_ERROR",
+            ))
+        }
+    }
+}
+
+// Valida a senha contra a política e a disponibilidade do email de um novo
+// cadastro, retornando o hash da senha
+pub async fn validate_registration(
+    pool: &PgPool,
+    hasher: &PasswordHasher,
+    policy: &PasswordPolicy,
+    nome: &str,
+    email: &str,
+    senha: &str,
+) -> std::result::Result<String, HttpResponse> {
+    let violations = policy.check(senha, nome, email);
+    if !violations.is_empty() {
+        return Err(password_policy_error(&violations));
+    }
+
     // Verificar se o email já existe
     let existing_user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
-        .bind(&user_data.email)
-        .fetch_optional(pool.get_ref())
+        .bind(email)
+        .fetch_optional(pool)
         .await;
 
     match existing_user {
         Ok(Some(_)) => {
-            return Ok(bad_request_error(
+            return Err(bad_request_error(
                 "Email já está em uso",
                 "EMAIL_ALREADY_EXISTS",
             ));
@@ -35,109 +128,162 @@ pub async fn register_user(
         }
         Err(e) => {
             eprintln!("Erro ao verificar email: {:?}", e);
-            return Ok(internal_server_error(
+            return Err(internal_server_error(
                 "Erro interno do servidor",
                 "DATABASE_ERROR",
             ));
         }
     }
 
-    // Hash da senha
-    let hashed_password = match hash(&user_data.senha, DEFAULT_COST) {
-        Ok(hashed) => hashed,
-        Err(e) => {
-            eprintln!("Erro ao fazer hash da senha: {:?}", e);
-            return Ok(internal_server_error(
-                "Erro interno do servidor",
-                "PASSWORD_HASH_ERROR",
-            ));
-        }
-    };
+    hasher
+        .hash(senha)
+        .await
+        .map_err(|e| password_error(&e, "PASSWORD_HASH_ERROR"))
+}
 
-    // Criar novo usuário
-    let new_user_id = Uuid::new_v4();
+// Cria o usuário com a role padrão, dentro da transação do chamador; outras
+// roles são atribuídas por um admin
+pub async fn insert_user(
+    conn: &mut PgConnection,
+    nome: &str,
+    email: &str,
+    password_hash: &str,
+) -> std::result::Result<User, sqlx::Error> {
     let now = Utc::now();
-    let user_role = user_data.role.clone().unwrap_or(UserRole::User);
 
-    let result = sqlx::query_as::<_, User>(
+    let user = sqlx::query_as::<_, User>(
         r#"
-        INSERT INTO users (id, nome, email, senha, role, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        INSERT INTO users (id, nome, email, senha, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
         RETURNING *
         "#,
     )
-    .bind(new_user_id)
-    .bind(&user_data.nome)
-    .bind(&user_data.email)
-    .bind(&hashed_password)
-    .bind(user_role)
+    .bind(Uuid::new_v4())
+    .bind(nome)
+    .bind(email)
+    .bind(password_hash)
     .bind(now)
     .bind(now)
-    .fetch_one(pool.get_ref())
-    .await;
+    .fetch_one(&mut *conn)
+    .await?;
 
-    match result {
-        Ok(user) => {
-            let user_response = UserResponse::from(user);
-            Ok(HttpResponse::Created().json(serde_json::json!({
-                "message": "Usuário criado com sucesso",
-                "user": user_response
-            })))
-        }
+    rbac_service::assign_role(conn, user.id, ROLE_USER).await?;
+
+    Ok(user)
+}
+
+// Organização pela qual as consultas de usuários são filtradas: a ativa do token para
+// quem não é administrador. Administradores do sistema (`users:admin`) e o próprio
+// usuário, ao acessar seus dados, não são filtrados.
+fn tenant_filter(claims: Option<&Claims>, user_id: Option<Uuid>) -> Option<Uuid> {
+    let claims = claims?;
+    if claims.is_admin() || (user_id.is_some() && claims.get_user_id().ok() == user_id) {
+        return None;
+    }
+    claims.get_organization_id()
+}
+
+// Usuário `user_id`, desde que visível para quem faz a requisição (ver `tenant_filter`);
+// fora da organização ativa a conta é tratada como inexistente
+async fn find_tenant_user(
+    pool: &PgPool,
+    req: &HttpRequest,
+    user_id: Uuid,
+) -> std::result::Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>(
+        r#"
+        SELECT * FROM users u
+        WHERE u.id = $1
+          AND ($2::UUID IS NULL OR EXISTS (
+              SELECT 1 FROM organization_members m
+              WHERE m.organization_id = $2 AND m.user_id = u.id
+          ))
+        "#,
+    )
+    .bind(user_id)
+    .bind(tenant_filter(
+        get_claims_from_http_request(req).as_ref(),
+        Some(user_id),
+    ))
+    .fetch_optional(pool)
+    .await
+}
+
+// Resposta com as roles atuais do usuário
+async fn user_response(
+    pool: &PgPool,
+    user: User,
+) -> std::result::Result<UserResponse, HttpResponse> {
+    match rbac_service::roles_for_user(pool, user.id).await {
+        Ok(roles) => Ok(UserResponse::new(user, roles)),
         Err(e) => {
-            eprintln!("Erro ao criar usuário: {:?}", e);
-            Ok(internal_server_error(
-                "Erro ao criar usuário",
-                "USER_CREATION_ERROR",
+            eprintln!("Erro ao buscar roles do usuário: {:?}", e);
+            Err(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
             ))
         }
     }
 }
 
+// Envia o email de verificação em segundo plano, sem atrasar a resposta
+fn spawn_verification_email(
+    pool: &web::Data<PgPool>,
+    email_config: &web::Data<EmailVerificationConfig>,
+    mailer: &web::Data<dyn Mailer>,
+    user: &User,
+    req: &HttpRequest,
+) {
+    let pool = pool.get_ref().clone();
+    let email_config = email_config.get_ref().clone();
+    let mailer = mailer.clone().into_inner();
+    let user = user.clone();
+    let locale = Locale::from_request(req);
+
+    actix_web::rt::spawn(async move {
+        if let Err(e) = email_verification_service::send_verification_email(
+            &pool,
+            mailer.as_ref(),
+            &email_config,
+            &user,
+            locale,
+        )
+        .await
+        {
+            eprintln!("Erro ao enviar email de verificação: {:?}", e);
+        }
+    });
+}
+
 // Listar usuários com paginação e busca (protegida por JWT - apenas admins)
 pub async fn list_users(
     pool: web::Data<PgPool>,
     query: web::Query<UserQueryParams>,
     req: HttpRequest,
 ) -> Result<HttpResponse> {
-    // Extrair claims do token JWT e verificar se é admin
-    if let Some(claims) = get_claims_from_http_request(&req) {
-        if !claims.is_admin() {
-            return Ok(forbidden_error(
-                "Acesso negado. Apenas administradores podem listar usuários.",
-                "ADMIN_REQUIRED",
-            ));
-        }
-    } else {
-        return Ok(unauthorized_error(
-            "Token JWT não encontrado",
-            "TOKEN_MISSING",
-        ));
-    }
     let page = query.page.unwrap_or(1).max(1);
-    let per_page = query.per_page.unwrap_or(10).min(100).max(1);
+    let per_page = query.per_page.unwrap_or(10).clamp(1, 100);
     let offset = (page - 1) * per_page;
 
-    // Construir query com busca opcional
-    let (where_clause, search_param) = if let Some(search) = &query.search {
-        (
-            "WHERE nome ILIKE $3 OR email ILIKE $3",
-            Some(format!("%{}%", search)),
-        )
-    } else {
-        ("", None)
-    };
+    let search = query.search.as_ref().map(|search| format!("%{}%", search));
+    let organization_id = tenant_filter(get_claims_from_http_request(&req).as_ref(), None);
+
+    // Busca opcional e, para quem não é administrador, filtro pela organização ativa
+    let where_clause = r#"
+        WHERE ($1::TEXT IS NULL OR u.nome ILIKE $1 OR u.email ILIKE $1)
+          AND ($2::UUID IS NULL OR EXISTS (
+              SELECT 1 FROM organization_members m
+              WHERE m.organization_id = $2 AND m.user_id = u.id
+          ))
+    "#;
 
     // Contar total de usuários
-    let count_query = format!("SELECT COUNT(*) FROM users {}", where_clause);
-    let total_result = if let Some(search) = &search_param {
-        sqlx::query_as(&count_query)
-            .bind(search)
+    let total_result =
+        sqlx::query_as::<_, (i64,)>(&format!("SELECT COUNT(*) FROM users u {}", where_clause))
+            .bind(&search)
+            .bind(organization_id)
             .fetch_one(pool.get_ref())
-            .await
-    } else {
-        sqlx::query_as(&count_query).fetch_one(pool.get_ref()).await
-    };
+            .await;
 
     let total: (i64,) = match total_result {
         Ok(count) => count,
@@ -151,25 +297,16 @@ pub async fn list_users(
     };
 
     // Buscar usuários
-    let users_query = format!(
-        "SELECT * FROM users {} ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+    let users_result = sqlx::query_as(&format!(
+        "SELECT u.* FROM users u {} ORDER BY u.created_at DESC LIMIT $3 OFFSET $4",
         where_clause
-    );
-
-    let users_result = if let Some(search) = &search_param {
-        sqlx::query_as(&users_query)
-            .bind(per_page)
-            .bind(offset)
-            .bind(search)
-            .fetch_all(pool.get_ref())
-            .await
-    } else {
-        sqlx::query_as(&users_query)
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(pool.get_ref())
-            .await
-    };
+    ))
+    .bind(&search)
+    .bind(organization_id)
+    .bind(per_page)
+    .bind(offset)
+    .fetch_all(pool.get_ref())
+    .await;
 
     let users: Vec<User> = match users_result {
         Ok(users_vec) => users_vec,
@@ -182,8 +319,26 @@ pub async fn list_users(
         }
     };
 
+    let user_ids: Vec<Uuid> = users.iter().map(|user| user.id).collect();
+    let mut roles = match rbac_service::roles_for_users(pool.get_ref(), &user_ids).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            eprintln!("Erro ao buscar roles dos usuários: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
     let total_pages = (total.0 as f64 / per_page as f64).ceil() as i64;
-    let user_responses: Vec<UserResponse> = users.into_iter().map(UserResponse::from).collect();
+    let user_responses: Vec<UserResponse> = users
+        .into_iter()
+        .map(|user| {
+            let user_roles = roles.remove(&user.id).unwrap_or_default();
+            UserResponse::new(user, user_roles)
+        })
+        .collect();
 
     let response = UserListResponse {
         users: user_responses,
@@ -204,29 +359,13 @@ pub async fn get_user(
 ) -> Result<HttpResponse> {
     let user_id = path.into_inner();
 
-    // Extrair claims do token JWT
-    if let Some(claims) = get_claims_from_http_request(&req) {
-        let requesting_user_id = claims.get_user_id().unwrap_or_default();
-
-        // Verificar se usuário está tentando acessar seus próprios dados ou é admin
-        if user_id != requesting_user_id && !claims.is_admin() {
-            return Ok(forbidden_error(
-                "Acesso negado. Você só pode ver seus próprios dados.",
-                "ACCESS_DENIED",
-            ));
-        }
-    }
-
-    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
-        .bind(user_id)
-        .fetch_optional(pool.get_ref())
-        .await;
+    let user = find_tenant_user(pool.get_ref(), &req, user_id).await;
 
     match user {
-        Ok(Some(user)) => {
-            let user_response = UserResponse::from(user);
-            Ok(HttpResponse::Ok().json(user_response))
-        }
+        Ok(Some(user)) => match user_response(pool.get_ref(), user).await {
+            Ok(user_response) => Ok(HttpResponse::Ok().json(user_response)),
+            Err(response) => Ok(response),
+        },
         Ok(None) => Ok(not_found_error("Usuário não encontrado", "USER_NOT_FOUND")),
         Err(e) => {
             eprintln!("Erro ao buscar usuário: {:?}", e);
@@ -239,42 +378,34 @@ pub async fn get_user(
 }
 
 // Atualizar usuário (protegida por JWT)
+#[allow(clippy::too_many_arguments)]
 pub async fn update_user(
     pool: web::Data<PgPool>,
+    hasher: web::Data<PasswordHasher>,
+    policy: web::Data<PasswordPolicy>,
+    revocation_store: web::Data<RevocationStore>,
+    email_config: web::Data<EmailVerificationConfig>,
+    mailer: web::Data<dyn Mailer>,
     path: web::Path<Uuid>,
     user_data: web::Json<UpdateUserRequest>,
     req: HttpRequest,
 ) -> Result<HttpResponse> {
     let user_id = path.into_inner();
 
-    // Extrair claims do token JWT
-    if let Some(claims) = get_claims_from_http_request(&req) {
-        let requesting_user_id = claims.get_user_id().unwrap_or_default();
-
-        // Verificar se usuário está tentando atualizar seus próprios dados ou é admin
-        if user_id != requesting_user_id && !claims.is_admin() {
-            return Ok(forbidden_error(
-                "Acesso negado. Você só pode atualizar seus próprios dados.",
-                "ACCESS_DENIED",
-            ));
-        }
+    // Acesso ao usuário verificado pela rota (ver `config`); roles são alteradas
+    // em `PUT /admin/users/{id}/roles`
 
-        // Verificar se usuário não-admin está tentando alterar role
-        if let Some(ref new_role) = user_data.role {
-            if !claims.is_admin() && *new_role != UserRole::User {
-                return Ok(forbidden_error(
-                    "Apenas administradores podem alterar roles de usuário.",
-                    "ADMIN_REQUIRED",
-                ));
-            }
-        }
+    // Senha e email dão acesso à conta: só podem ser alterados com o login do usuário
+    let delegated = get_claims_from_http_request(&req).is_some_and(|claims| claims.is_delegated());
+    if delegated && (user_data.senha.is_some() || user_data.email.is_some()) {
+        return Ok(forbidden_error(
+            "Senha e email só podem ser alterados com login por senha",
+            "API_KEY_NOT_ALLOWED",
+        ));
     }
 
-    // Verificar se usuário existe
-    let existing_user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
-        .bind(user_id)
-        .fetch_optional(pool.get_ref())
-        .await;
+    // Verificar se usuário existe na organização ativa
+    let existing_user = find_tenant_user(pool.get_ref(), &req, user_id).await;
 
     let current_user = match existing_user {
         Ok(Some(user)) => user,
@@ -324,31 +455,80 @@ pub async fn update_user(
     // Preparar dados para atualização
     let nome = user_data.nome.as_ref().unwrap_or(&current_user.nome);
     let email = user_data.email.as_ref().unwrap_or(&current_user.email);
-    let role = user_data.role.as_ref().unwrap_or(&current_user.role);
 
     // Hash da nova senha se fornecida
     let senha = if let Some(ref new_password) = user_data.senha {
-        match hash(new_password, DEFAULT_COST) {
-            Ok(hashed) => hashed,
-            Err(e) => {
-                eprintln!("Erro ao fazer hash da senha: {:?}", e);
-                return Ok(internal_server_error(
-                    "Erro interno do servidor",
-                    "PASSWORD_HASH_ERROR",
-                ));
-            }
+        match password_history_service::validate_new_password(
+            pool.get_ref(),
+            &hasher,
+            &policy,
+            &current_user,
+            new_password,
+            nome,
+            email,
+        )
+        .await
+        {
+            Ok(violations) if violations.is_empty() => {}
+            Ok(violations) => return Ok(password_policy_error(&violations)),
+            Err(e) => return Ok(password_error(&e, "PASSWORD_VERIFICATION_ERROR")),
         }
+
+        let hashed = match hasher.hash(new_password).await {
+            Ok(hashed) => hashed,
+            Err(e) => return Ok(password_error(&e, "PASSWORD_HASH_ERROR")),
+        };
+
+        hashed
     } else {
         current_user.senha.clone()
     };
 
+    // Troca de senha invalida os tokens já emitidos
+    let invalidate_tokens = user_data.senha.is_some();
+
+    // Senha definida por um admin para outra conta precisa ser trocada pelo usuário
+    let requesting_user_id = get_claims_from_http_request(&req)
+        .and_then(|claims| claims.get_user_id().ok())
+        .unwrap_or_default();
+    let must_change_password = match user_data.senha {
+        Some(_) => requesting_user_id != user_id,
+        None => current_user.must_change_password,
+    };
+
     let now = Utc::now();
 
+    // Histórico e nova senha são gravados juntos
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Erro ao iniciar transação: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    };
+
+    if user_data.senha.is_some() {
+        if let Err(e) = password_history_service::record(&mut tx, &policy, user_id).await {
+            eprintln!("Erro ao registrar histórico de senhas: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    }
+
     // Atualizar usuário
     let updated_user = sqlx::query_as::<_, User>(
         r#"
         UPDATE users
-        SET nome = $1, email = $2, senha = $3, role = $4, updated_at = $5
+        SET nome = $1, email = $2, senha = $3, updated_at = $4,
+            token_version = CASE WHEN $5 THEN token_version + 1 ELSE token_version END,
+            email_verified_at = CASE WHEN email = $2 THEN email_verified_at ELSE NULL END,
+            password_changed_at = CASE WHEN $7 THEN $4 ELSE password_changed_at END,
+            must_change_password = $8
         WHERE id = $6
         RETURNING *
         "#,
@@ -356,15 +536,43 @@ pub async fn update_user(
     .bind(nome)
     .bind(email)
     .bind(&senha)
-    .bind(role)
     .bind(now)
+    .bind(invalidate_tokens)
     .bind(user_id)
-    .fetch_one(pool.get_ref())
+    .bind(user_data.senha.is_some())
+    .bind(must_change_password)
+    .fetch_one(&mut *tx)
     .await;
 
+    let updated_user = match updated_user {
+        Ok(user) => tx.commit().await.map(|_| user),
+        Err(e) => Err(e),
+    };
+
     match updated_user {
         Ok(user) => {
-            let user_response = UserResponse::from(user);
+            if invalidate_tokens {
+                if let Err(e) = revocation_store
+                    .token_version_changed(user.id, user.token_version)
+                    .await
+                {
+                    eprintln!("Erro ao revogar sessões do usuário: {:?}", e);
+                    return Ok(internal_server_error(
+                        "Erro interno do servidor",
+                        "DATABASE_ERROR",
+                    ));
+                }
+            }
+
+            // Novo email precisa ser verificado novamente
+            if user.email != current_user.email {
+                spawn_verification_email(&pool, &email_config, &mailer, &user, &req);
+            }
+
+            let user_response = match user_response(pool.get_ref(), user).await {
+                Ok(user_response) => user_response,
+                Err(response) => return Ok(response),
+            };
             Ok(HttpResponse::Ok().json(serde_json::json!({
                 "message": "Usuário atualizado com sucesso",
                 "user": user_response
@@ -383,30 +591,30 @@ pub async fn update_user(
 // Alterar senha do usuário (protegida por JWT)
 pub async fn change_password(
     pool: web::Data<PgPool>,
+    hasher: web::Data<PasswordHasher>,
+    policy: web::Data<PasswordPolicy>,
+    revocation_store: web::Data<RevocationStore>,
     path: web::Path<Uuid>,
     password_data: web::Json<ChangePasswordRequest>,
     req: HttpRequest,
 ) -> Result<HttpResponse> {
     let user_id = path.into_inner();
 
-    // Extrair claims do token JWT
+    // Acesso ao usuário verificado pela rota (ver `config`)
     if let Some(claims) = get_claims_from_http_request(&req) {
         let requesting_user_id = claims.get_user_id().unwrap_or_default();
 
-        // Verificar se usuário está tentando alterar sua própria senha ou é admin
-        if user_id != requesting_user_id && !claims.is_admin() {
+        // Token restrito só permite trocar a própria senha
+        if claims.must_change_password && user_id != requesting_user_id {
             return Ok(forbidden_error(
-                "Acesso negado. Você só pode alterar sua própria senha.",
-                "ACCESS_DENIED",
+                "Altere sua senha para acessar este recurso",
+                "PASSWORD_CHANGE_REQUIRED",
             ));
         }
     }
 
     // Buscar usuário atual
-    let current_user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
-        .bind(user_id)
-        .fetch_optional(pool.get_ref())
-        .await;
+    let current_user = find_tenant_user(pool.get_ref(), &req, user_id).await;
 
     let user = match current_user {
         Ok(Some(user)) => user,
@@ -423,15 +631,9 @@ pub async fn change_password(
     };
 
     // Verificar senha atual
-    let password_valid = match verify(&password_data.senha_atual, &user.senha) {
-        Ok(valid) => valid,
-        Err(e) => {
-            eprintln!("Erro ao verificar senha: {:?}", e);
-            return Ok(internal_server_error(
-                "Erro interno do servidor",
-                "PASSWORD_VERIFICATION_ERROR",
-            ));
-        }
+    let password_valid = match hasher.verify(&password_data.senha_atual, &user.senha).await {
+        Ok(check) => check.is_valid(),
+        Err(e) => return Ok(password_error(&e, "PASSWORD_VERIFICATION_ERROR")),
     };
 
     if !password_valid {
@@ -441,32 +643,55 @@ pub async fn change_password(
         ));
     }
 
+    // Validar a nova senha contra a política e o histórico
+    match password_history_service::validate_new_password(
+        pool.get_ref(),
+        &hasher,
+        &policy,
+        &user,
+        &password_data.senha_nova,
+        &user.nome,
+        &user.email,
+    )
+    .await
+    {
+        Ok(violations) if violations.is_empty() => {}
+        Ok(violations) => return Ok(password_policy_error(&violations)),
+        Err(e) => return Ok(password_error(&e, "PASSWORD_VERIFICATION_ERROR")),
+    }
+
     // Hash da nova senha
-    let new_password_hash = match hash(&password_data.senha_nova, DEFAULT_COST) {
+    let new_password_hash = match hasher.hash(&password_data.senha_nova).await {
         Ok(hashed) => hashed,
-        Err(e) => {
-            eprintln!("Erro ao fazer hash da senha: {:?}", e);
-            return Ok(internal_server_error(
-                "Erro interno do servidor",
-                "PASSWORD_HASH_ERROR",
-            ));
-        }
+        Err(e) => return Ok(password_error(&e, "PASSWORD_HASH_ERROR")),
     };
 
-    let now = Utc::now();
-
-    // Atualizar senha
-    let result = sqlx::query("UPDATE users SET senha = $1, updated_at = $2 WHERE id = $3")
-        .bind(&new_password_hash)
-        .bind(now)
-        .bind(user_id)
-        .execute(pool.get_ref())
-        .await;
+    // Atualizar senha e histórico e invalidar tokens emitidos com a senha anterior
+    let result = password_history_service::change_password(
+        pool.get_ref(),
+        &policy,
+        user_id,
+        &new_password_hash,
+    )
+    .await;
 
     match result {
-        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
-            "message": "Senha alterada com sucesso"
-        }))),
+        Ok(token_version) => {
+            if let Err(e) = revocation_store
+                .token_version_changed(user_id, token_version)
+                .await
+            {
+                eprintln!("Erro ao revogar sessões do usuário: {:?}", e);
+                return Ok(internal_server_error(
+                    "Erro interno do servidor",
+                    "DATABASE_ERROR",
+                ));
+            }
+
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "message": "Senha alterada com sucesso. Faça login novamente."
+            })))
+        }
         Err(e) => {
             eprintln!("Erro ao alterar senha: {:?}", e);
             Ok(internal_server_error(
@@ -480,26 +705,15 @@ pub async fn change_password(
 // Deletar usuário (apenas admins)
 pub async fn delete_user(
     pool: web::Data<PgPool>,
+    revocation_store: web::Data<RevocationStore>,
+    permission_store: web::Data<PermissionStore>,
     path: web::Path<Uuid>,
     req: HttpRequest,
 ) -> Result<HttpResponse> {
     let user_id = path.into_inner();
 
-    // Extrair claims do token JWT e verificar se é admin
-    if let Some(claims) = get_claims_from_http_request(&req) {
-        if !claims.is_admin() {
-            return Ok(forbidden_error(
-                "Acesso negado. Apenas administradores podem deletar usuários.",
-                "ADMIN_REQUIRED",
-            ));
-        }
-    }
-
-    // Verificar se usuário existe
-    let user_exists = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
-        .bind(user_id)
-        .fetch_optional(pool.get_ref())
-        .await;
+    // Verificar se usuário existe na organização ativa
+    let user_exists = find_tenant_user(pool.get_ref(), &req, user_id).await;
 
     let _user = match user_exists {
         Ok(Some(user)) => user,
@@ -522,9 +736,15 @@ pub async fn delete_user(
         .await;
 
     match result {
-        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
-            "message": "Usuário deletado com sucesso"
-        }))),
+        Ok(_) => {
+            // Tokens do usuário removido deixam de valer imediatamente nesta instância
+            revocation_store.forget_user(user_id);
+            permission_store.invalidate_user(user_id);
+
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "message": "Usuário deletado com sucesso"
+            })))
+        }
         Err(e) => {
             eprintln!("Erro ao deletar usuário: {:?}", e);
             Ok(internal_server_error(
@@ -535,6 +755,46 @@ pub async fn delete_user(
     }
 }
 
+// Encerrar todas as sessões de um usuário (protegida por JWT)
+pub async fn revoke_user_sessions(
+    pool: web::Data<PgPool>,
+    revocation_store: web::Data<RevocationStore>,
+    path: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    let user_id = path.into_inner();
+
+    // Verificar se usuário existe na organização ativa
+    let user_exists = find_tenant_user(pool.get_ref(), &req, user_id).await;
+
+    match user_exists {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Ok(not_found_error("Usuário não encontrado", "USER_NOT_FOUND"));
+        }
+        Err(e) => {
+            eprintln!("Erro ao buscar usuário: {:?}", e);
+            return Ok(internal_server_error(
+                "Erro interno do servidor",
+                "DATABASE_ERROR",
+            ));
+        }
+    }
+
+    match revocation_store.revoke_all_for_user(user_id).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Todas as sessões do usuário foram encerradas"
+        }))),
+        Err(e) => {
+            eprintln!("Erro ao revogar sessões: {:?}", e);
+            Ok(internal_server_error(
+                "Erro ao encerrar sessões",
+                "DATABASE_ERROR",
+            ))
+        }
+    }
+}
+
 // Endpoint para obter dados do usuário logado
 pub async fn get_current_user(pool: web::Data<PgPool>, req: HttpRequest) -> Result<HttpResponse> {
     // Extrair claims do token JWT
@@ -555,10 +815,10 @@ pub async fn get_current_user(pool: web::Data<PgPool>, req: HttpRequest) -> Resu
             .await;
 
         match user {
-            Ok(Some(user)) => {
-                let user_response = UserResponse::from(user);
-                Ok(HttpResponse::Ok().json(user_response))
-            }
+            Ok(Some(user)) => match user_response(pool.get_ref(), user).await {
+                Ok(user_response) => Ok(HttpResponse::Ok().json(user_response)),
+                Err(response) => Ok(response),
+            },
             Ok(None) => Ok(not_found_error("Usuário não encontrado", "USER_NOT_FOUND")),
             Err(e) => {
                 eprintln!("Erro ao buscar usuário atual: {:?}", e);
@@ -577,8 +837,9 @@ pub async fn get_current_user(pool: web::Data<PgPool>, req: HttpRequest) -> Resu
 }
 
 pub fn config(cfg: &mut web::ServiceConfig) {
-    use crate::middleware::{admin_required, jwt_validator};
-    use actix_web_httpauth::middleware::HttpAuthentication;
+    use crate::handlers::{api_key_handler, rbac_handler, two_factor_handler};
+    use crate::middleware::require_permissions;
+    use crate::models::{PERMISSION_USERS_ADMIN, PERMISSION_USERS_READ, PERMISSION_USERS_WRITE};
 
     cfg.service(
         web::scope("/users")
@@ -587,44 +848,131 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             // Rota /me deve vir antes de /{id} para evitar conflito de parsing
             .route(
                 "/me",
-                web::get()
-                    .to(get_current_user)
-                    .wrap(HttpAuthentication::bearer(jwt_validator)),
+                web::get().to(get_current_user).wrap(
+                    require_permissions(&[PERMISSION_USERS_READ])
+                        .allow_unverified_email()
+                        .middleware(),
+                ),
+            )
+            // Autenticação em dois fatores do próprio usuário
+            .service(
+                web::scope("/me/2fa")
+                    .wrap(
+                        require_permissions(&[PERMISSION_USERS_WRITE])
+                            .interactive_only()
+                            .middleware(),
+                    )
+                    .configure(two_factor_handler::config),
+            )
+            // Chaves de API do próprio usuário
+            .service(
+                web::scope("/me/api-keys")
+                    .wrap(require_permissions(&[PERMISSION_USERS_WRITE]).middleware())
+                    .configure(api_key_handler::config),
             )
-            // Rota para mudança de senha
+            // Rota para mudança de senha (próprio usuário ou admin)
             .route(
                 "/{id}/change-password",
-                web::patch()
-                    .to(change_password)
-                    .wrap(HttpAuthentication::bearer(jwt_validator)),
+                web::patch().to(change_password).wrap(
+                    require_permissions(&[PERMISSION_USERS_WRITE])
+                        .owner_or_admin()
+                        .allow_password_change()
+                        .interactive_only()
+                        .middleware(),
+                ),
+            )
+            // Rota para encerrar todas as sessões de um usuário (próprio usuário ou admin)
+            .route(
+                "/{id}/sessions",
+                web::delete().to(revoke_user_sessions).wrap(
+                    require_permissions(&[PERMISSION_USERS_WRITE])
+                        .owner_or_admin()
+                        .interactive_only()
+                        .middleware(),
+                ),
+            )
+            // Permissões efetivas e suas origens (próprio usuário ou admin)
+            .route(
+                "/{id}/effective-permissions",
+                web::get().to(rbac_handler::get_effective_permissions).wrap(
+                    require_permissions(&[PERMISSION_USERS_READ])
+                        .owner_or_admin()
+                        .middleware(),
+                ),
             )
             // Rota para listar todos os usuários (requer admin)
             .route(
                 "",
-                web::get()
-                    .to(list_users)
-                    .wrap(HttpAuthentication::bearer(admin_required)),
+                web::get().to(list_users).wrap(
+                    require_permissions(&[PERMISSION_USERS_READ, PERMISSION_USERS_ADMIN])
+                        .middleware(),
+                ),
             )
             // Rota para criar usuário (público)
             .route("", web::post().to(register_user))
-            // Rotas para operações em usuário específico
+            // Rotas para operações em usuário específico (próprio usuário ou admin)
             .route(
                 "/{id}",
-                web::get()
-                    .to(get_user)
-                    .wrap(HttpAuthentication::bearer(jwt_validator)),
+                web::get().to(get_user).wrap(
+                    require_permissions(&[PERMISSION_USERS_READ])
+                        .owner_or_admin()
+                        .middleware(),
+                ),
             )
             .route(
                 "/{id}",
-                web::put()
-                    .to(update_user)
-                    .wrap(HttpAuthentication::bearer(jwt_validator)),
+                web::put().to(update_user).wrap(
+                    require_permissions(&[PERMISSION_USERS_WRITE])
+                        .owner_or_admin()
+                        .middleware(),
+                ),
             )
             .route(
                 "/{id}",
-                web::delete()
-                    .to(delete_user)
-                    .wrap(HttpAuthentication::bearer(admin_required)),
+                web::delete().to(delete_user).wrap(
+                    require_permissions(&[PERMISSION_USERS_WRITE, PERMISSION_USERS_ADMIN])
+                        .middleware(),
+                ),
             ),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PERMISSION_USERS_ADMIN, PERMISSION_USERS_READ, ROLE_ADMIN};
+
+    #[test]
+    fn test_tenant_filter() {
+        let organization_id = Uuid::new_v4();
+        let claims = |role: &str, permissions: &[&str]| {
+            let mut claims = Claims::new(
+                Uuid::new_v4(),
+                "user@example.com".to_string(),
+                "User".to_string(),
+                vec![role.to_string()],
+                0,
+                3600,
+            );
+            claims.permissions = permissions.iter().map(|p| p.to_string()).collect();
+            claims.org_id = Some(organization_id.to_string());
+            claims
+        };
+        let other_id = Some(Uuid::new_v4());
+
+        // Administradores do sistema não são limitados pela organização ativa
+        let admin = claims(ROLE_ADMIN, &[PERMISSION_USERS_READ, PERMISSION_USERS_ADMIN]);
+        assert_eq!(tenant_filter(Some(&admin), None), None);
+        assert_eq!(tenant_filter(Some(&admin), other_id), None);
+
+        let mut user = claims(ROLE_USER, &[PERMISSION_USERS_READ]);
+        let user_id = user.get_user_id().ok();
+        assert_eq!(tenant_filter(Some(&user), user_id), None);
+        assert_eq!(tenant_filter(Some(&user), other_id), Some(organization_id));
+        assert_eq!(tenant_filter(Some(&user), None), Some(organization_id));
+
+        user.org_id = None;
+        assert_eq!(tenant_filter(Some(&user), other_id), None);
+        assert_eq!(tenant_filter(None, other_id), None);
+    }
+}
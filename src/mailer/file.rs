@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::message::Mailbox;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use super::{Email, Mailer};
+
+// Grava cada email como um arquivo .eml no diretório informado (desenvolvimento e testes)
+pub struct FileMailer {
+    from: Mailbox,
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(from: Mailbox, dir: PathBuf) -> Self {
+        Self { from, dir }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> anyhow::Result<()> {
+        let message = email.to_message(&self.from)?;
+        let file_name = format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            Uuid::new_v4()
+        );
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.dir.join(file_name), message.formatted()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_mailer_writes_eml() {
+        let dir = std::env::temp_dir().join(format!("mail-{}", Uuid::new_v4()));
+        let mailer = FileMailer::new("no-reply@api.local".parse().unwrap(), dir.clone());
+
+        mailer
+            .send(Email {
+                to: "user@example.com".to_string(),
+                subject: "Teste".to_string(),
+                body: "Corpo".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(files.len(), 1);
+
+        let path = files[0].as_ref().unwrap().path();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(path.extension().and_then(|ext| ext.to_str()), Some("eml"));
+        assert!(content.contains("To: user@example.com"));
+        assert!(content.contains("Subject: Teste"));
+    }
+}
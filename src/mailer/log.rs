@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use super::{Email, Mailer};
+
+// Apenas registra os emails no log (desenvolvimento local)
+#[derive(Debug, Default, Clone)]
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: Email) -> anyhow::Result<()> {
+        tracing::info!(to = %email.to, subject = %email.subject, "email enviado");
+        println!(
+            "📧 Para: {}\n   Assunto: {}\n\n{}\n",
+            email.to, email.subject, email.body
+        );
+        Ok(())
+    }
+}
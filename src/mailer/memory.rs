@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+
+use super::{Email, Mailer};
+
+// Guarda os emails em memória, para inspeção em testes
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryMailer {
+    sent: Arc<Mutex<Vec<Email>>>,
+}
+
+impl InMemoryMailer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sent(&self) -> Vec<Email> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    pub fn last_to(&self, to: &str) -> Option<Email> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|email| email.to == to)
+            .cloned()
+    }
+}
+
+#[async_trait]
+impl Mailer for InMemoryMailer {
+    async fn send(&self, email: Email) -> anyhow::Result<()> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_memory_mailer_records_emails() {
+        let mailer = InMemoryMailer::new();
+        let shared: Arc<dyn Mailer> = Arc::new(mailer.clone());
+
+        for to in ["a@example.com", "b@example.com", "a@example.com"] {
+            shared
+                .send(Email {
+                    to: to.to_string(),
+                    subject: format!("Para {}", to),
+                    body: String::new(),
+                })
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(mailer.sent().len(), 3);
+        assert_eq!(
+            mailer.last_to("b@example.com").unwrap().subject,
+            "Para b@example.com"
+        );
+        assert!(mailer.last_to("c@example.com").is_none());
+    }
+}
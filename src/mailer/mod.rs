@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::Message;
+
+mod file;
+mod log;
+#[cfg(test)]
+mod memory;
+mod smtp;
+mod templates;
+
+pub use file::FileMailer;
+pub use log::LogMailer;
+pub use smtp::SmtpMailer;
+pub use templates::{render, Locale, MessageKind};
+
+// Mensagem de email em texto puro
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Email {
+    // Monta a mensagem MIME usada pelos transportes SMTP e de arquivo
+    fn to_message(&self, from: &Mailbox) -> anyhow::Result<Message> {
+        Ok(Message::builder()
+            .from(from.clone())
+            .to(self.to.parse()?)
+            .subject(self.subject.clone())
+            .header(ContentType::TEXT_PLAIN)
+            .body(self.body.clone())?)
+    }
+}
+
+// Transporte de emails de saída. Registrado como `web::Data<dyn Mailer>`.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> anyhow::Result<()>;
+}
@@ -0,0 +1,180 @@
+use actix_web::{http::header::ACCEPT_LANGUAGE, HttpRequest};
+
+use super::Email;
+
+// Idioma dos emails enviados
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    Pt,
+    En,
+}
+
+impl Locale {
+    // Escolhe o idioma suportado de maior peso em `Accept-Language`
+    // (ex.: "en-US,en;q=0.9,pt;q=0.8"); sem correspondência, usa português
+    pub fn from_accept_language(header: Option<&str>) -> Self {
+        let mut best: Option<(f32, Locale)> = None;
+
+        for entry in header.unwrap_or_default().split(',') {
+            let mut parts = entry.trim().split(';');
+            let tag = parts.next().unwrap_or_default().trim().to_lowercase();
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            let locale = match tag.split('-').next() {
+                Some("pt") => Locale::Pt,
+                Some("en") => Locale::En,
+                _ => continue,
+            };
+
+            if weight > 0.0 && best.is_none_or(|(best_weight, _)| weight > best_weight) {
+                best = Some((weight, locale));
+            }
+        }
+
+        best.map(|(_, locale)| locale).unwrap_or_default()
+    }
+
+    pub fn from_request(req: &HttpRequest) -> Self {
+        Self::from_accept_language(
+            req.headers()
+                .get(ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok()),
+        )
+    }
+
+    // Duração legível ("24 horas", "15 minutes")
+    pub fn format_duration(self, seconds: i64) -> String {
+        let (value, unit) = if seconds >= 3600 && seconds % 3600 == 0 {
+            (seconds / 3600, self.unit("hora", "hour"))
+        } else {
+            ((seconds / 60).max(1), self.unit("minuto", "minute"))
+        };
+        let plural = if value == 1 { "" } else { "s" };
+        format!("{} {}{}", value, unit, plural)
+    }
+
+    fn unit(self, pt: &'static str, en: &'static str) -> &'static str {
+        match self {
+            Locale::Pt => pt,
+            Locale::En => en,
+        }
+    }
+}
+
+// Tipos de mensagem; cada um possui um template por idioma em `templates/<idioma>/`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    PasswordReset,
+    EmailVerification,
+    OrganizationInvitation,
+}
+
+impl MessageKind {
+    fn template(self, locale: Locale) -> &'static str {
+        match (self, locale) {
+            (MessageKind::PasswordReset, Locale::Pt) => {
+                include_str!("templates/pt/password_reset.txt")
+            }
+            (MessageKind::PasswordReset, Locale::En) => {
+                include_str!("templates/en/password_reset.txt")
+            }
+            (MessageKind::EmailVerification, Locale::Pt) => {
+                include_str!("templates/pt/email_verification.txt")
+            }
+            (MessageKind::EmailVerification, Locale::En) => {
+                include_str!("templates/en/email_verification.txt")
+            }
+            (MessageKind::OrganizationInvitation, Locale::Pt) => {
+                include_str!("templates/pt/organization_invitation.txt")
+            }
+            (MessageKind::OrganizationInvitation, Locale::En) => {
+                include_str!("templates/en/organization_invitation.txt")
+            }
+        }
+    }
+}
+
+// Renderiza o template substituindo `{{variavel}}`. A primeira linha do
+// template ("Subject: ...") é o assunto; o restante, após a linha em branco, é o corpo.
+pub fn render(kind: MessageKind, locale: Locale, to: &str, vars: &[(&str, &str)]) -> Email {
+    let mut text = kind.template(locale).to_string();
+    for (name, value) in vars {
+        text = text.replace(&format!("{{{{{}}}}}", name), value);
+    }
+
+    let (subject, body) = text.split_once('\n').unwrap_or((&text, ""));
+
+    Email {
+        to: to.to_string(),
+        subject: subject
+            .strip_prefix("Subject:")
+            .unwrap_or(subject)
+            .trim()
+            .to_string(),
+        body: body.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [MessageKind; 3] = [
+        MessageKind::PasswordReset,
+        MessageKind::EmailVerification,
+        MessageKind::OrganizationInvitation,
+    ];
+
+    #[test]
+    fn test_render_substitutes_all_variables() {
+        for kind in KINDS {
+            for locale in [Locale::Pt, Locale::En] {
+                let email = render(
+                    kind,
+                    locale,
+                    "user@example.com",
+                    &[
+                        ("nome", "Maria"),
+                        ("action", "https://app/x?token=abc"),
+                        ("expires_in", "1 hora"),
+                        ("organizacao", "Acme"),
+                        ("papel", "admin"),
+                    ],
+                );
+
+                assert_eq!(email.to, "user@example.com");
+                assert!(!email.subject.is_empty() && !email.subject.contains("Subject"));
+                assert!(email.body.contains("Maria"));
+                assert!(email.body.contains("https://app/x?token=abc"));
+                assert!(!email.body.contains("{{"), "{:?} {:?}", kind, locale);
+            }
+        }
+    }
+
+    #[test]
+    fn test_locale_from_accept_language() {
+        assert_eq!(Locale::from_accept_language(None), Locale::Pt);
+        assert_eq!(Locale::from_accept_language(Some("en-US")), Locale::En);
+        assert_eq!(
+            Locale::from_accept_language(Some("fr-FR,en;q=0.8,pt-BR;q=0.9")),
+            Locale::Pt
+        );
+        assert_eq!(
+            Locale::from_accept_language(Some("de,en;q=0.5")),
+            Locale::En
+        );
+        assert_eq!(Locale::from_accept_language(Some("de")), Locale::Pt);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(Locale::Pt.format_duration(86400), "24 horas");
+        assert_eq!(Locale::En.format_duration(3600), "1 hour");
+        assert_eq!(Locale::Pt.format_duration(900), "15 minutos");
+        assert_eq!(Locale::En.format_duration(60), "1 minute");
+    }
+}
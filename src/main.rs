@@ -5,14 +5,31 @@ use tracing_actix_web::TracingLogger;
 
 mod config;
 mod handlers;
+mod mailer;
 mod middleware;
 mod models;
+mod password;
+mod services;
 mod telemetry;
 
+use config::bootstrap::bootstrap_admin_from_env;
 use config::database::{create_pool, run_migrations};
-use handlers::{auth_handler, user_handler};
-use middleware::{custom_rate_limiter, rate_limit_middleware};
-use models::JwtConfig;
+use config::jwt::load_key_ring;
+use config::mail::mailer_from_env;
+use config::oidc::oidc_config_from_env;
+use config::password::password_policy_from_env;
+use handlers::{admin_handler, auth_handler, oauth_handler, organization_handler, user_handler};
+use middleware::{custom_rate_limiter, rate_limit_middleware, TrustedProxies};
+use models::{
+    EmailVerificationConfig, EmailVerificationMode, InvitationConfig, JwtConfig,
+    LoginThrottleConfig, OAuthConfig, PasswordResetConfig, TwoFactorConfig,
+};
+use password::{PasswordConfig, PasswordHasher};
+use services::bootstrap_service::{self, BootstrapOutcome};
+use services::oauth_service::OAuthClientStore;
+use services::oidc_service::OidcClient;
+use services::rbac_service::PermissionStore;
+use services::revocation_service::RevocationStore;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -34,14 +51,241 @@ async fn main() -> std::io::Result<()> {
         .expect("Falha ao executar migrações");
 
     // Configurar JWT
-    let jwt_secret = env::var("JWT_SECRET")
-        .unwrap_or_else(|_| "your-secret-key-change-this-in-production".to_string());
     let jwt_expiration = env::var("JWT_EXPIRATION")
         .unwrap_or_else(|_| "3600".to_string())
         .parse::<i64>()
         .expect("JWT_EXPIRATION deve ser um número válido");
 
-    let jwt_config = JwtConfig::new(jwt_secret, jwt_expiration);
+    let refresh_expiration = env::var("REFRESH_TOKEN_EXPIRATION")
+        .unwrap_or_else(|_| "2592000".to_string())
+        .parse::<i64>()
+        .expect("REFRESH_TOKEN_EXPIRATION deve ser um número válido");
+
+    // Chave ativa + chaves aceitas apenas na verificação (rotação sem logout geral)
+    let (jwt_keys, jwt_keys_dir) = load_key_ring().expect("Falha ao carregar chaves JWT");
+    // Emissor/audiência evitam que tokens de um ambiente valham em outro que use a mesma chave
+    let jwt_issuer = env::var("JWT_ISSUER")
+        .ok()
+        .filter(|value| !value.is_empty());
+    let jwt_audience = env::var("JWT_AUDIENCE")
+        .ok()
+        .filter(|value| !value.is_empty());
+    let jwt_leeway = env::var("JWT_LEEWAY")
+        .unwrap_or_else(|_| "60".to_string())
+        .parse::<u64>()
+        .expect("JWT_LEEWAY deve ser um número válido");
+
+    let jwt_config = JwtConfig::with_key_ring(jwt_keys, jwt_expiration)
+        .with_refresh_expiration(refresh_expiration)
+        .with_keys_dir(jwt_keys_dir)
+        .with_issuer(jwt_issuer)
+        .with_audience(jwt_audience)
+        .with_leeway(jwt_leeway);
+
+    // Configurar redefinição de senha
+    let password_reset_config = PasswordResetConfig {
+        token_ttl_seconds: env::var("PASSWORD_RESET_TOKEN_EXPIRATION")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse::<i64>()
+            .expect("PASSWORD_RESET_TOKEN_EXPIRATION deve ser um número válido"),
+        reset_url: env::var("PASSWORD_RESET_URL")
+            .ok()
+            .filter(|value| !value.is_empty()),
+    };
+
+    // Configurar verificação de email
+    let email_verification_config = EmailVerificationConfig {
+        mode: env::var("EMAIL_VERIFICATION_MODE")
+            .unwrap_or_else(|_| "off".to_string())
+            .parse::<EmailVerificationMode>()
+            .expect("EMAIL_VERIFICATION_MODE deve ser off, restricted ou required"),
+        token_ttl_seconds: env::var("EMAIL_VERIFICATION_TOKEN_EXPIRATION")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse::<i64>()
+            .expect("EMAIL_VERIFICATION_TOKEN_EXPIRATION deve ser um número válido"),
+        verify_url: env::var("EMAIL_VERIFICATION_URL")
+            .ok()
+            .filter(|value| !value.is_empty()),
+    };
+
+    // Configurar convites para organizações
+    let invitation_config = InvitationConfig {
+        token_ttl_seconds: env::var("INVITATION_TOKEN_EXPIRATION")
+            .unwrap_or_else(|_| "604800".to_string())
+            .parse::<i64>()
+            .expect("INVITATION_TOKEN_EXPIRATION deve ser um número válido"),
+        accept_url: env::var("INVITATION_URL")
+            .ok()
+            .filter(|value| !value.is_empty()),
+    };
+
+    // Configurar servidor de autorização OAuth 2.0
+    let oauth_config = OAuthConfig {
+        code_ttl_seconds: env::var("OAUTH_CODE_EXPIRATION")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse::<i64>()
+            .expect("OAUTH_CODE_EXPIRATION deve ser um número válido"),
+    };
+
+    // Login via provedor OpenID Connect (OIDC_*), desativado sem OIDC_ISSUER
+    let oidc_client = oidc_config_from_env()
+        .expect("Falha ao configurar o login OIDC")
+        .map(|config| OidcClient::new(config).expect("Falha ao criar o cliente OIDC"));
+
+    // Configurar autenticação em dois fatores
+    let two_factor_config = TwoFactorConfig {
+        issuer: env::var("TOTP_ISSUER").unwrap_or_else(|_| "API Rust".to_string()),
+        challenge_ttl_seconds: env::var("TWO_FACTOR_CHALLENGE_EXPIRATION")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse::<i64>()
+            .expect("TWO_FACTOR_CHALLENGE_EXPIRATION deve ser um número válido"),
+        max_attempts: env::var("TWO_FACTOR_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<i32>()
+            .expect("TWO_FACTOR_MAX_ATTEMPTS deve ser um número válido"),
+    };
+
+    // Configurar controle de tentativas de login
+    let login_throttle_config = LoginThrottleConfig {
+        backoff_after: env::var("LOGIN_BACKOFF_AFTER")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<i32>()
+            .expect("LOGIN_BACKOFF_AFTER deve ser um número válido"),
+        backoff_base_seconds: env::var("LOGIN_BACKOFF_BASE_SECONDS")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse::<i64>()
+            .expect("LOGIN_BACKOFF_BASE_SECONDS deve ser um número válido"),
+        backoff_max_seconds: env::var("LOGIN_BACKOFF_MAX_SECONDS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<i64>()
+            .expect("LOGIN_BACKOFF_MAX_SECONDS deve ser um número válido"),
+        account_lock_threshold: env::var("LOGIN_LOCK_THRESHOLD")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<i32>()
+            .expect("LOGIN_LOCK_THRESHOLD deve ser um número válido"),
+        ip_lock_threshold: env::var("LOGIN_IP_LOCK_THRESHOLD")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse::<i32>()
+            .expect("LOGIN_IP_LOCK_THRESHOLD deve ser um número válido"),
+        lock_seconds: env::var("LOGIN_LOCK_DURATION")
+            .unwrap_or_else(|_| "900".to_string())
+            .parse::<i64>()
+            .expect("LOGIN_LOCK_DURATION deve ser um número válido"),
+        reset_after_seconds: env::var("LOGIN_FAILURE_RESET")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse::<i64>()
+            .expect("LOGIN_FAILURE_RESET deve ser um número válido"),
+    };
+
+    // Configurar hash de senhas (Argon2id). Por padrão, um hash simultâneo por CPU.
+    let default_password_config = PasswordConfig::default();
+    let password_config = PasswordConfig {
+        memory_kib: env::var("ARGON2_MEMORY_KIB")
+            .unwrap_or_else(|_| "19456".to_string())
+            .parse::<u32>()
+            .expect("ARGON2_MEMORY_KIB deve ser um número válido"),
+        iterations: env::var("ARGON2_ITERATIONS")
+            .unwrap_or_else(|_| "2".to_string())
+            .parse::<u32>()
+            .expect("ARGON2_ITERATIONS deve ser um número válido"),
+        parallelism: env::var("ARGON2_PARALLELISM")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse::<u32>()
+            .expect("ARGON2_PARALLELISM deve ser um número válido"),
+        max_concurrent: env::var("PASSWORD_HASH_CONCURRENCY")
+            .unwrap_or_else(|_| default_password_config.max_concurrent.to_string())
+            .parse::<usize>()
+            .expect("PASSWORD_HASH_CONCURRENCY deve ser um número válido"),
+        queue_timeout: std::time::Duration::from_millis(
+            env::var("PASSWORD_HASH_QUEUE_TIMEOUT_MS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse::<u64>()
+                .expect("PASSWORD_HASH_QUEUE_TIMEOUT_MS deve ser um número válido"),
+        ),
+    };
+    let password_hasher =
+        PasswordHasher::new(password_config).expect("Configuração de hash de senhas inválida");
+
+    // Política de senhas (PASSWORD_*)
+    let password_policy =
+        password_policy_from_env().expect("Falha ao configurar a política de senhas");
+
+    // Administrador inicial (BOOTSTRAP_ADMIN_*), criado apenas se ainda não houver admins
+    if bootstrap_service::remove_default_admin(&pool, &password_hasher)
+        .await
+        .expect("Falha ao verificar o administrador padrão")
+    {
+        println!("⚠️  ==========================================================");
+        println!("⚠️  Administrador padrão admin@sistema.com (senha admin123) REMOVIDO");
+        println!("⚠️  ==========================================================");
+    }
+
+    match bootstrap_admin_from_env().expect("Falha ao configurar o administrador inicial") {
+        Some(admin) => {
+            match bootstrap_service::bootstrap_admin(
+                &pool,
+                &password_hasher,
+                &password_policy,
+                &admin,
+            )
+            .await
+            .expect("Falha ao criar o administrador inicial")
+            {
+                BootstrapOutcome::Created(id) => {
+                    println!("👑 ==========================================================");
+                    println!(
+                        "👑 ADMINISTRADOR INICIAL CRIADO: {} (id: {})",
+                        admin.email, id
+                    );
+                    println!("👑 Troca de senha obrigatória no primeiro login.");
+                    println!("👑 Remova BOOTSTRAP_ADMIN_PASSWORD_FILE do ambiente.");
+                    println!("👑 ==========================================================");
+                }
+                BootstrapOutcome::AdminExists => {
+                    println!("👑 Bootstrap do administrador ignorado: já existe um admin");
+                }
+            }
+        }
+        None => {
+            if !bootstrap_service::admin_exists(&pool)
+                .await
+                .expect("Falha ao verificar administradores")
+            {
+                println!("⚠️  Nenhum administrador cadastrado. Defina BOOTSTRAP_ADMIN_EMAIL e BOOTSTRAP_ADMIN_PASSWORD_FILE para criar o primeiro.");
+            }
+        }
+    }
+
+    // Envio de emails (MAIL_TRANSPORT=log, file ou smtp)
+    let mailer = mailer_from_env().expect("Falha ao configurar envio de emails");
+
+    // Configurar denylist de tokens revogados
+    let revocation_cache_ttl = env::var("REVOCATION_CACHE_TTL")
+        .unwrap_or_else(|_| "30".to_string())
+        .parse::<u64>()
+        .expect("REVOCATION_CACHE_TTL deve ser um número válido");
+
+    let revocation_store = RevocationStore::new(
+        pool.clone(),
+        std::time::Duration::from_secs(revocation_cache_ttl),
+    );
+
+    // Cache das roles e permissões dos usuários (RBAC)
+    let permission_cache_ttl = env::var("PERMISSION_CACHE_TTL")
+        .unwrap_or_else(|_| "30".to_string())
+        .parse::<u64>()
+        .expect("PERMISSION_CACHE_TTL deve ser um número válido");
+
+    let permission_store = PermissionStore::new(
+        pool.clone(),
+        std::time::Duration::from_secs(permission_cache_ttl),
+    );
+
+    // Cache da situação dos clientes OAuth, com a mesma validade
+    let oauth_client_store = OAuthClientStore::new(
+        pool.clone(),
+        std::time::Duration::from_secs(permission_cache_ttl),
+    );
 
     // Configurar rate limiting
     let rate_limit_rpm = env::var("RATE_LIMIT_RPM")
@@ -56,6 +300,10 @@ async fn main() -> std::io::Result<()> {
 
     let rate_limiter = custom_rate_limiter(rate_limit_rpm, rate_limit_burst);
 
+    // Proxies reversos dos quais X-Forwarded-For e X-Real-IP são aceitos
+    let trusted_proxies = TrustedProxies::parse(&env::var("TRUSTED_PROXIES").unwrap_or_default())
+        .expect("TRUSTED_PROXIES deve conter IPs ou redes CIDR separados por vírgula");
+
     // Configurar servidor
     let host = env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
     let port = env::var("SERVER_PORT")
@@ -64,10 +312,58 @@ async fn main() -> std::io::Result<()> {
         .expect("PORT deve ser um número válido");
 
     println!("🚀 Servidor rodando em http://{}:{}", host, port);
+    let active_key = jwt_config.active_key();
     println!(
-        "🔑 JWT configurado com expiração de {} segundos",
+        "🔑 JWT configurado com {:?} (kid: {}, {} chave(s) carregada(s)) e expiração de {} segundos",
+        active_key.algorithm,
+        active_key.kid.as_deref().unwrap_or("-"),
+        jwt_config.key_ring().keys().len(),
         jwt_expiration
     );
+    println!(
+        "🎯 Emissor: {}, audiência: {}, tolerância de relógio: {} segundos",
+        jwt_config.issuer.as_deref().unwrap_or("-"),
+        jwt_config.audience.as_deref().unwrap_or("-"),
+        jwt_leeway
+    );
+    println!(
+        "📧 Verificação de email: {:?}",
+        email_verification_config.mode
+    );
+    let password_config = password_hasher.config();
+    println!(
+        "🔒 Senhas com Argon2id (memória: {} KiB, iterações: {}, paralelismo: {}), até {} hash(es) simultâneo(s)",
+        password_config.memory_kib,
+        password_config.iterations,
+        password_config.parallelism,
+        password_config.max_concurrent
+    );
+    println!(
+        "🧾 Política de senhas: {} a {} caracteres, {} senha(s) comum(ns) bloqueada(s), histórico de {}, validade de {} dia(s) (0 = sem limite)",
+        password_policy.min_length,
+        password_policy.max_length,
+        password_policy.common_passwords.len(),
+        password_policy.history_size,
+        password_policy.max_age_days
+    );
+    match oidc_client.as_ref().map(OidcClient::config) {
+        Some(oidc_config) => println!(
+            "🪪 Login OIDC com {} (cliente: {}, domínios: {}, provisionamento automático: {})",
+            oidc_config.issuer,
+            oidc_config.client_id,
+            if oidc_config.allowed_domains.is_empty() {
+                "todos".to_string()
+            } else {
+                oidc_config.allowed_domains.join(", ")
+            },
+            oidc_config.auto_provision
+        ),
+        None => println!("🪪 Login OIDC desativado (defina OIDC_ISSUER para ativar)"),
+    }
+    println!(
+        "🔄 Refresh tokens com expiração de {} segundos",
+        refresh_expiration
+    );
     println!(
         "🚦 Rate limiting: {} requisições/minuto, burst de {}",
         rate_limit_rpm, rate_limit_burst
@@ -77,14 +373,36 @@ async fn main() -> std::io::Result<()> {
         App::new()
             .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(jwt_config.clone()))
+            .app_data(web::Data::new(revocation_store.clone()))
+            .app_data(web::Data::new(permission_store.clone()))
+            .app_data(web::Data::new(oauth_client_store.clone()))
+            .app_data(web::Data::new(password_reset_config.clone()))
+            .app_data(web::Data::new(email_verification_config.clone()))
+            .app_data(web::Data::new(invitation_config.clone()))
+            .app_data(web::Data::new(oauth_config.clone()))
+            .app_data(web::Data::new(two_factor_config.clone()))
+            .app_data(web::Data::new(login_throttle_config.clone()))
+            .app_data(web::Data::new(password_hasher.clone()))
+            .app_data(web::Data::new(password_policy.clone()))
+            .app_data(web::Data::from(mailer.clone()))
             .app_data(rate_limiter.clone())
+            .app_data(web::Data::new(trusted_proxies.clone()))
+            .configure(|cfg| {
+                if let Some(oidc_client) = &oidc_client {
+                    cfg.app_data(web::Data::new(oidc_client.clone()));
+                }
+            })
             .wrap(TracingLogger::default())
             .wrap(actix_web_lab::middleware::from_fn(rate_limit_middleware))
             .service(
                 web::scope("/api/v1")
                     .configure(auth_handler::config)
-                    .configure(user_handler::config),
+                    .configure(user_handler::config)
+                    .configure(organization_handler::config)
+                    .configure(oauth_handler::config)
+                    .configure(admin_handler::config),
             )
+            .configure(auth_handler::well_known_config)
             .route("/health", web::get().to(health_check))
     })
     .bind((host, port))?
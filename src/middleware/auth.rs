@@ -1,44 +1,490 @@
-use actix_web::{dev::ServiceRequest, Error, HttpMessage};
+use actix_web::dev::{Payload, ServiceRequest};
+use actix_web::{error::InternalError, Error, FromRequest, HttpMessage, HttpRequest};
 use actix_web_httpauth::extractors::bearer::{BearerAuth, Config};
 use actix_web_httpauth::extractors::AuthenticationError;
+use actix_web_httpauth::headers::www_authenticate::bearer::Bearer;
+use actix_web_httpauth::middleware::HttpAuthentication;
+use chrono::Utc;
+use futures::future::LocalBoxFuture;
+use sqlx::PgPool;
+use std::future::{ready, Ready};
+use uuid::Uuid;
 
-use crate::models::{Claims, JwtConfig};
+use crate::middleware::forbidden_error;
+use crate::models::{
+    Claims, EmailVerificationConfig, EmailVerificationMode, JwtConfig, OrganizationRole,
+    API_KEY_PREFIX, PERMISSION_USERS_ADMIN,
+};
+use crate::services::api_key_service::{self, ApiKeyOutcome};
+use crate::services::oauth_service::OAuthClientStore;
+use crate::services::rbac_service::PermissionStore;
+use crate::services::revocation_service::RevocationStore;
+
+// Header alternativo para chaves de API
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+// Credencial de uma rota autenticada: JWT em `Authorization: Bearer` ou chave de API
+// (`pat_...`) em `Authorization: Bearer` ou `X-API-Key`
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    Jwt(String),
+    ApiKey(String),
+}
+
+impl FromRequest for Credentials {
+    type Error = AuthenticationError<Bearer>;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if let Some(key) = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            return ready(Ok(Credentials::ApiKey(key.trim().to_string())));
+        }
+
+        ready(
+            BearerAuth::from_request(req, payload)
+                .into_inner()
+                .map(|bearer| {
+                    let token = bearer.token().to_string();
+                    if token.starts_with(API_KEY_PREFIX) {
+                        Credentials::ApiKey(token)
+                    } else {
+                        Credentials::Jwt(token)
+                    }
+                }),
+        )
+    }
+}
+
+// Token restrito aceito por um validador, além dos tokens completos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RestrictedAccess {
+    None,
+    UnverifiedEmail,
+    PasswordChange,
+}
 
 pub async fn jwt_validator(
     req: ServiceRequest,
-    credentials: BearerAuth,
+    credentials: Credentials,
+) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    validate_credentials(req, credentials, RestrictedAccess::None).await
+}
+
+// Aceita também tokens restritos de contas com email não verificado
+// (ver `EmailVerificationMode::Restricted`)
+pub async fn unverified_jwt_validator(
+    req: ServiceRequest,
+    credentials: Credentials,
+) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    validate_credentials(req, credentials, RestrictedAccess::UnverifiedEmail).await
+}
+
+type ValidatorResult = Result<ServiceRequest, (Error, ServiceRequest)>;
+
+// Requisitos de acesso de uma rota: permissões exigidas, acesso do próprio usuário do `{id}`,
+// papel mínimo na organização ativa, tokens restritos aceitos e se credenciais
+// delegadas (chaves de API e tokens OAuth) são aceitas.
+// Uso: `.wrap(require_permissions(&[PERMISSION_USERS_READ]).middleware())`
+#[derive(Debug, Clone, Copy)]
+pub struct PermissionGuard {
+    permissions: &'static [&'static str],
+    owner_or_admin: bool,
+    organization_role: Option<OrganizationRole>,
+    allowed: RestrictedAccess,
+    interactive_only: bool,
+}
+
+pub fn require_permissions(permissions: &'static [&'static str]) -> PermissionGuard {
+    PermissionGuard {
+        permissions,
+        owner_or_admin: false,
+        organization_role: None,
+        allowed: RestrictedAccess::None,
+        interactive_only: false,
+    }
+}
+
+impl PermissionGuard {
+    // Rotas com `{id}`: o próprio usuário tem acesso; os demais precisam de `users:admin`
+    pub fn owner_or_admin(mut self) -> Self {
+        self.owner_or_admin = true;
+        self
+    }
+
+    // Rotas da organização ativa: exige vínculo atual com ela e papel mínimo `role`
+    pub fn organization_role(mut self, role: OrganizationRole) -> Self {
+        self.organization_role = Some(role);
+        self
+    }
+
+    // Aceita também tokens restritos de contas com email não verificado
+    pub fn allow_unverified_email(mut self) -> Self {
+        self.allowed = RestrictedAccess::UnverifiedEmail;
+        self
+    }
+
+    // Aceita também tokens restritos de contas que precisam trocar a senha
+    pub fn allow_password_change(mut self) -> Self {
+        self.allowed = RestrictedAccess::PasswordChange;
+        self
+    }
+
+    // Rotas que alteram credenciais (senha, email, 2FA, sessões): exigem o login do
+    // próprio usuário, pois um terceiro com escopo `users:write` poderia assumir a conta
+    pub fn interactive_only(mut self) -> Self {
+        self.interactive_only = true;
+        self
+    }
+
+    pub fn middleware(
+        self,
+    ) -> HttpAuthentication<
+        Credentials,
+        impl Fn(ServiceRequest, Credentials) -> LocalBoxFuture<'static, ValidatorResult>,
+    > {
+        HttpAuthentication::with_fn(move |req, credentials| {
+            Box::pin(async move {
+                let req = validate_credentials(req, credentials, self.allowed).await?;
+                self.check(req)
+            }) as LocalBoxFuture<'static, ValidatorResult>
+        })
+    }
+
+    fn check(self, req: ServiceRequest) -> ValidatorResult {
+        let Some(claims) = get_claims_from_request(&req) else {
+            return Err((
+                actix_web::error::ErrorInternalServerError(
+                    "Erro interno: claims não encontrados após a validação do token.",
+                ),
+                req,
+            ));
+        };
+
+        if self.interactive_only && claims.is_delegated() {
+            return Err(forbidden(
+                req,
+                "Acesso negado. Esta operação exige o login do usuário, não uma chave de API ou token OAuth.",
+                "API_KEY_NOT_ALLOWED",
+            ));
+        }
+
+        if let Some(missing) = self
+            .permissions
+            .iter()
+            .find(|permission| !claims.has_permission(permission))
+        {
+            return Err(missing_permission(req, &claims, missing));
+        }
+
+        if self.owner_or_admin {
+            let path_id = req
+                .match_info()
+                .get("id")
+                .and_then(|id| Uuid::parse_str(id).ok());
+            let is_owner = matches!(path_id, Some(id) if claims.get_user_id().ok() == Some(id));
+            if !is_owner && !claims.is_admin() {
+                return Err(forbidden(
+                    req,
+                    "Acesso negado. Você só pode acessar seus próprios dados.",
+                    "ACCESS_DENIED",
+                ));
+            }
+        }
+
+        if let Some(required) = self.organization_role {
+            if claims.org_id.is_none() {
+                return Err(forbidden(
+                    req,
+                    "Nenhuma organização ativa. Selecione uma organização.",
+                    "NO_ACTIVE_ORGANIZATION",
+                ));
+            }
+            match claims.org_role {
+                None => {
+                    return Err(forbidden(
+                        req,
+                        "Acesso negado. Você não participa da organização ativa.",
+                        "NOT_ORGANIZATION_MEMBER",
+                    ));
+                }
+                Some(role) if role < required => {
+                    return Err(forbidden(
+                        req,
+                        &format!("Acesso negado. Requer o papel {} na organização.", required),
+                        "ORGANIZATION_ROLE_REQUIRED",
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(req)
+    }
+}
+
+// O usuário tem a permissão mas a credencial não (chave de API com escopos): INSUFFICIENT_SCOPE
+fn missing_permission(
+    req: ServiceRequest,
+    claims: &Claims,
+    permission: &str,
+) -> (Error, ServiceRequest) {
+    if claims
+        .permissions
+        .iter()
+        .any(|granted| granted == permission)
+    {
+        forbidden(
+            req,
+            &format!(
+                "Acesso negado. A credencial não tem o escopo {}.",
+                permission
+            ),
+            "INSUFFICIENT_SCOPE",
+        )
+    } else if permission == PERMISSION_USERS_ADMIN {
+        forbidden(
+            req,
+            "Acesso negado. Requer privilégios de administrador.",
+            "ADMIN_REQUIRED",
+        )
+    } else {
+        forbidden(
+            req,
+            &format!("Acesso negado. Requer a permissão {}.", permission),
+            "PERMISSION_DENIED",
+        )
+    }
+}
+
+fn forbidden(req: ServiceRequest, message: &str, code: &str) -> (Error, ServiceRequest) {
+    let response = forbidden_error(message, code);
+    (InternalError::from_response("", response).into(), req)
+}
+
+async fn validate_credentials(
+    req: ServiceRequest,
+    credentials: Credentials,
+    allowed: RestrictedAccess,
 ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    let claims = match credentials {
+        Credentials::Jwt(token) => verify_jwt(&req, &token).await,
+        Credentials::ApiKey(key) => verify_api_key(&req, &key).await,
+    };
+
+    let claims = match claims {
+        Ok(claims) => claims,
+        Err(e) => return Err((e, req)),
+    };
+
+    let claims = match resolve_access(&req, claims).await {
+        Ok(claims) => claims,
+        Err(e) => return Err((e, req)),
+    };
+
+    if !claims.email_verified && allowed != RestrictedAccess::UnverifiedEmail {
+        return Err(forbidden(
+            req,
+            "Confirme seu email para acessar este recurso",
+            "EMAIL_NOT_VERIFIED",
+        ));
+    }
+
+    if claims.must_change_password && allowed != RestrictedAccess::PasswordChange {
+        return Err(forbidden(
+            req,
+            "Altere sua senha para acessar este recurso",
+            "PASSWORD_CHANGE_REQUIRED",
+        ));
+    }
+
+    // Adicionar claims às extensões da requisição para uso posterior
+    req.extensions_mut().insert(claims);
+    Ok(req)
+}
+
+// Roles, permissões e papel na organização ativa atuais do usuário, que prevalecem
+// sobre os valores gravados no token
+async fn resolve_access(req: &ServiceRequest, mut claims: Claims) -> Result<Claims, Error> {
+    let Some(store) = req.app_data::<actix_web::web::Data<PermissionStore>>() else {
+        return Ok(claims);
+    };
+
+    let user_id = claims.get_user_id().unwrap_or_default();
+    match store.access(user_id).await {
+        Ok(access) => {
+            claims.roles = access.roles.clone();
+            claims.permissions = access.permissions.clone();
+            claims.org_role = claims
+                .get_organization_id()
+                .and_then(|organization_id| access.organizations.get(&organization_id).copied());
+            Ok(claims)
+        }
+        Err(e) => {
+            eprintln!("Erro ao carregar permissões do usuário: {:?}", e);
+            Err(actix_web::error::ErrorInternalServerError(
+                "Erro interno ao verificar permissões",
+            ))
+        }
+    }
+}
+
+async fn verify_jwt(req: &ServiceRequest, token: &str) -> Result<Claims, Error> {
     // Extrair configuração JWT do app data
     let jwt_config = match req.app_data::<actix_web::web::Data<JwtConfig>>() {
         Some(config) => config.get_ref(),
-        None => {
-            let config = Config::default().realm("Restricted area");
-            return Err((AuthenticationError::from(config).into(), req));
-        }
+        None => return Err(authentication_error("invalid token")),
     };
 
-    // Verificar token
-    let token = credentials.token();
-
-    match jwt_config.verify_token(token) {
-        Ok(claims) => {
-            // Verificar se token não expirou
-            if claims.is_expired() {
-                let config = Config::default()
-                    .realm("Restricted area")
-                    .scope("token expired");
-                return Err((AuthenticationError::from(config).into(), req));
+    let claims = jwt_config.verify_token(token).map_err(|e| {
+        // Expiração, emissor, audiência e `nbf` são informados em escopos distintos
+        authentication_error(TokenError::from(&e).scope())
+    })?;
+
+    // Verificar se token foi revogado (logout)
+    if let Some(store) = req.app_data::<actix_web::web::Data<RevocationStore>>() {
+        match store.is_revoked(&claims).await {
+            Ok(false) => {}
+            Ok(true) => return Err(authentication_error("token revoked")),
+            Err(e) => {
+                eprintln!("Erro ao verificar revogação do token: {:?}", e);
+                return Err(actix_web::error::ErrorInternalServerError(
+                    "Erro interno ao verificar token",
+                ));
             }
+        }
+    }
 
-            // Adicionar claims às extensões da requisição para uso posterior
-            req.extensions_mut().insert(claims);
-            Ok(req)
+    // Tokens de clientes OAuth deixam de valer quando o cliente é revogado
+    if let Some(client_id) = &claims.client_id {
+        let Some(store) = req.app_data::<actix_web::web::Data<OAuthClientStore>>() else {
+            return Err(authentication_error("invalid token"));
+        };
+
+        match store.is_active(client_id).await {
+            Ok(true) => {}
+            Ok(false) => return Err(authentication_error("token revoked")),
+            Err(e) => {
+                eprintln!("Erro ao verificar cliente OAuth do token: {:?}", e);
+                return Err(actix_web::error::ErrorInternalServerError(
+                    "Erro interno ao verificar token",
+                ));
+            }
         }
-        Err(_) => {
-            let config = Config::default()
-                .realm("Restricted area")
-                .scope("invalid token");
-            Err((AuthenticationError::from(config).into(), req))
+    }
+
+    Ok(claims)
+}
+
+// Claims equivalentes às do usuário dono da chave, limitadas pelos escopos dela
+async fn verify_api_key(req: &ServiceRequest, key: &str) -> Result<Claims, Error> {
+    let pool = match req.app_data::<actix_web::web::Data<PgPool>>() {
+        Some(pool) => pool.get_ref(),
+        None => return Err(authentication_error("invalid token")),
+    };
+
+    let (api_key, user) = match api_key_service::authenticate(pool, key).await {
+        Ok(ApiKeyOutcome::Valid { api_key, user }) => (api_key, user),
+        Ok(ApiKeyOutcome::NotFound) => return Err(authentication_error("invalid token")),
+        Ok(ApiKeyOutcome::Expired) => return Err(authentication_error("token expired")),
+        Ok(ApiKeyOutcome::Revoked) => return Err(authentication_error("token revoked")),
+        Err(e) => {
+            eprintln!("Erro ao verificar chave de API: {:?}", e);
+            return Err(actix_web::error::ErrorInternalServerError(
+                "Erro interno ao verificar token",
+            ));
+        }
+    };
+
+    let expires_in = api_key
+        .expires_at
+        .map(|expires_at| (expires_at - Utc::now()).num_seconds())
+        .unwrap_or(i64::from(i32::MAX));
+
+    let mut claims = Claims::new(
+        user.id,
+        user.email.clone(),
+        user.nome.clone(),
+        Vec::new(),
+        user.token_version,
+        expires_in,
+    );
+    claims.jti = api_key.id.to_string();
+    claims.api_key_id = Some(api_key.id.to_string());
+    claims.scopes = api_key.claim_scopes();
+    claims.org_id = api_key.organization_id.map(|id| id.to_string());
+    claims.email_verified = user.email_verified_at.is_some()
+        || req
+            .app_data::<actix_web::web::Data<EmailVerificationConfig>>()
+            .is_none_or(|config| config.mode == EmailVerificationMode::Off);
+    claims.must_change_password = user.must_change_password;
+
+    Ok(claims)
+}
+
+fn authentication_error(scope: &'static str) -> Error {
+    let config = Config::default().realm("Restricted area").scope(scope);
+    AuthenticationError::from(config).into()
+}
+
+// Motivo pelo qual um JWT foi rejeitado na verificação
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    Expired,
+    NotYetValid,
+    InvalidIssuer,
+    InvalidAudience,
+    Invalid,
+}
+
+impl TokenError {
+    // Valor do `scope` no header `WWW-Authenticate`
+    pub fn scope(self) -> &'static str {
+        match self {
+            TokenError::Expired => "token expired",
+            TokenError::NotYetValid => "token not yet valid",
+            TokenError::InvalidIssuer => "invalid issuer",
+            TokenError::InvalidAudience => "invalid audience",
+            TokenError::Invalid => "invalid token",
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            TokenError::Expired => "TOKEN_EXPIRED",
+            TokenError::NotYetValid => "TOKEN_NOT_YET_VALID",
+            TokenError::InvalidIssuer => "INVALID_ISSUER",
+            TokenError::InvalidAudience => "INVALID_AUDIENCE",
+            TokenError::Invalid => "INVALID_TOKEN",
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            TokenError::Expired => "Token expirado",
+            TokenError::NotYetValid => "Token ainda não é válido",
+            TokenError::InvalidIssuer => "Token emitido por outro emissor",
+            TokenError::InvalidAudience => "Token destinado a outra audiência",
+            TokenError::Invalid => "Token inválido",
+        }
+    }
+}
+
+impl From<&jsonwebtoken::errors::Error> for TokenError {
+    fn from(error: &jsonwebtoken::errors::Error) -> Self {
+        use jsonwebtoken::errors::ErrorKind;
+
+        match error.kind() {
+            ErrorKind::ExpiredSignature => TokenError::Expired,
+            ErrorKind::ImmatureSignature => TokenError::NotYetValid,
+            ErrorKind::InvalidIssuer => TokenError::InvalidIssuer,
+            ErrorKind::InvalidAudience => TokenError::InvalidAudience,
+            ErrorKind::MissingRequiredClaim(claim) if claim == "iss" => TokenError::InvalidIssuer,
+            ErrorKind::MissingRequiredClaim(claim) if claim == "aud" => TokenError::InvalidAudience,
+            _ => TokenError::Invalid,
         }
     }
 }
@@ -56,7 +502,7 @@ pub fn get_claims_from_http_request(req: &actix_web::HttpRequest) -> Option<Clai
 // Middleware para verificar se usuário é admin
 pub async fn admin_required(
     req: ServiceRequest,
-    credentials: BearerAuth,
+    credentials: Credentials,
 ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
     // Primeiro, validar o JWT
     let req = match jwt_validator(req, credentials).await {
@@ -69,13 +515,13 @@ pub async fn admin_required(
         if claims.is_admin() {
             Ok(req)
         } else {
-            Err((Error::from(actix_web::error::ErrorForbidden("")), req))
+            Err(missing_permission(req, &claims, PERMISSION_USERS_ADMIN))
         }
     } else {
         Err((
-            Error::from(actix_web::error::ErrorInternalServerError(
+            actix_web::error::ErrorInternalServerError(
                 "Erro interno: claims não encontrados após a validação do token.".to_string(),
-            )),
+            ),
             req,
         ))
     }
@@ -84,7 +530,9 @@ pub async fn admin_required(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{JwtConfig, UserRole};
+    use crate::models::{
+        JwtConfig, PERMISSION_USERS_READ, PERMISSION_USERS_WRITE, ROLE_ADMIN, ROLE_USER,
+    };
     use uuid::Uuid;
 
     #[test]
@@ -95,7 +543,8 @@ mod tests {
             Uuid::new_v4(),
             "test@example.com".to_string(),
             "Test User".to_string(),
-            UserRole::User,
+            vec![ROLE_USER.to_string()],
+            0,
             3600,
         );
 
@@ -104,6 +553,411 @@ mod tests {
 
         assert_eq!(claims.email, verified_claims.email);
         assert_eq!(claims.nome, verified_claims.nome);
-        assert_eq!(claims.role, verified_claims.role);
+        assert_eq!(claims.roles, verified_claims.roles);
+        assert_eq!(claims.jti, verified_claims.jti);
+        assert_eq!(claims.token_version, verified_claims.token_version);
+    }
+
+    #[test]
+    fn test_jwt_config_with_asymmetric_key() {
+        use crate::models::jwt_key::tests::{ED_PRIVATE_PEM, ED_PUBLIC_PEM};
+        use crate::models::JwtKey;
+        use jsonwebtoken::Algorithm;
+
+        let key = JwtKey::from_pem(
+            Algorithm::EdDSA,
+            ED_PRIVATE_PEM.as_bytes(),
+            ED_PUBLIC_PEM.as_bytes(),
+            Some("ed-1".to_string()),
+        )
+        .unwrap();
+        let config = JwtConfig::with_key(key, 3600);
+
+        let claims = Claims::new(
+            Uuid::new_v4(),
+            "test@example.com".to_string(),
+            "Test User".to_string(),
+            vec![ROLE_USER.to_string()],
+            0,
+            3600,
+        );
+
+        let token = config.generate_token(&claims).unwrap();
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(header.alg, Algorithm::EdDSA);
+        assert_eq!(header.kid.as_deref(), Some("ed-1"));
+
+        let verified_claims = config.verify_token(&token).unwrap();
+        assert_eq!(claims.jti, verified_claims.jti);
+
+        // Tokens HMAC não são aceitos por uma configuração assimétrica
+        let hmac_config = JwtConfig::new("test_secret".to_string(), 3600);
+        let hmac_token = hmac_config.generate_token(&claims).unwrap();
+        assert!(config.verify_token(&hmac_token).is_err());
+
+        assert_eq!(config.jwks().keys.len(), 1);
+        assert!(hmac_config.jwks().keys.is_empty());
+    }
+
+    #[test]
+    fn test_jwt_config_key_rotation() {
+        use crate::models::{JwtKey, JwtKeyRing};
+
+        let old_key = JwtKey::hmac(b"old_secret", Some("old".to_string()));
+        let new_key = JwtKey::hmac(b"new_secret", Some("new".to_string()));
+        let config = JwtConfig::with_key_ring(JwtKeyRing::new(old_key).with_key(new_key), 3600);
+
+        let claims = Claims::new(
+            Uuid::new_v4(),
+            "test@example.com".to_string(),
+            "Test User".to_string(),
+            vec![ROLE_USER.to_string()],
+            0,
+            3600,
+        );
+
+        let old_token = config.generate_token(&claims).unwrap();
+        config.promote_key("new").unwrap();
+        let new_token = config.generate_token(&claims).unwrap();
+
+        let header = jsonwebtoken::decode_header(&new_token).unwrap();
+        assert_eq!(header.kid.as_deref(), Some("new"));
+
+        // Tokens assinados com a chave anterior continuam válidos após a promoção
+        assert!(config.verify_token(&old_token).is_ok());
+        assert!(config.verify_token(&new_token).is_ok());
+
+        // Tokens sem `kid` (emitidos antes da rotação) são testados contra todas as chaves
+        let legacy_config = JwtConfig::new("old_secret".to_string(), 3600);
+        let legacy_token = legacy_config.generate_token(&claims).unwrap();
+        assert!(config.verify_token(&legacy_token).is_ok());
+
+        // Chave retirada do conjunto deixa de validar seus tokens
+        config.replace_keys(JwtKeyRing::new(JwtKey::hmac(
+            b"new_secret",
+            Some("new".to_string()),
+        )));
+        assert!(config.verify_token(&old_token).is_err());
+        assert!(config.promote_key("old").is_err());
+    }
+
+    #[test]
+    fn test_jwt_config_issuer_and_audience() {
+        let config = JwtConfig::new("test_secret".to_string(), 3600)
+            .with_issuer(Some("https://auth.prod".to_string()))
+            .with_audience(Some("api-prod".to_string()));
+
+        let claims = Claims::new(
+            Uuid::new_v4(),
+            "test@example.com".to_string(),
+            "Test User".to_string(),
+            vec![ROLE_USER.to_string()],
+            0,
+            3600,
+        );
+
+        let token = config.generate_token(&claims).unwrap();
+        let verified_claims = config.verify_token(&token).unwrap();
+        assert_eq!(verified_claims.iss.as_deref(), Some("https://auth.prod"));
+        assert_eq!(verified_claims.aud.as_deref(), Some("api-prod"));
+
+        // Mesmo segredo, outro ambiente
+        let staging = JwtConfig::new("test_secret".to_string(), 3600)
+            .with_issuer(Some("https://auth.staging".to_string()))
+            .with_audience(Some("api-prod".to_string()));
+        let error = staging.verify_token(&token).unwrap_err();
+        assert_eq!(TokenError::from(&error), TokenError::InvalidIssuer);
+
+        let other_api = JwtConfig::new("test_secret".to_string(), 3600)
+            .with_issuer(Some("https://auth.prod".to_string()))
+            .with_audience(Some("api-billing".to_string()));
+        let error = other_api.verify_token(&token).unwrap_err();
+        assert_eq!(TokenError::from(&error), TokenError::InvalidAudience);
+
+        // Tokens sem emissor/audiência não são aceitos quando eles são exigidos
+        let without_issuer = JwtConfig::new("test_secret".to_string(), 3600)
+            .with_audience(Some("api-prod".to_string()));
+        let token = without_issuer.generate_token(&claims).unwrap();
+        let error = config.verify_token(&token).unwrap_err();
+        assert_eq!(TokenError::from(&error), TokenError::InvalidIssuer);
+
+        let without_audience = JwtConfig::new("test_secret".to_string(), 3600)
+            .with_issuer(Some("https://auth.prod".to_string()));
+        let token = without_audience.generate_token(&claims).unwrap();
+        let error = config.verify_token(&token).unwrap_err();
+        assert_eq!(TokenError::from(&error), TokenError::InvalidAudience);
+    }
+
+    #[test]
+    fn test_jwt_config_leeway() {
+        let mut claims = Claims::new(
+            Uuid::new_v4(),
+            "test@example.com".to_string(),
+            "Test User".to_string(),
+            vec![ROLE_USER.to_string()],
+            0,
+            3600,
+        );
+        // Expirado há 30 segundos
+        claims.exp = claims.iat - 30;
+
+        let tolerant = JwtConfig::new("test_secret".to_string(), 3600).with_leeway(60);
+        let token = tolerant.generate_token(&claims).unwrap();
+        assert!(tolerant.verify_token(&token).is_ok());
+
+        let strict = JwtConfig::new("test_secret".to_string(), 3600).with_leeway(0);
+        let error = strict.verify_token(&token).unwrap_err();
+        assert_eq!(TokenError::from(&error), TokenError::Expired);
+
+        // Emitido por um servidor com relógio 30 segundos adiantado
+        claims.exp = claims.iat + 3600;
+        claims.nbf = claims.iat + 30;
+        let token = tolerant.generate_token(&claims).unwrap();
+        assert!(tolerant.verify_token(&token).is_ok());
+        let error = strict.verify_token(&token).unwrap_err();
+        assert_eq!(TokenError::from(&error), TokenError::NotYetValid);
+    }
+
+    #[test]
+    fn test_restricted_token_keeps_email_verified_claim() {
+        let config = JwtConfig::new("test_secret".to_string(), 3600);
+        let mut claims = Claims::new(
+            Uuid::new_v4(),
+            "test@example.com".to_string(),
+            "Test User".to_string(),
+            vec![ROLE_USER.to_string()],
+            0,
+            3600,
+        );
+        assert!(claims.email_verified);
+
+        claims.email_verified = false;
+        let token = config.generate_token(&claims).unwrap();
+        assert!(!config.verify_token(&token).unwrap().email_verified);
+
+        // Tokens emitidos antes da verificação de email não possuem o claim
+        let legacy: Claims = serde_json::from_value(serde_json::json!({
+            "sub": claims.sub,
+            "email": claims.email,
+            "nome": claims.nome,
+            "role": "User",
+            "iat": claims.iat,
+            "exp": claims.exp,
+            "jti": claims.jti,
+            "token_version": 0
+        }))
+        .unwrap();
+        assert!(legacy.email_verified);
+        assert!(!legacy.must_change_password);
+    }
+
+    #[actix_web::test]
+    async fn test_restricted_token_keeps_must_change_password_claim() {
+        use actix_web::test::TestRequest;
+
+        let config = JwtConfig::new("test_secret".to_string(), 3600);
+        let mut claims = Claims::new(
+            Uuid::new_v4(),
+            "test@example.com".to_string(),
+            "Test User".to_string(),
+            vec![ROLE_ADMIN.to_string()],
+            0,
+            3600,
+        );
+        assert!(!claims.must_change_password);
+
+        claims.must_change_password = true;
+        let token = config.generate_token(&claims).unwrap();
+        assert!(config.verify_token(&token).unwrap().must_change_password);
+
+        // Rotas comuns recusam o token restrito; a troca de senha o aceita
+        let config = actix_web::web::Data::new(config);
+        let validate = |allowed: RestrictedAccess| {
+            let req = TestRequest::default()
+                .app_data(config.clone())
+                .to_srv_request();
+            validate_credentials(req, Credentials::Jwt(token.clone()), allowed)
+        };
+
+        let Err((error, _)) = validate(RestrictedAccess::None).await else {
+            panic!("token restrito aceito em rota comum");
+        };
+        let response = error.error_response();
+        assert_eq!(response.status().as_u16(), 403);
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "PASSWORD_CHANGE_REQUIRED");
+
+        let req = validate(RestrictedAccess::PasswordChange).await.unwrap();
+        assert!(get_claims_from_request(&req).unwrap().must_change_password);
+    }
+
+    #[test]
+    fn test_permissions_and_scopes() {
+        let config = JwtConfig::new("test_secret".to_string(), 3600);
+        let mut claims = Claims::new(
+            Uuid::new_v4(),
+            "test@example.com".to_string(),
+            "Test User".to_string(),
+            vec![ROLE_ADMIN.to_string()],
+            0,
+            3600,
+        );
+
+        // Permissões não são gravadas no token: o middleware as carrega a cada requisição
+        let token = config.generate_token(&claims).unwrap();
+        let verified = config.verify_token(&token).unwrap();
+        assert_eq!(verified.roles, vec![ROLE_ADMIN.to_string()]);
+        assert!(verified.permissions.is_empty());
+        assert!(!verified.is_admin());
+
+        claims.permissions = vec![
+            PERMISSION_USERS_ADMIN.to_string(),
+            PERMISSION_USERS_READ.to_string(),
+        ];
+        assert!(claims.is_admin());
+
+        // Admin limitado a leitura (ex.: chave de API) não é tratado como admin
+        claims.scopes = Some(vec![PERMISSION_USERS_READ.to_string()]);
+        assert!(claims.has_permission(PERMISSION_USERS_READ));
+        assert!(!claims.is_admin());
+        assert_eq!(
+            claims.effective_permissions(),
+            vec![PERMISSION_USERS_READ.to_string()]
+        );
+
+        // Escopo sem a permissão correspondente não concede nada
+        claims.scopes = Some(vec![PERMISSION_USERS_WRITE.to_string()]);
+        assert!(!claims.has_permission(PERMISSION_USERS_WRITE));
+    }
+
+    #[test]
+    fn test_permission_guard() {
+        use actix_web::test::TestRequest;
+
+        fn status(guard: PermissionGuard, claims: &Claims, path_id: Uuid) -> u16 {
+            let req = TestRequest::default()
+                .param("id", path_id.to_string())
+                .to_srv_request();
+            req.extensions_mut().insert(claims.clone());
+            match guard.check(req) {
+                Ok(_) => 200,
+                Err((error, _)) => error.error_response().status().as_u16(),
+            }
+        }
+
+        fn claims(user_id: Uuid, role: &str, permissions: &[&str]) -> Claims {
+            let mut claims = Claims::new(
+                user_id,
+                "user@example.com".to_string(),
+                "User".to_string(),
+                vec![role.to_string()],
+                0,
+                3600,
+            );
+            claims.permissions = permissions.iter().map(|p| p.to_string()).collect();
+            claims
+        }
+
+        let user_id = Uuid::new_v4();
+        let other_id = Uuid::new_v4();
+        let mut user = claims(
+            user_id,
+            ROLE_USER,
+            &[PERMISSION_USERS_READ, PERMISSION_USERS_WRITE],
+        );
+        let admin = claims(
+            other_id,
+            ROLE_ADMIN,
+            &[
+                PERMISSION_USERS_READ,
+                PERMISSION_USERS_WRITE,
+                PERMISSION_USERS_ADMIN,
+            ],
+        );
+        let auditor = claims(other_id, "AUDITOR", &[PERMISSION_USERS_ADMIN]);
+
+        let read_own = require_permissions(&[PERMISSION_USERS_READ]).owner_or_admin();
+        assert_eq!(status(read_own, &user, user_id), 200);
+        assert_eq!(status(read_own, &user, other_id), 403);
+        assert_eq!(status(read_own, &admin, user_id), 200);
+        assert_eq!(status(read_own, &auditor, user_id), 403);
+
+        let admin_only = require_permissions(&[PERMISSION_USERS_READ, PERMISSION_USERS_ADMIN]);
+        assert_eq!(status(admin_only, &user, user_id), 403);
+        assert_eq!(status(admin_only, &admin, user_id), 200);
+
+        // Somente leitura
+        user.scopes = Some(vec![PERMISSION_USERS_READ.to_string()]);
+        assert_eq!(status(read_own, &user, user_id), 200);
+        let write_own = require_permissions(&[PERMISSION_USERS_WRITE]).owner_or_admin();
+        assert_eq!(status(write_own, &user, user_id), 403);
+
+        // Rotas de credenciais recusam chaves de API e tokens OAuth, mesmo com o escopo
+        user.scopes = None;
+        let credentials = write_own.interactive_only();
+        assert_eq!(status(credentials, &user, user_id), 200);
+        user.client_id = Some("cli_0123".to_string());
+        user.scopes = Some(vec![PERMISSION_USERS_WRITE.to_string()]);
+        assert_eq!(status(write_own, &user, user_id), 200);
+        assert_eq!(status(credentials, &user, user_id), 403);
+        user.client_id = None;
+        user.api_key_id = Some(Uuid::new_v4().to_string());
+        assert_eq!(status(credentials, &user, user_id), 403);
+    }
+
+    #[test]
+    fn test_organization_role_guard() {
+        use actix_web::test::TestRequest;
+
+        fn code(guard: PermissionGuard, claims: &Claims) -> Option<String> {
+            let req = TestRequest::default().to_srv_request();
+            req.extensions_mut().insert(claims.clone());
+            let (error, _) = guard.check(req).err()?;
+            let body = actix_web::body::to_bytes(error.error_response().into_body());
+            let body = futures::executor::block_on(body).ok()?;
+            let json: serde_json::Value = serde_json::from_slice(&body).ok()?;
+            json["code"].as_str().map(str::to_string)
+        }
+
+        let mut claims = Claims::new(
+            Uuid::new_v4(),
+            "user@example.com".to_string(),
+            "User".to_string(),
+            vec![ROLE_USER.to_string()],
+            0,
+            3600,
+        );
+        claims.permissions = vec![PERMISSION_USERS_READ.to_string()];
+
+        let members = require_permissions(&[PERMISSION_USERS_READ])
+            .organization_role(OrganizationRole::Member);
+        let admins = require_permissions(&[PERMISSION_USERS_READ])
+            .organization_role(OrganizationRole::Admin);
+
+        assert_eq!(
+            code(members, &claims).as_deref(),
+            Some("NO_ACTIVE_ORGANIZATION")
+        );
+
+        // Vínculo removido após a emissão do token
+        claims = claims.with_organization(Some((Uuid::new_v4(), OrganizationRole::Admin)));
+        claims.org_role = None;
+        assert_eq!(
+            code(members, &claims).as_deref(),
+            Some("NOT_ORGANIZATION_MEMBER")
+        );
+
+        claims.org_role = Some(OrganizationRole::Member);
+        assert_eq!(code(members, &claims), None);
+        assert_eq!(
+            code(admins, &claims).as_deref(),
+            Some("ORGANIZATION_ROLE_REQUIRED")
+        );
+
+        claims.org_role = Some(OrganizationRole::Owner);
+        assert_eq!(code(admins, &claims), None);
     }
 }
@@ -1,6 +1,9 @@
+use actix_web::http::header::{HeaderValue, RETRY_AFTER};
 use actix_web::HttpResponse;
 use serde_json::json;
 
+use crate::password::{PasswordError, PasswordViolation};
+
 // Helper function to create standardized JSON error responses
 pub fn create_json_error_response(
     status_code: u16,
@@ -20,9 +23,13 @@ pub fn create_json_error_response(
         401 => HttpResponse::Unauthorized().json(json_body),
         403 => HttpResponse::Forbidden().json(json_body),
         404 => HttpResponse::NotFound().json(json_body),
+        409 => HttpResponse::Conflict().json(json_body),
         422 => HttpResponse::UnprocessableEntity().json(json_body),
+        423 => HttpResponse::Locked().json(json_body),
         429 => HttpResponse::TooManyRequests().json(json_body),
         500 => HttpResponse::InternalServerError().json(json_body),
+        502 => HttpResponse::BadGateway().json(json_body),
+        503 => HttpResponse::ServiceUnavailable().json(json_body),
         _ => HttpResponse::InternalServerError().json(json!({
             "error": "Internal Server Error",
             "message": "An unexpected error occurred",
@@ -49,10 +56,65 @@ pub fn not_found_error(message: &str, code: &str) -> HttpResponse {
     create_json_error_response(404, "Not Found", message, code)
 }
 
+pub fn conflict_error(message: &str, code: &str) -> HttpResponse {
+    create_json_error_response(409, "Conflict", message, code)
+}
+
 pub fn internal_server_error(message: &str, code: &str) -> HttpResponse {
     create_json_error_response(500, "Internal Server Error", message, code)
 }
 
+// Falha de um serviço externo do qual a requisição depende
+pub fn bad_gateway_error(message: &str, code: &str) -> HttpResponse {
+    create_json_error_response(502, "Bad Gateway", message, code)
+}
+
+// Resposta de erro temporário com o header `Retry-After` (em segundos)
+pub fn retry_after_error(
+    status_code: u16,
+    error: &str,
+    message: &str,
+    code: &str,
+    retry_after_seconds: u64,
+) -> HttpResponse {
+    let mut response = create_json_error_response(status_code, error, message, code);
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(retry_after_seconds));
+    response
+}
+
+// Resposta para falhas de hash/verificação de senha: 503 com `Retry-After` quando o
+// limite de hashes simultâneos foi atingido, 500 nos demais casos
+pub fn password_error(error: &PasswordError, code: &str) -> HttpResponse {
+    match error {
+        PasswordError::Busy {
+            retry_after_seconds,
+        } => retry_after_error(
+            503,
+            "Service Unavailable",
+            "Servidor sobrecarregado, tente novamente em instantes",
+            "SERVER_BUSY",
+            *retry_after_seconds,
+        ),
+        PasswordError::Failed(e) => {
+            eprintln!("Erro ao processar senha: {:?}", e);
+            internal_server_error("Erro interno do servidor", code)
+        }
+    }
+}
+
+// Senha recusada pela política: 422 listando todas as regras não atendidas
+pub fn password_policy_error(violations: &[PasswordViolation]) -> HttpResponse {
+    HttpResponse::UnprocessableEntity().json(json!({
+        "error": "Unprocessable Entity",
+        "message": "A senha não atende à política de senhas",
+        "code": "PASSWORD_POLICY_VIOLATION",
+        "violations": violations,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,6 +138,13 @@ mod tests {
         assert_eq!(response.status(), 403);
     }
 
+    #[test]
+    fn test_retry_after_error() {
+        let response = retry_after_error(423, "Locked", "Account locked", "ACCOUNT_LOCKED", 90);
+        assert_eq!(response.status(), 423);
+        assert_eq!(response.headers().get("Retry-After").unwrap(), "90");
+    }
+
     #[test]
     fn test_create_json_error_response_unknown_status() {
         let response = create_json_error_response(999, "Unknown", "Unknown error", "UNKNOWN");
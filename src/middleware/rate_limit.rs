@@ -1,6 +1,7 @@
 use actix_web::{
     dev::{ServiceRequest, ServiceResponse},
-    Error, HttpResponse,
+    http::header::HeaderMap,
+    web, Error, HttpRequest, HttpResponse,
 };
 use actix_web_lab::middleware::Next;
 use std::{
@@ -105,28 +106,12 @@ impl RateLimiter {
     }
 
     fn extract_ip(&self, req: &ServiceRequest) -> Option<IpAddr> {
-        // Try x-forwarded-for first
-        if let Some(forwarded_for) = req.headers().get("x-forwarded-for") {
-            if let Ok(header_str) = forwarded_for.to_str() {
-                if let Some(first_ip) = header_str.split(',').next() {
-                    if let Ok(ip) = first_ip.trim().parse::<IpAddr>() {
-                        return Some(ip);
-                    }
-                }
-            }
-        }
-
-        // Try x-real-ip header
-        if let Some(real_ip) = req.headers().get("x-real-ip") {
-            if let Ok(header_str) = real_ip.to_str() {
-                if let Ok(ip) = header_str.parse::<IpAddr>() {
-                    return Some(ip);
-                }
-            }
-        }
-
-        // Fall back to peer address
-        req.peer_addr().map(|addr| addr.ip())
+        let trusted = req.app_data::<web::Data<TrustedProxies>>();
+        resolve_ip(
+            req.peer_addr().map(|addr| addr.ip()),
+            req.headers(),
+            trusted.map(|trusted| trusted.get_ref()),
+        )
     }
 
     fn is_rate_limited(&self, ip: IpAddr) -> (bool, Duration) {
@@ -145,6 +130,113 @@ impl RateLimiter {
     }
 }
 
+// Proxies reversos cujos headers `X-Forwarded-For` e `X-Real-IP` são aceitos
+// (TRUSTED_PROXIES). De qualquer outra conexão esses headers são ignorados, pois
+// o cliente poderia escolher o IP usado no rate limiting e no bloqueio de login.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies {
+    networks: Vec<(IpAddr, u8)>,
+}
+
+impl TrustedProxies {
+    // IPs ou redes em notação CIDR, separados por vírgula
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut networks = Vec::new();
+        for entry in value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (ip, prefix) = match entry.split_once('/') {
+                Some((ip, prefix)) => (ip, Some(prefix)),
+                None => (entry, None),
+            };
+            let ip = ip
+                .parse::<IpAddr>()
+                .map_err(|_| format!("endereço inválido: {}", entry))?;
+            let max_prefix = if ip.is_ipv4() { 32 } else { 128 };
+            let prefix = match prefix {
+                Some(prefix) => prefix
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|prefix| *prefix <= max_prefix)
+                    .ok_or_else(|| format!("prefixo inválido: {}", entry))?,
+                None => max_prefix,
+            };
+            networks.push((ip, prefix));
+        }
+        Ok(Self { networks })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.networks
+            .iter()
+            .any(|&(network, prefix)| match (network, ip) {
+                (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                    let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+                    u32::from(network) & mask == u32::from(ip) & mask
+                }
+                (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                    let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+                    u128::from(network) & mask == u128::from(ip) & mask
+                }
+                _ => false,
+            })
+    }
+}
+
+// IP de origem: o da conexão ou, se ela vem de um proxy confiável, o informado
+// pelos proxies. Em `X-Forwarded-For` cada proxy acrescenta à direita o endereço
+// de quem o chamou, então a lista é lida da direita para a esquerda até o
+// primeiro endereço que não é de um proxy confiável.
+fn resolve_ip(
+    peer: Option<IpAddr>,
+    headers: &HeaderMap,
+    trusted: Option<&TrustedProxies>,
+) -> Option<IpAddr> {
+    let peer = peer?;
+    let Some(trusted) = trusted.filter(|trusted| trusted.contains(peer)) else {
+        return Some(peer);
+    };
+
+    let forwarded: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect();
+
+    if !forwarded.is_empty() {
+        let mut client = peer;
+        for entry in forwarded.iter().rev() {
+            let Ok(ip) = entry.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = ip;
+            if !trusted.contains(ip) {
+                break;
+            }
+        }
+        return Some(client);
+    }
+
+    let real_ip = headers
+        .get("x-real-ip")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<IpAddr>().ok());
+
+    Some(real_ip.unwrap_or(peer))
+}
+
+// IP de origem da requisição, com a mesma regra usada pelo rate limiting
+pub fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    let trusted = req.app_data::<web::Data<TrustedProxies>>();
+    resolve_ip(
+        req.peer_addr().map(|addr| addr.ip()),
+        req.headers(),
+        trusted.map(|trusted| trusted.get_ref()),
+    )
+}
+
 pub async fn rate_limit_middleware(
     req: ServiceRequest,
     next: Next<impl actix_web::body::MessageBody + 'static>,
@@ -231,6 +323,71 @@ mod tests {
         assert!(!client.can_consume(&config));
     }
 
+    #[test]
+    fn test_resolve_ip() {
+        use actix_web::http::header::{HeaderName, HeaderValue};
+
+        fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.append(
+                    HeaderName::from_static(name),
+                    HeaderValue::from_static(value),
+                );
+            }
+            headers
+        }
+
+        let ip = |value: &str| value.parse::<IpAddr>().unwrap();
+        let trusted = TrustedProxies::parse("10.0.0.0/8, 192.168.1.10").unwrap();
+        let spoofed = headers(&[("x-forwarded-for", "1.2.3.4"), ("x-real-ip", "1.2.3.4")]);
+
+        // Conexão direta: os headers são ignorados
+        assert_eq!(
+            resolve_ip(Some(ip("203.0.113.9")), &spoofed, Some(&trusted)),
+            Some(ip("203.0.113.9"))
+        );
+        assert_eq!(
+            resolve_ip(Some(ip("10.1.2.3")), &spoofed, None),
+            Some(ip("10.1.2.3"))
+        );
+
+        // Atrás do proxy: último endereço não confiável, mesmo com valores forjados à esquerda
+        let chain = headers(&[("x-forwarded-for", "1.2.3.4, 198.51.100.7, 10.0.0.2")]);
+        assert_eq!(
+            resolve_ip(Some(ip("192.168.1.10")), &chain, Some(&trusted)),
+            Some(ip("198.51.100.7"))
+        );
+        assert_eq!(
+            resolve_ip(Some(ip("10.0.0.1")), &spoofed, Some(&trusted)),
+            Some(ip("1.2.3.4"))
+        );
+        let real_ip = headers(&[("x-real-ip", "198.51.100.7")]);
+        assert_eq!(
+            resolve_ip(Some(ip("10.0.0.1")), &real_ip, Some(&trusted)),
+            Some(ip("198.51.100.7"))
+        );
+        assert_eq!(
+            resolve_ip(Some(ip("10.0.0.1")), &HeaderMap::new(), Some(&trusted)),
+            Some(ip("10.0.0.1"))
+        );
+    }
+
+    #[test]
+    fn test_trusted_proxies() {
+        let trusted = TrustedProxies::parse("10.0.0.0/8,::1, 2001:db8::/32").unwrap();
+        assert!(trusted.contains("10.255.0.1".parse().unwrap()));
+        assert!(!trusted.contains("11.0.0.1".parse().unwrap()));
+        assert!(trusted.contains("::1".parse().unwrap()));
+        assert!(trusted.contains("2001:db8::42".parse().unwrap()));
+        assert!(!trusted.contains("2001:db9::42".parse().unwrap()));
+
+        assert!(!TrustedProxies::default().contains("127.0.0.1".parse().unwrap()));
+        assert!(TrustedProxies::parse("").unwrap().networks.is_empty());
+        assert!(TrustedProxies::parse("10.0.0.0/33").is_err());
+        assert!(TrustedProxies::parse("proxy.local").is_err());
+    }
+
     #[test]
     fn test_rate_limiter_creation() {
         let limiter = custom_rate_limiter(100, 15);
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Início de toda chave de API; distingue a chave de um JWT em `Authorization: Bearer`
+pub const API_KEY_PREFIX: &str = "pat_";
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub organization_id: Option<Uuid>,
+}
+
+impl ApiKey {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| Utc::now() > expires_at)
+    }
+
+    // Escopos que limitam as requisições feitas com a chave; sem escopos, nenhum limite
+    pub fn claim_scopes(&self) -> Option<Vec<String>> {
+        (!self.scopes.is_empty()).then(|| self.scopes.clone())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    // Sem valor, a chave não expira
+    pub expires_in_days: Option<i64>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub organization_id: Option<Uuid>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(api_key: ApiKey) -> Self {
+        Self {
+            id: api_key.id,
+            name: api_key.name,
+            prefix: api_key.prefix,
+            scopes: api_key.scopes,
+            expires_at: api_key.expires_at,
+            last_used_at: api_key.last_used_at,
+            revoked_at: api_key.revoked_at,
+            created_at: api_key.created_at,
+            organization_id: api_key.organization_id,
+        }
+    }
+}
+
+// Resposta da criação: a única vez em que a chave completa é exibida
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKeyResponse {
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn api_key(scopes: &[&str], expires_at: Option<DateTime<Utc>>) -> ApiKey {
+        ApiKey {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            name: "ci".to_string(),
+            prefix: "pat_0123abcd".to_string(),
+            scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+            organization_id: None,
+        }
+    }
+
+    #[test]
+    fn test_claim_scopes() {
+        assert_eq!(api_key(&[], None).claim_scopes(), None);
+        assert_eq!(
+            api_key(&["users:read"], None).claim_scopes(),
+            Some(vec!["users:read".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_is_expired() {
+        assert!(!api_key(&[], None).is_expired());
+        assert!(!api_key(&[], Some(Utc::now() + Duration::days(1))).is_expired());
+        assert!(api_key(&[], Some(Utc::now() - Duration::seconds(1))).is_expired());
+    }
+}
@@ -1,18 +1,64 @@
 use chrono::{DateTime, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Header, Validation};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
-use super::UserRole;
+use super::{JwtKey, JwtKeyRing, OrganizationRole, PERMISSION_USERS_ADMIN};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
-    pub sub: String,    // Subject (user ID)
-    pub email: String,  // Email do usuário
-    pub nome: String,   // Nome do usuário
-    pub role: UserRole, // Role do usuário
-    pub iat: i64,       // Issued at (timestamp)
-    pub exp: i64,       // Expiration time (timestamp)
+    pub sub: String,        // Subject (user ID)
+    pub email: String,      // Email do usuário
+    pub nome: String,       // Nome do usuário
+    pub iat: i64,           // Issued at (timestamp)
+    pub exp: i64,           // Expiration time (timestamp)
+    pub jti: String,        // JWT ID (usado para revogação)
+    pub token_version: i32, // Versão de token do usuário na emissão
+    // Roles do usuário na emissão, para consumidores externos do token. As
+    // verificações desta API usam as roles atuais (ver `PermissionStore`).
+    #[serde(default)]
+    pub roles: Vec<String>,
+    // Not before (timestamp); `0` em tokens emitidos sem `nbf`
+    #[serde(default)]
+    pub nbf: i64,
+    // Issuer e audience, preenchidos por `JwtConfig::generate_token` quando configurados
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    // `false` em tokens restritos emitidos para contas com email não verificado
+    #[serde(default = "default_email_verified")]
+    pub email_verified: bool,
+    // `true` em tokens restritos à troca de senha (`users.must_change_password`)
+    #[serde(default)]
+    pub must_change_password: bool,
+    // Escopos que limitam a credencial a um subconjunto das permissões do usuário
+    // (chaves de API); ausente = todas as permissões do usuário
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    // Organização ativa (tenant) e o papel do usuário nela na emissão. O middleware
+    // substitui `org_role` pelo papel atual (ausente se o vínculo foi removido).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_role: Option<OrganizationRole>,
+    // Chave de API usada na requisição; nunca presente em JWTs emitidos
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_id: Option<String>,
+    // Cliente OAuth para o qual o token foi emitido (`/oauth/token`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    // Permissões atuais do usuário, preenchidas pelo middleware a cada requisição
+    #[serde(skip)]
+    pub permissions: Vec<String>,
+}
+
+fn default_email_verified() -> bool {
+    true
 }
 
 impl Claims {
@@ -20,7 +66,8 @@ impl Claims {
         user_id: Uuid,
         email: String,
         nome: String,
-        role: UserRole,
+        roles: Vec<String>,
+        token_version: i32,
         expires_in_seconds: i64,
     ) -> Self {
         let now = Utc::now().timestamp();
@@ -28,9 +75,22 @@ impl Claims {
             sub: user_id.to_string(),
             email,
             nome,
-            role,
             iat: now,
             exp: now + expires_in_seconds,
+            jti: Uuid::new_v4().to_string(),
+            token_version,
+            roles,
+            nbf: now,
+            iss: None,
+            aud: None,
+            email_verified: true,
+            must_change_password: false,
+            scopes: None,
+            org_id: None,
+            org_role: None,
+            api_key_id: None,
+            client_id: None,
+            permissions: Vec::new(),
         }
     }
 
@@ -38,12 +98,48 @@ impl Claims {
         Uuid::parse_str(&self.sub)
     }
 
-    pub fn is_expired(&self) -> bool {
-        Utc::now().timestamp() > self.exp
+    pub fn get_organization_id(&self) -> Option<Uuid> {
+        self.org_id
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok())
+    }
+
+    // Define a organização ativa do token
+    pub fn with_organization(mut self, membership: Option<(Uuid, OrganizationRole)>) -> Self {
+        self.org_id = membership.map(|(id, _)| id.to_string());
+        self.org_role = membership.map(|(_, role)| role);
+        self
+    }
+
+    // Credencial entregue a terceiros (chave de API ou cliente OAuth), que não pode
+    // ampliar o próprio acesso
+    pub fn is_delegated(&self) -> bool {
+        self.api_key_id.is_some() || self.client_id.is_some()
+    }
+
+    // O escopo da credencial inclui a permissão (sempre, sem `scopes`)
+    pub fn allows_scope(&self, permission: &str) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.iter().any(|scope| scope == permission))
+    }
+
+    // Permissão concedida pelas roles do usuário e pelo escopo da credencial
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|granted| granted == permission)
+            && self.allows_scope(permission)
+    }
+
+    pub fn effective_permissions(&self) -> Vec<String> {
+        self.permissions
+            .iter()
+            .filter(|permission| self.allows_scope(permission))
+            .cloned()
+            .collect()
     }
 
     pub fn is_admin(&self) -> bool {
-        matches!(self.role, UserRole::Admin)
+        self.has_permission(PERMISSION_USERS_ADMIN)
     }
 }
 
@@ -51,8 +147,14 @@ impl Claims {
 pub struct JwtConfig {
     // pub secret: String,
     pub expires_in_seconds: i64,
-    pub encoding_key: EncodingKey,
-    pub decoding_key: DecodingKey,
+    pub refresh_expires_in_seconds: i64,
+    // Compartilhado entre os workers: a promoção de uma chave vale para todos
+    keys: Arc<RwLock<JwtKeyRing>>,
+    // Diretório de onde as chaves foram carregadas (permite recarregar em tempo de execução)
+    pub keys_dir: Option<PathBuf>,
+    // Gravados em `iss`/`aud` dos tokens emitidos e exigidos na verificação
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
     pub validation: Validation,
 }
 
@@ -61,36 +163,161 @@ impl std::fmt::Debug for JwtConfig {
         f.debug_struct("JwtConfig")
             .field("secret", &"[REDACTED]")
             .field("expires_in_seconds", &self.expires_in_seconds)
-            .field("encoding_key", &"[REDACTED]")
-            .field("decoding_key", &"[REDACTED]")
+            .field(
+                "refresh_expires_in_seconds",
+                &self.refresh_expires_in_seconds,
+            )
+            .field("keys", &self.keys)
+            .field("keys_dir", &self.keys_dir)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
             .field("validation", &self.validation)
             .finish()
     }
 }
 
 impl JwtConfig {
+    #[cfg(test)]
     pub fn new(secret: String, expires_in_seconds: i64) -> Self {
-        let encoding_key = EncodingKey::from_secret(secret.as_bytes());
-        let decoding_key = DecodingKey::from_secret(secret.as_bytes());
-        let validation = Validation::default();
+        Self::with_key(JwtKey::hmac(secret.as_bytes(), None), expires_in_seconds)
+    }
+
+    // Configuração com uma chave arbitrária (HMAC ou assimétrica, ver `JwtKey::from_pem`)
+    #[cfg(test)]
+    pub fn with_key(signing_key: JwtKey, expires_in_seconds: i64) -> Self {
+        Self::with_key_ring(JwtKeyRing::new(signing_key), expires_in_seconds)
+    }
 
+    // Configuração com uma chave ativa e chaves adicionais aceitas apenas na verificação
+    pub fn with_key_ring(keys: JwtKeyRing, expires_in_seconds: i64) -> Self {
         Self {
-            // secret,
             expires_in_seconds,
-            encoding_key,
-            decoding_key,
-            validation,
+            refresh_expires_in_seconds: 30 * 24 * 60 * 60,
+            keys: Arc::new(RwLock::new(keys)),
+            keys_dir: None,
+            issuer: None,
+            audience: None,
+            validation: {
+                let mut validation = Validation::default();
+                validation.validate_nbf = true;
+                validation
+            },
         }
     }
 
+    pub fn with_refresh_expiration(mut self, refresh_expires_in_seconds: i64) -> Self {
+        self.refresh_expires_in_seconds = refresh_expires_in_seconds;
+        self
+    }
+
+    // Tokens sem `iss` ou com outro emissor são rejeitados
+    pub fn with_issuer(mut self, issuer: Option<String>) -> Self {
+        match &issuer {
+            Some(issuer) => {
+                self.validation.set_issuer(&[issuer]);
+                self.validation
+                    .required_spec_claims
+                    .insert("iss".to_string());
+            }
+            None => {
+                self.validation.iss = None;
+                self.validation.required_spec_claims.remove("iss");
+            }
+        }
+        self.issuer = issuer;
+        self
+    }
+
+    // Tokens sem `aud` ou destinados a outro serviço são rejeitados. Sem audiência
+    // configurada, tokens que declaram `aud` também são rejeitados.
+    pub fn with_audience(mut self, audience: Option<String>) -> Self {
+        match &audience {
+            Some(audience) => {
+                self.validation.set_audience(&[audience]);
+                self.validation
+                    .required_spec_claims
+                    .insert("aud".to_string());
+            }
+            None => {
+                self.validation.aud = None;
+                self.validation.required_spec_claims.remove("aud");
+            }
+        }
+        self.audience = audience;
+        self
+    }
+
+    // Tolerância (em segundos) para diferenças de relógio em `exp` e `nbf`
+    pub fn with_leeway(mut self, leeway_seconds: u64) -> Self {
+        self.validation.leeway = leeway_seconds;
+        self
+    }
+
+    pub fn with_keys_dir(mut self, keys_dir: Option<PathBuf>) -> Self {
+        self.keys_dir = keys_dir;
+        self
+    }
+
+    // Cópia do conjunto de chaves atual
+    pub fn key_ring(&self) -> JwtKeyRing {
+        self.keys.read().unwrap().clone()
+    }
+
+    pub fn active_key(&self) -> JwtKey {
+        self.keys.read().unwrap().active().clone()
+    }
+
+    // Substitui todas as chaves (ex.: após recarregar o diretório de chaves)
+    pub fn replace_keys(&self, keys: JwtKeyRing) {
+        *self.keys.write().unwrap() = keys;
+    }
+
+    // Passa a assinar novos tokens com a chave `kid`
+    pub fn promote_key(&self, kid: &str) -> anyhow::Result<()> {
+        self.keys.write().unwrap().promote(kid)
+    }
+
     pub fn generate_token(&self, claims: &Claims) -> Result<String, jsonwebtoken::errors::Error> {
-        jsonwebtoken::encode(&Header::default(), claims, &self.encoding_key)
+        let keys = self.keys.read().unwrap();
+        let signing_key = keys.active();
+        let encoding_key = signing_key
+            .encoding_key
+            .as_ref()
+            .ok_or(ErrorKind::InvalidKeyFormat)?;
+
+        let mut claims = claims.clone();
+        claims.iss = claims.iss.or_else(|| self.issuer.clone());
+        claims.aud = claims.aud.or_else(|| self.audience.clone());
+
+        let mut header = Header::new(signing_key.algorithm);
+        header.kid = signing_key.kid.clone();
+        jsonwebtoken::encode(&header, &claims, encoding_key)
     }
 
+    // Seleciona a chave pelo `kid` do header; tokens sem `kid` (emitidos antes
+    // da rotação de chaves) são testados contra todas as chaves do mesmo algoritmo
     pub fn verify_token(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-        let token_data =
-            jsonwebtoken::decode::<Claims>(token, &self.decoding_key, &self.validation)?;
-        Ok(token_data.claims)
+        let header = jsonwebtoken::decode_header(token)?;
+        let keys = self.keys.read().unwrap();
+        let mut last_error: jsonwebtoken::errors::Error = ErrorKind::InvalidSignature.into();
+
+        for key in keys.verification_keys(header.kid.as_deref(), header.alg) {
+            let mut validation = self.validation.clone();
+            validation.algorithms = vec![key.algorithm];
+
+            match jsonwebtoken::decode::<Claims>(token, &key.decoding_key, &validation) {
+                Ok(token_data) => return Ok(token_data.claims),
+                Err(e) if matches!(e.kind(), ErrorKind::InvalidSignature) => last_error = e,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error)
+    }
+
+    // Chaves públicas publicadas em /.well-known/jwks.json (vazio para HMAC)
+    pub fn jwks(&self) -> JwkSet {
+        self.keys.read().unwrap().jwks()
     }
 }
 
@@ -100,7 +327,7 @@ pub struct TokenInfo {
     pub user_id: Uuid,
     pub email: String,
     pub nome: String,
-    pub role: UserRole,
+    pub roles: Vec<String>,
     pub expires_at: DateTime<Utc>,
 }
 
@@ -110,7 +337,7 @@ impl From<Claims> for TokenInfo {
             user_id: claims.get_user_id().unwrap_or_default(),
             email: claims.email,
             nome: claims.nome,
-            role: claims.role,
+            roles: claims.roles,
             expires_at: DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now),
         }
     }
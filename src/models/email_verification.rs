@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::FromRow;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::password_reset::token_link;
+
+#[derive(Debug, Clone, FromRow)]
+#[allow(dead_code)]
+pub struct EmailVerificationToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EmailVerificationToken {
+    pub fn is_expired(&self) -> bool {
+        Utc::now() > self.expires_at
+    }
+}
+
+// Tratamento de contas com email não verificado no login
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmailVerificationMode {
+    // Login normal; a verificação é apenas informativa
+    #[default]
+    Off,
+    // Login emite tokens restritos (`email_verified: false`), aceitos apenas
+    // nas rotas que não exigem email verificado
+    Restricted,
+    // Login recusado até o email ser verificado
+    Required,
+}
+
+impl FromStr for EmailVerificationMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "off" => Ok(EmailVerificationMode::Off),
+            "restricted" => Ok(EmailVerificationMode::Restricted),
+            "required" => Ok(EmailVerificationMode::Required),
+            other => Err(format!("modo de verificação de email inválido: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailVerificationConfig {
+    pub mode: EmailVerificationMode,
+    pub token_ttl_seconds: i64,
+    // URL do frontend que recebe `?token=...`; sem ela, o email contém apenas o código
+    pub verify_url: Option<String>,
+}
+
+impl EmailVerificationConfig {
+    pub fn verify_link(&self, token: &str) -> Option<String> {
+        self.verify_url.as_ref().map(|url| token_link(url, token))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResendVerificationRequest {
+    pub email: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_verification_mode_from_str() {
+        assert_eq!("off".parse(), Ok(EmailVerificationMode::Off));
+        assert_eq!("Restricted".parse(), Ok(EmailVerificationMode::Restricted));
+        assert_eq!("REQUIRED".parse(), Ok(EmailVerificationMode::Required));
+        assert!("sometimes".parse::<EmailVerificationMode>().is_err());
+    }
+}
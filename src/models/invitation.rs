@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::organization::OrganizationRole;
+use super::password_reset::token_link;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct OrganizationInvitation {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub email: String,
+    pub role: OrganizationRole,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub accepted_by: Option<Uuid>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Revoked,
+    Expired,
+}
+
+impl OrganizationInvitation {
+    pub fn status(&self) -> InvitationStatus {
+        if self.accepted_at.is_some() {
+            InvitationStatus::Accepted
+        } else if self.revoked_at.is_some() {
+            InvitationStatus::Revoked
+        } else if Utc::now() > self.expires_at {
+            InvitationStatus::Expired
+        } else {
+            InvitationStatus::Pending
+        }
+    }
+}
+
+// Convite como exibido aos admins da organização (sem o token)
+#[derive(Debug, Serialize)]
+pub struct InvitationResponse {
+    pub id: Uuid,
+    pub email: String,
+    pub role: OrganizationRole,
+    pub status: InvitationStatus,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub accepted_by: Option<Uuid>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<OrganizationInvitation> for InvitationResponse {
+    fn from(invitation: OrganizationInvitation) -> Self {
+        Self {
+            status: invitation.status(),
+            id: invitation.id,
+            email: invitation.email,
+            role: invitation.role,
+            invited_by: invitation.invited_by,
+            expires_at: invitation.expires_at,
+            accepted_at: invitation.accepted_at,
+            accepted_by: invitation.accepted_by,
+            revoked_at: invitation.revoked_at,
+            created_at: invitation.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InvitationConfig {
+    pub token_ttl_seconds: i64,
+    // URL do frontend que recebe `?token=...`; sem ela, o email contém apenas o token
+    pub accept_url: Option<String>,
+}
+
+impl InvitationConfig {
+    pub fn accept_link(&self, token: &str) -> Option<String> {
+        self.accept_url.as_ref().map(|url| token_link(url, token))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    #[serde(default)]
+    pub role: OrganizationRole,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvitationTokenRequest {
+    pub token: String,
+}
+
+// Aceite do convite. `nome` e `senha` são exigidos apenas quando o email
+// convidado ainda não possui conta.
+#[derive(Debug, Deserialize)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+    pub nome: Option<String>,
+    pub senha: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_invitation_status() {
+        let mut invitation = OrganizationInvitation {
+            id: Uuid::new_v4(),
+            organization_id: Uuid::new_v4(),
+            email: "convidado@x.com".to_string(),
+            role: OrganizationRole::Member,
+            invited_by: None,
+            expires_at: Utc::now() + Duration::hours(1),
+            accepted_at: None,
+            accepted_by: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        };
+        assert_eq!(invitation.status(), InvitationStatus::Pending);
+
+        invitation.expires_at = Utc::now() - Duration::seconds(1);
+        assert_eq!(invitation.status(), InvitationStatus::Expired);
+
+        invitation.revoked_at = Some(Utc::now());
+        assert_eq!(invitation.status(), InvitationStatus::Revoked);
+
+        invitation.accepted_at = Some(Utc::now());
+        assert_eq!(invitation.status(), InvitationStatus::Accepted);
+
+        let response = InvitationResponse::from(invitation);
+        assert_eq!(
+            serde_json::to_value(&response).unwrap()["status"],
+            "accepted"
+        );
+
+        let request: CreateInvitationRequest =
+            serde_json::from_value(serde_json::json!({ "email": "a@b.com" })).unwrap();
+        assert_eq!(request.role, OrganizationRole::Member);
+    }
+}
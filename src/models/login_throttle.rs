@@ -0,0 +1,215 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::FromRow;
+
+// Escopo do controle de falhas de login
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleScope {
+    // Identificado pelo email informado (existente ou não)
+    Account,
+    // Identificado pelo IP de origem
+    Ip,
+}
+
+impl ThrottleScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThrottleScope::Account => "account",
+            ThrottleScope::Ip => "ip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+#[allow(dead_code)]
+pub struct LoginThrottle {
+    pub scope: String,
+    pub identifier: String,
+    pub failed_count: i32,
+    pub last_failed_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginThrottleConfig {
+    // Falhas toleradas antes de começar o atraso progressivo
+    pub backoff_after: i32,
+    // Atraso após a primeira falha excedente; dobra a cada nova falha
+    pub backoff_base_seconds: i64,
+    pub backoff_max_seconds: i64,
+    // Falhas que bloqueiam temporariamente a conta / o IP
+    pub account_lock_threshold: i32,
+    pub ip_lock_threshold: i32,
+    pub lock_seconds: i64,
+    // Sem novas falhas nesse intervalo, a contagem recomeça
+    pub reset_after_seconds: i64,
+}
+
+impl LoginThrottleConfig {
+    pub fn lock_threshold(&self, scope: ThrottleScope) -> i32 {
+        match scope {
+            ThrottleScope::Account => self.account_lock_threshold,
+            ThrottleScope::Ip => self.ip_lock_threshold,
+        }
+    }
+
+    // Atraso exigido após `failed_count` falhas consecutivas
+    pub fn backoff(&self, failed_count: i32) -> Duration {
+        let excess = failed_count - self.backoff_after;
+        if excess <= 0 {
+            return Duration::zero();
+        }
+
+        let seconds = self
+            .backoff_base_seconds
+            .saturating_mul(1i64 << (excess - 1).min(30))
+            .min(self.backoff_max_seconds);
+        Duration::seconds(seconds)
+    }
+}
+
+// Motivo pelo qual uma tentativa de login é recusada antes de conferir a senha
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginBlock {
+    // Bloqueio temporário após atingir o limite de falhas
+    Locked {
+        scope: ThrottleScope,
+        until: DateTime<Utc>,
+    },
+    // Atraso progressivo entre tentativas
+    Backoff {
+        until: DateTime<Utc>,
+    },
+}
+
+impl LoginBlock {
+    pub fn until(&self) -> DateTime<Utc> {
+        match self {
+            LoginBlock::Locked { until, .. } | LoginBlock::Backoff { until } => *until,
+        }
+    }
+
+    // Segundos até a próxima tentativa permitida (arredondado para cima)
+    pub fn retry_after_seconds(&self, now: DateTime<Utc>) -> u64 {
+        let millis = (self.until() - now).num_milliseconds().max(0) as u64;
+        millis.div_ceil(1000).max(1)
+    }
+}
+
+impl LoginThrottle {
+    pub fn scope(&self) -> ThrottleScope {
+        if self.scope == ThrottleScope::Ip.as_str() {
+            ThrottleScope::Ip
+        } else {
+            ThrottleScope::Account
+        }
+    }
+
+    // Bloqueio vigente em `now`, se houver
+    pub fn block(&self, config: &LoginThrottleConfig, now: DateTime<Utc>) -> Option<LoginBlock> {
+        if let Some(until) = self.locked_until.filter(|until| *until > now) {
+            return Some(LoginBlock::Locked {
+                scope: self.scope(),
+                until,
+            });
+        }
+
+        // O atraso progressivo vale só para a conta; o IP, que pode ser
+        // compartilhado (NAT), apenas é bloqueado ao atingir seu limite
+        if self.scope() == ThrottleScope::Ip {
+            return None;
+        }
+
+        // Contagem zerada por inatividade ou por bloqueio já cumprido
+        if self.locked_until.is_some()
+            || now - self.last_failed_at > Duration::seconds(config.reset_after_seconds)
+        {
+            return None;
+        }
+
+        let until = self.last_failed_at + config.backoff(self.failed_count);
+        (until > now).then_some(LoginBlock::Backoff { until })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LoginThrottleConfig {
+        LoginThrottleConfig {
+            backoff_after: 3,
+            backoff_base_seconds: 1,
+            backoff_max_seconds: 30,
+            account_lock_threshold: 10,
+            ip_lock_threshold: 50,
+            lock_seconds: 900,
+            reset_after_seconds: 3600,
+        }
+    }
+
+    fn throttle(failed_count: i32, last_failed_at: DateTime<Utc>) -> LoginThrottle {
+        LoginThrottle {
+            scope: "account".to_string(),
+            identifier: "joao@exemplo.com".to_string(),
+            failed_count,
+            last_failed_at,
+            locked_until: None,
+        }
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let config = config();
+
+        assert_eq!(config.backoff(3), Duration::zero());
+        assert_eq!(config.backoff(4), Duration::seconds(1));
+        assert_eq!(config.backoff(5), Duration::seconds(2));
+        assert_eq!(config.backoff(7), Duration::seconds(8));
+        assert_eq!(config.backoff(9), Duration::seconds(30));
+        assert_eq!(config.backoff(1000), Duration::seconds(30));
+    }
+
+    #[test]
+    fn test_block() {
+        let config = config();
+        let now = Utc::now();
+
+        assert_eq!(throttle(3, now).block(&config, now), None);
+        assert_eq!(
+            throttle(6, now).block(&config, now),
+            Some(LoginBlock::Backoff {
+                until: now + Duration::seconds(4)
+            })
+        );
+        assert_eq!(
+            throttle(6, now - Duration::seconds(5)).block(&config, now),
+            None
+        );
+        // Falhas antigas não contam mais
+        assert_eq!(
+            throttle(9, now - Duration::seconds(7200)).block(&config, now),
+            None
+        );
+
+        let mut locked = throttle(10, now);
+        locked.locked_until = Some(now + Duration::seconds(900));
+        let block = locked.block(&config, now).unwrap();
+        assert!(matches!(
+            block,
+            LoginBlock::Locked {
+                scope: ThrottleScope::Account,
+                ..
+            }
+        ));
+        assert_eq!(block.retry_after_seconds(now), 900);
+
+        // Bloqueio cumprido libera a conta
+        locked.locked_until = Some(now - Duration::seconds(1));
+        assert_eq!(locked.block(&config, now), None);
+
+        // IP não sofre atraso progressivo, apenas bloqueio
+        let mut ip = throttle(6, now);
+        ip.scope = ThrottleScope::Ip.as_str().to_string();
+        assert_eq!(ip.block(&config, now), None);
+    }
+}
@@ -1,5 +1,29 @@
+pub mod api_key;
 pub mod auth;
+pub mod email_verification;
+pub mod invitation;
+pub mod jwt_key;
+pub mod login_throttle;
+pub mod oauth;
+pub mod oidc;
+pub mod organization;
+pub mod password_reset;
+pub mod rbac;
+pub mod refresh_token;
+pub mod two_factor;
 pub mod user;
 
+pub use api_key::*;
 pub use auth::*;
+pub use email_verification::*;
+pub use invitation::*;
+pub use jwt_key::*;
+pub use login_throttle::*;
+pub use oauth::*;
+pub use oidc::*;
+pub use organization::*;
+pub use password_reset::*;
+pub use rbac::*;
+pub use refresh_token::*;
+pub use two_factor::*;
 pub use user::*;
@@ -0,0 +1,299 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use url::Url;
+use uuid::Uuid;
+
+// Grants aceitos em `/oauth/token`
+pub const GRANT_AUTHORIZATION_CODE: &str = "authorization_code";
+pub const GRANT_CLIENT_CREDENTIALS: &str = "client_credentials";
+pub const GRANT_TYPES: [&str; 2] = [GRANT_AUTHORIZATION_CODE, GRANT_CLIENT_CREDENTIALS];
+
+// Início de todo `client_id` gerado pela API
+pub const OAUTH_CLIENT_ID_PREFIX: &str = "cli_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "oauth_client_type", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum OAuthClientType {
+    // Guarda um client_secret (servidores de parceiros)
+    Confidential,
+    // Não consegue guardar segredos (SPAs e apps nativos); depende apenas do PKCE
+    Public,
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    // Validade do código de autorização entregue ao `redirect_uri`
+    pub code_ttl_seconds: i64,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct OAuthClient {
+    pub id: Uuid,
+    pub client_id: String,
+    pub name: String,
+    pub client_type: OAuthClientType,
+    pub secret_hash: Option<String>,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    pub user_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OAuthClient {
+    pub fn allows_grant(&self, grant_type: &str) -> bool {
+        self.grant_types.iter().any(|grant| grant == grant_type)
+    }
+
+    // O `redirect_uri` precisa ser idêntico a um dos registrados
+    pub fn allows_redirect_uri(&self, redirect_uri: &str) -> bool {
+        self.redirect_uris.iter().any(|uri| uri == redirect_uri)
+    }
+
+    // Escopos pedidos (separados por espaço) ou, sem pedido, todos os do cliente.
+    // `None` se algum escopo pedido não foi concedido ao cliente.
+    pub fn granted_scopes(&self, requested: Option<&str>) -> Option<Vec<String>> {
+        let requested = requested.map(str::trim).filter(|scope| !scope.is_empty());
+        let Some(requested) = requested else {
+            return Some(self.scopes.clone());
+        };
+
+        let mut scopes: Vec<String> = Vec::new();
+        for scope in requested.split_whitespace() {
+            if !self.scopes.iter().any(|allowed| allowed == scope) {
+                return None;
+            }
+            if !scopes.iter().any(|granted| granted == scope) {
+                scopes.push(scope.to_string());
+            }
+        }
+        Some(scopes)
+    }
+}
+
+// URL absoluta sem fragmento (RFC 6749, seção 3.1.2); esquemas próprios de apps
+// nativos são aceitos
+pub fn valid_redirect_uri(redirect_uri: &str) -> bool {
+    Url::parse(redirect_uri).is_ok_and(|url| !url.cannot_be_a_base() && url.fragment().is_none())
+}
+
+// Verificador PKCE: 43 a 128 caracteres `[A-Za-z0-9-._~]` (RFC 7636, seção 4.1)
+pub fn valid_code_verifier(code_verifier: &str) -> bool {
+    (43..=128).contains(&code_verifier.len())
+        && code_verifier
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"-._~".contains(&byte))
+}
+
+// Desafio S256: SHA-256 do verificador em base64url sem padding (43 caracteres)
+pub fn valid_code_challenge(code_challenge: &str) -> bool {
+    code_challenge.len() == 43
+        && code_challenge
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOAuthClientRequest {
+    pub name: String,
+    pub client_type: OAuthClientType,
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    // Conta de serviço em nome da qual o grant client_credentials age; obrigatória
+    // com esse grant
+    pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OAuthClientResponse {
+    pub id: Uuid,
+    pub client_id: String,
+    pub name: String,
+    pub client_type: OAuthClientType,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    pub user_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<OAuthClient> for OAuthClientResponse {
+    fn from(client: OAuthClient) -> Self {
+        Self {
+            id: client.id,
+            client_id: client.client_id,
+            name: client.name,
+            client_type: client.client_type,
+            redirect_uris: client.redirect_uris,
+            grant_types: client.grant_types,
+            scopes: client.scopes,
+            user_id: client.user_id,
+            created_by: client.created_by,
+            revoked_at: client.revoked_at,
+            created_at: client.created_at,
+        }
+    }
+}
+
+// Resposta do registro: a única vez em que o client_secret é exibido
+#[derive(Debug, Serialize)]
+pub struct CreatedOAuthClientResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[serde(flatten)]
+    pub client: OAuthClientResponse,
+}
+
+// Dados do código de autorização necessários para emitir o token
+#[derive(Debug, FromRow)]
+pub struct OAuthAuthorizationCode {
+    pub user_id: Uuid,
+    pub organization_id: Option<Uuid>,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    pub code_challenge: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Parâmetros de `/oauth/authorize` (query string no GET, JSON no POST)
+#[derive(Debug, Deserialize)]
+pub struct AuthorizeRequest {
+    pub response_type: Option<String>,
+    pub client_id: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+}
+
+// Decisão do usuário na tela de consentimento
+#[derive(Debug, Deserialize)]
+pub struct AuthorizeDecisionRequest {
+    #[serde(flatten)]
+    pub request: AuthorizeRequest,
+    pub approve: bool,
+}
+
+// Dados para a tela de consentimento
+#[derive(Debug, Serialize)]
+pub struct AuthorizeResponse {
+    pub client_id: String,
+    pub client_name: String,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+}
+
+// URL para a qual o frontend redireciona o navegador após a decisão
+#[derive(Debug, Serialize)]
+pub struct AuthorizeRedirectResponse {
+    pub redirect_to: String,
+}
+
+// Corpo de `/oauth/token` (`application/x-www-form-urlencoded`)
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    pub grant_type: Option<String>,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub scope: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+// Resposta de `/oauth/token` (RFC 6749, seção 5.1)
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(scopes: &[&str]) -> OAuthClient {
+        OAuthClient {
+            id: Uuid::new_v4(),
+            client_id: "cli_0123".to_string(),
+            name: "Parceiro".to_string(),
+            client_type: OAuthClientType::Public,
+            secret_hash: None,
+            redirect_uris: vec!["https://parceiro.com/callback".to_string()],
+            grant_types: vec![GRANT_AUTHORIZATION_CODE.to_string()],
+            scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+            user_id: None,
+            created_by: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_granted_scopes() {
+        let client = client(&["users:read", "users:write"]);
+
+        assert_eq!(
+            client.granted_scopes(None),
+            Some(vec!["users:read".to_string(), "users:write".to_string()])
+        );
+        assert_eq!(
+            client.granted_scopes(Some("  ")),
+            client.granted_scopes(None)
+        );
+        assert_eq!(
+            client.granted_scopes(Some("users:read users:read")),
+            Some(vec!["users:read".to_string()])
+        );
+        assert_eq!(client.granted_scopes(Some("users:read users:admin")), None);
+    }
+
+    #[test]
+    fn test_redirect_uri() {
+        let client = client(&[]);
+        assert!(client.allows_redirect_uri("https://parceiro.com/callback"));
+        assert!(!client.allows_redirect_uri("https://parceiro.com/callback/"));
+        assert!(!client.allows_redirect_uri("https://parceiro.com/callback?x=1"));
+        assert!(!client.allows_grant(GRANT_CLIENT_CREDENTIALS));
+
+        assert!(valid_redirect_uri(
+            "https://parceiro.com/callback?origem=api"
+        ));
+        assert!(valid_redirect_uri("http://localhost:3000/callback"));
+        assert!(valid_redirect_uri("com.parceiro.app:/callback"));
+        assert!(!valid_redirect_uri("https://parceiro.com/callback#token"));
+        assert!(!valid_redirect_uri("/callback"));
+        assert!(!valid_redirect_uri("javascript:alert(1)"));
+    }
+
+    #[test]
+    fn test_pkce_format() {
+        assert!(valid_code_verifier(
+            "dBjftJeZ4CVP-mB92K1uhbF2J8dR2X5ARxI6hoR1mWk"
+        ));
+        assert!(!valid_code_verifier("curto"));
+        assert!(!valid_code_verifier(&"a".repeat(129)));
+        assert!(!valid_code_verifier(&format!("{}+/", "a".repeat(43))));
+
+        assert!(valid_code_challenge(
+            "zXV0fA6b-N3ACQUwQ5sP1AmXxMrR3kqdhBOyI3xtwcs"
+        ));
+        assert!(!valid_code_challenge("plain"));
+        assert!(!valid_code_challenge(
+            "zXV0fA6b+N3ACQUwQ5sP1AmXxMrR3kqdhBOyI3xtwcs"
+        ));
+    }
+}
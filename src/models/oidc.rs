@@ -0,0 +1,196 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+const MAX_NAME_LENGTH: usize = 255;
+
+// Provedor OpenID Connect usado no login (fluxo authorization code + PKCE)
+#[derive(Clone)]
+pub struct OidcConfig {
+    // Identificador do provedor; os metadados vêm de `{issuer}/.well-known/openid-configuration`
+    pub issuer: String,
+    pub client_id: String,
+    // Ausente para clientes públicos, que dependem apenas do PKCE
+    pub client_secret: Option<String>,
+    // URL registrada no provedor, que recebe `?code=...&state=...` após o login
+    pub redirect_uri: String,
+    pub scopes: String,
+    // Domínios de email aceitos (em minúsculas); vazio aceita qualquer domínio
+    pub allowed_domains: Vec<String>,
+    // Cria o usuário local no primeiro login de uma identidade desconhecida
+    pub auto_provision: bool,
+    pub login_ttl_seconds: i64,
+}
+
+impl std::fmt::Debug for OidcConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OidcConfig")
+            .field("issuer", &self.issuer)
+            .field("client_id", &self.client_id)
+            .field(
+                "client_secret",
+                &self.client_secret.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("redirect_uri", &self.redirect_uri)
+            .field("scopes", &self.scopes)
+            .field("allowed_domains", &self.allowed_domains)
+            .field("auto_provision", &self.auto_provision)
+            .field("login_ttl_seconds", &self.login_ttl_seconds)
+            .finish()
+    }
+}
+
+impl OidcConfig {
+    pub fn email_allowed(&self, email: &str) -> bool {
+        if self.allowed_domains.is_empty() {
+            return true;
+        }
+
+        match email.rsplit_once('@') {
+            Some((_, domain)) => self
+                .allowed_domains
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(domain)),
+            None => false,
+        }
+    }
+}
+
+// Claims do ID token usados no login; os demais são ignorados
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub azp: Option<String>,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    // Alguns provedores enviam "true"/"false" como texto
+    #[serde(default, deserialize_with = "lenient_bool")]
+    pub email_verified: Option<bool>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+}
+
+impl IdTokenClaims {
+    // Nome do usuário criado no primeiro login: `name`, nome + sobrenome ou o início do email
+    pub fn display_name(&self) -> String {
+        let full_name = [self.given_name.as_deref(), self.family_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let name = self
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .or_else(|| (!full_name.is_empty()).then_some(full_name))
+            .or_else(|| {
+                self.email
+                    .as_deref()
+                    .and_then(|email| email.split('@').next())
+                    .filter(|local| !local.is_empty())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| self.sub.clone());
+
+        name.chars().take(MAX_NAME_LENGTH).collect()
+    }
+}
+
+fn lenient_bool<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrString {
+        Bool(bool),
+        String(String),
+    }
+
+    Ok(match Option::<BoolOrString>::deserialize(deserializer)? {
+        Some(BoolOrString::Bool(value)) => Some(value),
+        Some(BoolOrString::String(value)) => Some(value.eq_ignore_ascii_case("true")),
+        None => None,
+    })
+}
+
+// Início do login: o cliente redireciona o navegador para `authorization_url`
+// e guarda o `state` para conferir no retorno
+#[derive(Debug, Serialize)]
+pub struct OidcAuthorizationResponse {
+    pub authorization_url: String,
+    pub state: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Parâmetros recebidos pelo `redirect_uri` e repassados à API
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackRequest {
+    pub code: String,
+    pub state: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(allowed_domains: &[&str]) -> OidcConfig {
+        OidcConfig {
+            issuer: "https://sso.empresa.com".to_string(),
+            client_id: "api".to_string(),
+            client_secret: Some("segredo".to_string()),
+            redirect_uri: "https://app.empresa.com/sso/callback".to_string(),
+            scopes: "openid email profile".to_string(),
+            allowed_domains: allowed_domains.iter().map(|d| d.to_string()).collect(),
+            auto_provision: true,
+            login_ttl_seconds: 600,
+        }
+    }
+
+    #[test]
+    fn test_email_domain_allowlist() {
+        assert!(config(&[]).email_allowed("qualquer@gmail.com"));
+
+        let config = config(&["empresa.com", "empresa.com.br"]);
+        assert!(config.email_allowed("ana@empresa.com"));
+        assert!(config.email_allowed("ana@EMPRESA.com.br"));
+        assert!(!config.email_allowed("ana@sub.empresa.com"));
+        assert!(!config.email_allowed("ana@empresa.com.evil.io"));
+        assert!(!config.email_allowed("empresa.com"));
+
+        assert!(!format!("{:?}", config).contains("segredo"));
+    }
+
+    #[test]
+    fn test_id_token_claims() {
+        let claims: IdTokenClaims = serde_json::from_value(serde_json::json!({
+            "iss": "https://sso.empresa.com",
+            "sub": "123",
+            "aud": "api",
+            "email": "ana.souza@empresa.com",
+            "email_verified": "true",
+            "given_name": "Ana",
+            "family_name": "Souza"
+        }))
+        .unwrap();
+        assert_eq!(claims.email_verified, Some(true));
+        assert_eq!(claims.display_name(), "Ana Souza");
+
+        let claims: IdTokenClaims = serde_json::from_value(serde_json::json!({
+            "iss": "https://sso.empresa.com",
+            "sub": "123",
+            "email": "ana.souza@empresa.com",
+            "email_verified": false,
+            "name": "  "
+        }))
+        .unwrap();
+        assert_eq!(claims.email_verified, Some(false));
+        assert_eq!(claims.display_name(), "ana.souza");
+    }
+}
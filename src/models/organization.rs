@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+const MAX_ORGANIZATION_NAME_LENGTH: usize = 100;
+
+// Papel do usuário dentro de uma organização, em ordem crescente de privilégio
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    sqlx::Type,
+)]
+#[sqlx(type_name = "organization_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum OrganizationRole {
+    #[default]
+    Member,
+    Admin,
+    Owner,
+}
+
+impl std::fmt::Display for OrganizationRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrganizationRole::Member => write!(f, "member"),
+            OrganizationRole::Admin => write!(f, "admin"),
+            OrganizationRole::Owner => write!(f, "owner"),
+        }
+    }
+}
+
+impl OrganizationRole {
+    // Owners gerenciam qualquer vínculo; admins gerenciam membros e outros admins
+    pub fn can_manage(self, target: OrganizationRole) -> bool {
+        match self {
+            OrganizationRole::Owner => true,
+            OrganizationRole::Admin => target != OrganizationRole::Owner,
+            OrganizationRole::Member => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Organization {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Organização da qual o usuário participa, com o papel dele
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct UserOrganization {
+    pub id: Uuid,
+    pub name: String,
+    pub role: OrganizationRole,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Membro de uma organização (sem dados de outras organizações)
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OrganizationMember {
+    pub user_id: Uuid,
+    pub nome: String,
+    pub email: String,
+    pub role: OrganizationRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrganizationMemberListResponse {
+    pub members: Vec<OrganizationMember>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOrganizationRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateOrganizationRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMemberRequest {
+    pub role: OrganizationRole,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwitchOrganizationRequest {
+    pub organization_id: Uuid,
+}
+
+// Nome sem espaços nas pontas; `None` se vazio ou longo demais
+pub fn normalize_organization_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    let valid = !name.is_empty() && name.chars().count() <= MAX_ORGANIZATION_NAME_LENGTH;
+    valid.then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_organization_role_order_and_management() {
+        assert!(OrganizationRole::Owner > OrganizationRole::Admin);
+        assert!(OrganizationRole::Admin > OrganizationRole::Member);
+
+        assert!(OrganizationRole::Owner.can_manage(OrganizationRole::Owner));
+        assert!(OrganizationRole::Admin.can_manage(OrganizationRole::Admin));
+        assert!(OrganizationRole::Admin.can_manage(OrganizationRole::Member));
+        assert!(!OrganizationRole::Admin.can_manage(OrganizationRole::Owner));
+        assert!(!OrganizationRole::Member.can_manage(OrganizationRole::Member));
+
+        assert_eq!(
+            serde_json::to_value(OrganizationRole::Owner).unwrap(),
+            "owner"
+        );
+    }
+
+    #[test]
+    fn test_normalize_organization_name() {
+        assert_eq!(
+            normalize_organization_name("  Acme Ltda "),
+            Some("Acme Ltda".to_string())
+        );
+        assert_eq!(normalize_organization_name("   "), None);
+        assert_eq!(
+            normalize_organization_name(&"é".repeat(100)).map(|n| n.chars().count()),
+            Some(100)
+        );
+        assert_eq!(normalize_organization_name(&"a".repeat(101)), None);
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow)]
+#[allow(dead_code)]
+pub struct PasswordResetToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PasswordResetToken {
+    pub fn is_expired(&self) -> bool {
+        Utc::now() > self.expires_at
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PasswordResetConfig {
+    pub token_ttl_seconds: i64,
+    // URL do frontend que recebe `?token=...`; sem ela, o email contém apenas o token
+    pub reset_url: Option<String>,
+}
+
+impl PasswordResetConfig {
+    pub fn reset_link(&self, token: &str) -> Option<String> {
+        self.reset_url.as_ref().map(|url| token_link(url, token))
+    }
+}
+
+// Acrescenta `token=...` à URL do frontend
+pub fn token_link(url: &str, token: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}token={}", url, separator, token)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub senha_nova: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_link() {
+        let mut config = PasswordResetConfig {
+            token_ttl_seconds: 3600,
+            reset_url: None,
+        };
+        assert_eq!(config.reset_link("abc"), None);
+
+        config.reset_url = Some("https://app/reset".to_string());
+        assert_eq!(
+            config.reset_link("abc").as_deref(),
+            Some("https://app/reset?token=abc")
+        );
+
+        config.reset_url = Some("https://app/?page=reset".to_string());
+        assert_eq!(
+            config.reset_link("abc").as_deref(),
+            Some("https://app/?page=reset&token=abc")
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use super::OrganizationRole;
+
+// Permissões verificadas pelo código (criadas pela migração do RBAC)
+pub const PERMISSION_USERS_READ: &str = "users:read";
+pub const PERMISSION_USERS_WRITE: &str = "users:write";
+pub const PERMISSION_USERS_ADMIN: &str = "users:admin";
+
+// Roles padrão: novos usuários recebem `USER`; o administrador inicial recebe `ADMIN`
+pub const ROLE_USER: &str = "USER";
+pub const ROLE_ADMIN: &str = "ADMIN";
+
+const MAX_PERMISSION_NAME_LENGTH: usize = 100;
+const MAX_ROLE_NAME_LENGTH: usize = 50;
+const MAX_GROUP_NAME_LENGTH: usize = 100;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Permission {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_system: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// Role com os nomes das permissões que concede
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Role {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_system: bool,
+    pub permissions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePermissionRequest {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+// Campos ausentes não são alterados; `permissions` substitui o conjunto inteiro
+#[derive(Debug, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub permissions: Option<Vec<String>>,
+}
+
+// Substitui as roles do usuário
+#[derive(Debug, Deserialize)]
+pub struct AssignRolesRequest {
+    pub roles: Vec<String>,
+}
+
+// Grupo com as roles e permissões que concede a todos os membros
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Group {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    pub member_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct GroupMember {
+    pub user_id: Uuid,
+    pub nome: String,
+    pub email: String,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateGroupRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+// Campos ausentes não são alterados; `roles` e `permissions` substituem o conjunto inteiro
+#[derive(Debug, Deserialize)]
+pub struct UpdateGroupRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub roles: Option<Vec<String>>,
+    pub permissions: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddGroupMembersRequest {
+    pub user_ids: Vec<Uuid>,
+}
+
+// Roles diretas do usuário, seus grupos, a união das permissões concedidas por
+// ambos e o papel do usuário em cada organização da qual participa
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UserAccess {
+    pub roles: Vec<String>,
+    pub groups: Vec<String>,
+    pub permissions: Vec<String>,
+    pub organizations: HashMap<Uuid, OrganizationRole>,
+}
+
+// Origem de uma permissão efetiva: role direta (`role`), role de um grupo
+// (`role` e `group`) ou permissão atribuída ao grupo (`group`)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct PermissionGrant {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectivePermission {
+    pub name: String,
+    pub granted_by: Vec<PermissionGrant>,
+}
+
+// Agrupa as origens `(permissão, role, grupo)` por permissão, em ordem alfabética
+pub fn effective_permissions(
+    grants: Vec<(String, Option<String>, Option<String>)>,
+) -> Vec<EffectivePermission> {
+    let mut by_permission: BTreeMap<String, Vec<PermissionGrant>> = BTreeMap::new();
+    for (permission, role, group) in grants {
+        by_permission
+            .entry(permission)
+            .or_default()
+            .push(PermissionGrant { group, role });
+    }
+
+    by_permission
+        .into_iter()
+        .map(|(name, mut granted_by)| {
+            granted_by.sort();
+            granted_by.dedup();
+            EffectivePermission { name, granted_by }
+        })
+        .collect()
+}
+
+// Formato `recurso:ação` (ex.: `users:read`), com letras minúsculas, dígitos, `_` e `-`
+pub fn is_valid_permission_name(name: &str) -> bool {
+    name.len() <= MAX_PERMISSION_NAME_LENGTH
+        && name.split(':').count() >= 2
+        && name.split(':').all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        })
+}
+
+// Nome da role em maiúsculas (ex.: `suporte` -> `SUPORTE`); `None` se inválido
+pub fn normalize_role_name(name: &str) -> Option<String> {
+    let name = name.trim().to_ascii_uppercase();
+    let valid = !name.is_empty()
+        && name.len() <= MAX_ROLE_NAME_LENGTH
+        && name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    valid.then_some(name)
+}
+
+// Nome sem espaços nas pontas; `None` se vazio ou longo demais
+pub fn normalize_group_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    let valid = !name.is_empty() && name.chars().count() <= MAX_GROUP_NAME_LENGTH;
+    valid.then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_permission_name() {
+        assert!(is_valid_permission_name(PERMISSION_USERS_ADMIN));
+        assert!(is_valid_permission_name("reports:export-csv"));
+        assert!(is_valid_permission_name("orgs:members:write"));
+        assert!(!is_valid_permission_name("users"));
+        assert!(!is_valid_permission_name("users:"));
+        assert!(!is_valid_permission_name("Users:Read"));
+        assert!(!is_valid_permission_name("users: read"));
+    }
+
+    #[test]
+    fn test_normalize_role_name() {
+        assert_eq!(
+            normalize_role_name(" suporte "),
+            Some("SUPORTE".to_string())
+        );
+        assert_eq!(
+            normalize_role_name("Billing_Admin2"),
+            Some("BILLING_ADMIN2".to_string())
+        );
+        assert_eq!(normalize_role_name(""), None);
+        assert_eq!(normalize_role_name("2FA"), None);
+        assert_eq!(normalize_role_name("SUPORTE-N1"), None);
+        assert_eq!(normalize_role_name(&"A".repeat(51)), None);
+    }
+
+    #[test]
+    fn test_effective_permissions() {
+        let grant = |permission: &str, role: Option<&str>, group: Option<&str>| {
+            (
+                permission.to_string(),
+                role.map(str::to_string),
+                group.map(str::to_string),
+            )
+        };
+
+        let permissions = effective_permissions(vec![
+            grant("users:write", Some("USER"), None),
+            grant("users:read", Some("SUPORTE"), Some("Suporte")),
+            grant("users:read", Some("USER"), None),
+            grant("reports:read", None, Some("Suporte")),
+            grant("users:read", Some("USER"), None),
+        ]);
+
+        let names: Vec<&str> = permissions.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["reports:read", "users:read", "users:write"]);
+        // Roles diretas primeiro; origens repetidas aparecem uma vez
+        assert_eq!(
+            serde_json::to_value(&permissions[1].granted_by).unwrap(),
+            serde_json::json!([
+                { "role": "USER" },
+                { "group": "Suporte", "role": "SUPORTE" }
+            ])
+        );
+        assert_eq!(
+            serde_json::to_value(&permissions[0].granted_by).unwrap(),
+            serde_json::json!([{ "group": "Suporte" }])
+        );
+    }
+
+    #[test]
+    fn test_normalize_group_name() {
+        assert_eq!(
+            normalize_group_name("  Suporte N1 "),
+            Some("Suporte N1".to_string())
+        );
+        assert_eq!(normalize_group_name(" "), None);
+        assert_eq!(normalize_group_name(&"g".repeat(101)), None);
+    }
+}
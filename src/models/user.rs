@@ -3,38 +3,23 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "user_role")]
-#[sqlx(rename_all = "UPPERCASE")]
-pub enum UserRole {
-    User,
-    Admin,
-}
-
-impl Default for UserRole {
-    fn default() -> Self {
-        UserRole::User
-    }
-}
-
-impl std::fmt::Display for UserRole {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            UserRole::User => write!(f, "USER"),
-            UserRole::Admin => write!(f, "ADMIN"),
-        }
-    }
-}
-
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: Uuid,
     pub nome: String,
     pub email: String,
     pub senha: String,
-    pub role: UserRole,
+    pub token_version: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub totp_last_step: Option<i64>,
+    pub password_changed_at: DateTime<Utc>,
+    pub must_change_password: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,7 +27,8 @@ pub struct CreateUserRequest {
     pub nome: String,
     pub email: String,
     pub senha: String,
-    pub role: Option<UserRole>,
+    // Nome de uma nova organização da qual o usuário será owner
+    pub organizacao: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -50,7 +36,6 @@ pub struct UpdateUserRequest {
     pub nome: Option<String>,
     pub email: Option<String>,
     pub senha: Option<String>,
-    pub role: Option<UserRole>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -59,17 +44,36 @@ pub struct ChangePasswordRequest {
     pub senha_nova: String,
 }
 
+// Troca de senha expirada, feita sem token: exige a senha atual e, com 2FA
+// ativo, um código TOTP ou de recuperação
+#[derive(Debug, Deserialize)]
+pub struct ExpiredPasswordChangeRequest {
+    pub email: String,
+    pub senha_atual: String,
+    pub senha_nova: String,
+    pub code: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LoginRequest {
     pub email: String,
     pub senha: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct LogoutRequest {
+    pub refresh_token: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
     pub user: UserResponse,
     pub token: String,
     pub expires_at: DateTime<Utc>,
+    pub refresh_token: String,
+    pub refresh_expires_at: DateTime<Utc>,
+    // Organização ativa gravada no token (`null` se o usuário não participa de nenhuma)
+    pub organization_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize)]
@@ -77,7 +81,10 @@ pub struct UserResponse {
     pub id: Uuid,
     pub nome: String,
     pub email: String,
-    pub role: UserRole,
+    pub roles: Vec<String>,
+    pub email_verified: bool,
+    pub two_factor_enabled: bool,
+    pub must_change_password: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -108,13 +115,17 @@ impl Default for UserQueryParams {
     }
 }
 
-impl From<User> for UserResponse {
-    fn from(user: User) -> Self {
+impl UserResponse {
+    // `roles` vem de `rbac_service` (ver `models::rbac`)
+    pub fn new(user: User, roles: Vec<String>) -> Self {
         UserResponse {
             id: user.id,
             nome: user.nome,
             email: user.email,
-            role: user.role,
+            roles,
+            email_verified: user.email_verified_at.is_some(),
+            two_factor_enabled: user.totp_enabled_at.is_some(),
+            must_change_password: user.must_change_password,
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
@@ -0,0 +1,307 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::SaltString;
+use argon2::{
+    Algorithm, Argon2, Params, PasswordHash, PasswordHasher as _, PasswordVerifier, Version,
+};
+use tokio::sync::Semaphore;
+
+mod policy;
+
+pub use policy::{PasswordPolicy, PasswordViolation};
+
+// Parâmetros do Argon2id usados para novos hashes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordConfig {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    // Hashes calculados ao mesmo tempo no pool de threads bloqueantes
+    pub max_concurrent: usize,
+    // Espera máxima por uma vaga antes de recusar a operação
+    pub queue_timeout: Duration,
+}
+
+impl Default for PasswordConfig {
+    // Recomendação da OWASP para Argon2id (19 MiB, 2 iterações, 1 via)
+    fn default() -> Self {
+        Self {
+            memory_kib: 19456,
+            iterations: 2,
+            parallelism: 1,
+            max_concurrent: std::thread::available_parallelism().map_or(4, |n| n.get()),
+            queue_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+// Falha ao calcular ou verificar um hash de senha
+#[derive(Debug)]
+pub enum PasswordError {
+    // Todas as vagas ocupadas durante `queue_timeout`
+    Busy { retry_after_seconds: u64 },
+    Failed(anyhow::Error),
+}
+
+impl fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordError::Busy { .. } => write!(f, "limite de hashes simultâneos atingido"),
+            PasswordError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+// Resultado da verificação de uma senha
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordCheck {
+    Invalid,
+    Valid,
+    // Senha correta, mas o hash é bcrypt ou usa parâmetros diferentes dos atuais
+    ValidNeedsRehash,
+}
+
+impl PasswordCheck {
+    pub fn is_valid(self) -> bool {
+        self != PasswordCheck::Invalid
+    }
+}
+
+// Ponto único de hash e verificação de senhas: novos hashes usam Argon2id; hashes
+// bcrypt (`$2a$`, `$2b$`, `$2y$`) continuam aceitos até serem atualizados no login.
+// O cálculo roda no pool de threads bloqueantes do tokio, limitado por um semáforo,
+// para não travar os workers do actix.
+#[derive(Debug, Clone)]
+pub struct PasswordHasher {
+    config: PasswordConfig,
+    argon2: Argon2<'static>,
+    permits: Arc<Semaphore>,
+}
+
+impl PasswordHasher {
+    pub fn new(config: PasswordConfig) -> Result<Self> {
+        let params = Params::new(
+            config.memory_kib,
+            config.iterations,
+            config.parallelism,
+            None,
+        )
+        .map_err(|e| anyhow!("parâmetros Argon2 inválidos: {}", e))?;
+
+        if config.max_concurrent == 0 {
+            return Err(anyhow!(
+                "o limite de hashes simultâneos deve ser maior que zero"
+            ));
+        }
+
+        Ok(Self {
+            config,
+            argon2: Argon2::new(Algorithm::Argon2id, Version::V0x13, params),
+            permits: Arc::new(Semaphore::new(config.max_concurrent)),
+        })
+    }
+
+    pub fn config(&self) -> PasswordConfig {
+        self.config
+    }
+
+    pub async fn hash(&self, password: &str) -> Result<String, PasswordError> {
+        let password = password.to_string();
+        self.run_blocking(move |hasher| hasher.hash_blocking(&password))
+            .await
+    }
+
+    pub async fn verify(&self, password: &str, hash: &str) -> Result<PasswordCheck, PasswordError> {
+        let password = password.to_string();
+        let hash = hash.to_string();
+        self.run_blocking(move |hasher| hasher.verify_blocking(&password, &hash))
+            .await
+    }
+
+    // Executa `task` no pool bloqueante assim que houver vaga. A vaga acompanha a
+    // tarefa, então só é liberada quando o cálculo termina, mesmo que a requisição
+    // seja cancelada antes.
+    async fn run_blocking<T, F>(&self, task: F) -> Result<T, PasswordError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&PasswordHasher) -> Result<T> + Send + 'static,
+    {
+        let permit = tokio::time::timeout(
+            self.config.queue_timeout,
+            self.permits.clone().acquire_owned(),
+        )
+        .await
+        .map_err(|_| PasswordError::Busy {
+            retry_after_seconds: self.retry_after_seconds(),
+        })?
+        .map_err(|e| PasswordError::Failed(e.into()))?;
+
+        let hasher = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            task(&hasher)
+        })
+        .await
+        .map_err(|e| PasswordError::Failed(e.into()))?
+        .map_err(PasswordError::Failed)
+    }
+
+    // Sugestão de espera enviada no `Retry-After` quando não há vaga
+    fn retry_after_seconds(&self) -> u64 {
+        self.config.queue_timeout.as_secs().max(1)
+    }
+
+    fn hash_blocking(&self, password: &str) -> Result<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        self.argon2
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| anyhow!("falha ao gerar hash da senha: {}", e))
+    }
+
+    fn verify_blocking(&self, password: &str, hash: &str) -> Result<PasswordCheck> {
+        if is_bcrypt(hash) {
+            let valid = bcrypt::verify(password, hash)?;
+            return Ok(if valid {
+                PasswordCheck::ValidNeedsRehash
+            } else {
+                PasswordCheck::Invalid
+            });
+        }
+
+        let parsed =
+            PasswordHash::new(hash).map_err(|e| anyhow!("hash de senha inválido: {}", e))?;
+
+        match self.argon2.verify_password(password.as_bytes(), &parsed) {
+            Ok(()) if self.is_current(&parsed) => Ok(PasswordCheck::Valid),
+            Ok(()) => Ok(PasswordCheck::ValidNeedsRehash),
+            Err(argon2::password_hash::Error::Password) => Ok(PasswordCheck::Invalid),
+            Err(e) => Err(anyhow!("falha ao verificar senha: {}", e)),
+        }
+    }
+
+    // Hash gerado com o algoritmo e os parâmetros configurados atualmente
+    fn is_current(&self, hash: &PasswordHash) -> bool {
+        if hash.algorithm != Algorithm::Argon2id.ident() {
+            return false;
+        }
+
+        match Params::try_from(hash) {
+            Ok(params) => {
+                params.m_cost() == self.config.memory_kib
+                    && params.t_cost() == self.config.iterations
+                    && params.p_cost() == self.config.parallelism
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+fn is_bcrypt(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parâmetros reduzidos para manter os testes rápidos
+    fn hasher(iterations: u32) -> PasswordHasher {
+        PasswordHasher::new(PasswordConfig {
+            memory_kib: 1024,
+            iterations,
+            parallelism: 1,
+            ..PasswordConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_hash_and_verify_argon2id() {
+        let hasher = hasher(1);
+        let hash = hasher.hash_blocking("senha123").unwrap();
+
+        assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+        assert_eq!(
+            hasher.verify_blocking("senha123", &hash).unwrap(),
+            PasswordCheck::Valid
+        );
+        assert_eq!(
+            hasher.verify_blocking("outra", &hash).unwrap(),
+            PasswordCheck::Invalid
+        );
+    }
+
+    #[test]
+    fn test_bcrypt_hash_needs_rehash() {
+        let hasher = hasher(1);
+        let hash = bcrypt::hash("senha123", 4).unwrap();
+
+        assert_eq!(
+            hasher.verify_blocking("senha123", &hash).unwrap(),
+            PasswordCheck::ValidNeedsRehash
+        );
+        assert_eq!(
+            hasher.verify_blocking("outra", &hash).unwrap(),
+            PasswordCheck::Invalid
+        );
+    }
+
+    #[test]
+    fn test_changed_params_need_rehash() {
+        let old_hash = hasher(1).hash_blocking("senha123").unwrap();
+
+        assert_eq!(
+            hasher(2).verify_blocking("senha123", &old_hash).unwrap(),
+            PasswordCheck::ValidNeedsRehash
+        );
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        let config = PasswordConfig {
+            memory_kib: 1,
+            ..PasswordConfig::default()
+        };
+        assert!(PasswordHasher::new(config).is_err());
+
+        let config = PasswordConfig {
+            max_concurrent: 0,
+            ..PasswordConfig::default()
+        };
+        assert!(PasswordHasher::new(config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_busy_when_saturated() {
+        let hasher = PasswordHasher::new(PasswordConfig {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+            max_concurrent: 1,
+            queue_timeout: Duration::from_millis(10),
+        })
+        .unwrap();
+
+        let permit = hasher.permits.clone().acquire_owned().await.unwrap();
+        assert!(matches!(
+            hasher.hash("senha123").await,
+            Err(PasswordError::Busy {
+                retry_after_seconds: 1
+            })
+        ));
+
+        drop(permit);
+        let hash = hasher.hash("senha123").await.unwrap();
+        assert_eq!(
+            hasher.verify("senha123", &hash).await.unwrap(),
+            PasswordCheck::Valid
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, Utc};
+
+use serde::Serialize;
+
+// Tamanho mínimo de uma parte do nome/email para ser considerada na senha
+const MIN_PERSONAL_INFO_LENGTH: usize = 3;
+
+// Regras aplicadas a toda senha nova (cadastro, alteração e redefinição)
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    // Limita o custo do hash para entradas muito longas
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    // Recusa senhas que contenham o nome ou o email do usuário
+    pub forbid_personal_info: bool,
+    // Senhas comuns, em minúsculas
+    pub common_passwords: HashSet<String>,
+    // Senhas recentes (incluindo a atual) que não podem ser reutilizadas; 0 desativa
+    pub history_size: usize,
+    // Validade máxima da senha em dias; 0 desativa
+    pub max_age_days: i64,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 128,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: true,
+            require_symbol: false,
+            forbid_personal_info: true,
+            common_passwords: HashSet::new(),
+            history_size: 5,
+            max_age_days: 0,
+        }
+    }
+}
+
+// Regra da política não atendida pela senha
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PasswordViolation {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl PasswordViolation {
+    pub fn reused(history_size: usize) -> Self {
+        Self::new(
+            "PASSWORD_REUSED",
+            format!(
+                "A senha não pode ser igual a nenhuma das últimas {} senhas",
+                history_size
+            ),
+        )
+    }
+
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    // Lê a lista de senhas comuns: uma por linha, ignorando linhas vazias e `#`
+    pub fn parse_common_passwords(contents: &str) -> HashSet<String> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    // Senha trocada em `changed_at` já passou da validade em `now`
+    pub fn is_expired(&self, changed_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.max_age_days > 0 && now - changed_at > Duration::days(self.max_age_days)
+    }
+
+    // Retorna todas as regras violadas pela senha (vazio quando a senha é aceita)
+    pub fn check(&self, password: &str, nome: &str, email: &str) -> Vec<PasswordViolation> {
+        let mut violations = Vec::new();
+        let length = password.chars().count();
+
+        if length < self.min_length {
+            violations.push(PasswordViolation::new(
+                "TOO_SHORT",
+                format!("A senha deve ter pelo menos {} caracteres", self.min_length),
+            ));
+        }
+
+        if length > self.max_length {
+            violations.push(PasswordViolation::new(
+                "TOO_LONG",
+                format!("A senha deve ter no máximo {} caracteres", self.max_length),
+            ));
+        }
+
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push(PasswordViolation::new(
+                "MISSING_LOWERCASE",
+                "A senha deve conter uma letra minúscula",
+            ));
+        }
+
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push(PasswordViolation::new(
+                "MISSING_UPPERCASE",
+                "A senha deve conter uma letra maiúscula",
+            ));
+        }
+
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PasswordViolation::new(
+                "MISSING_DIGIT",
+                "A senha deve conter um número",
+            ));
+        }
+
+        if self.require_symbol && !password.chars().any(|c| !c.is_alphanumeric()) {
+            violations.push(PasswordViolation::new(
+                "MISSING_SYMBOL",
+                "A senha deve conter um símbolo",
+            ));
+        }
+
+        let lowered = password.to_lowercase();
+
+        if self.forbid_personal_info && contains_personal_info(&lowered, nome, email) {
+            violations.push(PasswordViolation::new(
+                "CONTAINS_PERSONAL_INFO",
+                "A senha não pode conter o nome ou o email do usuário",
+            ));
+        }
+
+        if self.common_passwords.contains(&lowered) {
+            violations.push(PasswordViolation::new(
+                "COMMON_PASSWORD",
+                "A senha é muito comum",
+            ));
+        }
+
+        violations
+    }
+}
+
+// Partes do nome e do email (antes do `@`) com tamanho suficiente para identificar o usuário
+fn contains_personal_info(password: &str, nome: &str, email: &str) -> bool {
+    let local_part = email.split('@').next().unwrap_or_default();
+
+    nome.split_whitespace()
+        .chain(local_part.split(['.', '_', '-', '+']))
+        .chain(std::iter::once(local_part))
+        .map(str::to_lowercase)
+        .filter(|part| part.chars().count() >= MIN_PERSONAL_INFO_LENGTH)
+        .any(|part| password.contains(&part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(violations: Vec<PasswordViolation>) -> Vec<&'static str> {
+        violations.into_iter().map(|v| v.code).collect()
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy = PasswordPolicy::default();
+
+        assert!(policy
+            .check("minhasenha123", "Maria Souza", "maria@exemplo.com")
+            .is_empty());
+        assert_eq!(
+            codes(policy.check("", "Maria Souza", "maria@exemplo.com")),
+            vec!["TOO_SHORT", "MISSING_DIGIT"]
+        );
+        assert_eq!(
+            codes(policy.check(&"a1".repeat(65), "Maria", "maria@exemplo.com")),
+            vec!["TOO_LONG"]
+        );
+    }
+
+    #[test]
+    fn test_character_classes() {
+        let policy = PasswordPolicy {
+            require_lowercase: true,
+            require_uppercase: true,
+            require_symbol: true,
+            ..PasswordPolicy::default()
+        };
+
+        assert_eq!(
+            codes(policy.check("abcdefgh", "Maria", "maria@exemplo.com")),
+            vec!["MISSING_UPPERCASE", "MISSING_DIGIT", "MISSING_SYMBOL"]
+        );
+        assert!(policy
+            .check("Çaminho#2024", "Maria", "maria@exemplo.com")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_personal_info() {
+        let policy = PasswordPolicy::default();
+
+        for password in ["souza2024!", "MARIA1234", "m.silva99x"] {
+            assert_eq!(
+                codes(policy.check(password, "Maria Souza", "m.silva@exemplo.com")),
+                vec!["CONTAINS_PERSONAL_INFO"],
+                "{}",
+                password
+            );
+        }
+
+        // Partes curtas do nome não contam
+        assert!(policy
+            .check("ana-e-li-123", "Li", "li@exemplo.com")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let now = Utc::now();
+        let mut policy = PasswordPolicy::default();
+        assert!(!policy.is_expired(now - Duration::days(3650), now));
+
+        policy.max_age_days = 90;
+        assert!(!policy.is_expired(now - Duration::days(90), now));
+        assert!(policy.is_expired(now - Duration::days(91), now));
+    }
+
+    #[test]
+    fn test_common_passwords() {
+        let policy = PasswordPolicy {
+            common_passwords: PasswordPolicy::parse_common_passwords(
+                "# senhas comuns\n\nSenha123\n  12345678  \n",
+            ),
+            ..PasswordPolicy::default()
+        };
+
+        assert_eq!(policy.common_passwords.len(), 2);
+        assert_eq!(
+            codes(policy.check("SENHA123", "Maria", "maria@exemplo.com")),
+            vec!["COMMON_PASSWORD"]
+        );
+        assert_eq!(
+            codes(policy.check("12345678", "Maria", "maria@exemplo.com")),
+            vec!["COMMON_PASSWORD"]
+        );
+    }
+}
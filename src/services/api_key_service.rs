@@ -0,0 +1,163 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{ApiKey, User, API_KEY_PREFIX};
+use crate::services::refresh_token_service::{generate_token, hash_token};
+
+// Caracteres da chave guardados em claro para identificá-la nas listagens
+const DISPLAY_PREFIX_LENGTH: usize = 8;
+
+// Colunas de `ApiKey`; o hash da chave só é usado na busca
+const API_KEY_COLUMNS: &str =
+    "id, user_id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at, organization_id";
+
+// Resultado da autenticação por chave de API
+#[derive(Debug)]
+pub enum ApiKeyOutcome {
+    Valid {
+        api_key: Box<ApiKey>,
+        user: Box<User>,
+    },
+    NotFound,
+    Expired,
+    Revoked,
+}
+
+// Gera uma nova chave: `pat_` seguido de 256 bits aleatórios em hex
+pub fn generate_key() -> String {
+    format!("{}{}", API_KEY_PREFIX, generate_token())
+}
+
+fn display_prefix(key: &str) -> String {
+    key.chars()
+        .take(API_KEY_PREFIX.len() + DISPLAY_PREFIX_LENGTH)
+        .collect()
+}
+
+// Cria uma chave para o usuário, vinculada à organização informada (se houver).
+// Retorna a chave completa, que não pode ser recuperada depois.
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    organization_id: Option<Uuid>,
+    name: &str,
+    scopes: &[String],
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(String, ApiKey), sqlx::Error> {
+    let key = generate_key();
+
+    let api_key = sqlx::query_as::<_, ApiKey>(&format!(
+        r#"
+        INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at, organization_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {}
+        "#,
+        API_KEY_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(name)
+    .bind(display_prefix(&key))
+    .bind(hash_token(&key))
+    .bind(scopes)
+    .bind(expires_at)
+    .bind(organization_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok((key, api_key))
+}
+
+// Chaves do usuário, incluindo as revogadas e expiradas
+pub async fn list_for_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<ApiKey>, sqlx::Error> {
+    sqlx::query_as::<_, ApiKey>(&format!(
+        "SELECT {} FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC",
+        API_KEY_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+// Revoga uma chave ativa do usuário. Retorna `false` se ela não existe ou já foi revogada.
+pub async fn revoke(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE api_keys SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Revoga todas as chaves ativas do usuário (logout geral, troca de senha e
+// encerramento de sessões)
+pub async fn revoke_all_for_user(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE api_keys SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// Valida a chave apresentada e registra o uso
+pub async fn authenticate(pool: &PgPool, key: &str) -> Result<ApiKeyOutcome, sqlx::Error> {
+    let api_key = sqlx::query_as::<_, ApiKey>(&format!(
+        "SELECT {} FROM api_keys WHERE key_hash = $1",
+        API_KEY_COLUMNS
+    ))
+    .bind(hash_token(key))
+    .fetch_optional(pool)
+    .await?;
+
+    let api_key = match api_key {
+        Some(api_key) => api_key,
+        None => return Ok(ApiKeyOutcome::NotFound),
+    };
+
+    if api_key.revoked_at.is_some() {
+        return Ok(ApiKeyOutcome::Revoked);
+    }
+
+    if api_key.is_expired() {
+        return Ok(ApiKeyOutcome::Expired);
+    }
+
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(api_key.user_id)
+        .fetch_one(pool)
+        .await?;
+
+    // Uso registrado no máximo uma vez por minuto, para não gravar a cada requisição
+    sqlx::query(
+        r#"
+        UPDATE api_keys SET last_used_at = NOW()
+        WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+        "#,
+    )
+    .bind(api_key.id)
+    .execute(pool)
+    .await?;
+
+    Ok(ApiKeyOutcome::Valid {
+        api_key: Box::new(api_key),
+        user: Box::new(user),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_key() {
+        let key = generate_key();
+
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + 64);
+        assert_ne!(key, generate_key());
+        assert_eq!(display_prefix(&key), key[..12]);
+    }
+}
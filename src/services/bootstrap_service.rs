@@ -0,0 +1,215 @@
+use anyhow::{anyhow, bail, Context, Result};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::ROLE_ADMIN;
+use crate::password::{PasswordHasher, PasswordPolicy};
+use crate::services::rbac_service;
+
+// Conta criada pela migração original, com a senha pública `admin123`
+const DEFAULT_ADMIN_ID: Uuid = Uuid::from_u128(1);
+const DEFAULT_ADMIN_PASSWORD: &str = "admin123";
+
+// Chave do advisory lock que serializa o bootstrap entre instâncias iniciando juntas
+const BOOTSTRAP_LOCK_KEY: i64 = 0x6164_6d69_6e00;
+
+// Primeiro administrador, lido de BOOTSTRAP_ADMIN_*
+#[derive(Clone)]
+pub struct BootstrapAdmin {
+    pub nome: String,
+    pub email: String,
+    pub password: String,
+}
+
+// Resultado do bootstrap do primeiro administrador
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootstrapOutcome {
+    Created(Uuid),
+    // Já existe pelo menos um admin; nada foi alterado
+    AdminExists,
+}
+
+// Remove o administrador padrão se ele ainda aceita a senha pública (inclusive depois de
+// o hash ter sido atualizado para Argon2id no login). Retorna se a conta foi removida.
+pub async fn remove_default_admin(pool: &PgPool, hasher: &PasswordHasher) -> Result<bool> {
+    let hash: Option<String> = sqlx::query_scalar("SELECT senha FROM users WHERE id = $1")
+        .bind(DEFAULT_ADMIN_ID)
+        .fetch_optional(pool)
+        .await?;
+
+    let Some(hash) = hash else {
+        return Ok(false);
+    };
+
+    if !accepts_default_password(hasher, &hash).await? {
+        return Ok(false);
+    }
+
+    // Só remove se o hash não mudou desde a verificação
+    let result = sqlx::query("DELETE FROM users WHERE id = $1 AND senha = $2")
+        .bind(DEFAULT_ADMIN_ID)
+        .bind(&hash)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() > 0 {
+        tracing::warn!(
+            user_id = %DEFAULT_ADMIN_ID,
+            "administrador padrão com senha conhecida removido"
+        );
+    }
+
+    Ok(result.rows_affected() > 0)
+}
+
+// O hash (bcrypt da migração original ou Argon2id) ainda aceita a senha pública
+async fn accepts_default_password(hasher: &PasswordHasher, hash: &str) -> Result<bool> {
+    let check = hasher
+        .verify(DEFAULT_ADMIN_PASSWORD, hash)
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+    Ok(check.is_valid())
+}
+
+// Existe pelo menos um usuário com a permissão `users:admin`
+pub async fn admin_exists(pool: &PgPool) -> Result<bool, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    rbac_service::admin_exists(&mut conn).await
+}
+
+// Decide se o administrador configurado deve ser criado. Com um admin existente nada
+// é feito, nem mesmo a validação da configuração; sem admin, a configuração precisa ser
+// válida.
+fn should_create(
+    admin_exists: bool,
+    email_taken: bool,
+    policy: &PasswordPolicy,
+    admin: &BootstrapAdmin,
+) -> Result<bool> {
+    if admin_exists {
+        return Ok(false);
+    }
+
+    if email_taken {
+        bail!(
+            "BOOTSTRAP_ADMIN_EMAIL {} já pertence a um usuário que não é admin",
+            admin.email
+        );
+    }
+
+    let violations = policy.check(&admin.password, &admin.nome, &admin.email);
+    if !violations.is_empty() {
+        let messages: Vec<String> = violations.into_iter().map(|v| v.message).collect();
+        bail!(
+            "Senha em BOOTSTRAP_ADMIN_PASSWORD_FILE recusada pela política: {}",
+            messages.join("; ")
+        );
+    }
+
+    Ok(true)
+}
+
+// Cria o primeiro administrador quando ainda não há nenhum. Idempotente: com um admin
+// existente não altera nada, mesmo que o email configurado seja outro.
+pub async fn bootstrap_admin(
+    pool: &PgPool,
+    hasher: &PasswordHasher,
+    policy: &PasswordPolicy,
+    admin: &BootstrapAdmin,
+) -> Result<BootstrapOutcome> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(BOOTSTRAP_LOCK_KEY)
+        .execute(&mut *tx)
+        .await?;
+
+    let admin_exists = rbac_service::admin_exists(&mut tx).await?;
+    let email_taken: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)")
+            .bind(&admin.email)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    if !should_create(admin_exists, email_taken, policy, admin)? {
+        return Ok(BootstrapOutcome::AdminExists);
+    }
+
+    let senha_hash = hasher
+        .hash(&admin.password)
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+
+    // A senha ficou em um arquivo de configuração: o primeiro login exige a troca
+    let id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO users (nome, email, senha, email_verified_at, password_changed_at, must_change_password)
+        VALUES ($1, $2, $3, NOW(), NOW(), TRUE)
+        RETURNING id
+        "#,
+    )
+    .bind(&admin.nome)
+    .bind(&admin.email)
+    .bind(&senha_hash)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Falha ao criar o administrador inicial")?;
+
+    rbac_service::assign_role(&mut tx, id, ROLE_ADMIN).await?;
+
+    tx.commit().await?;
+
+    tracing::warn!(user_id = %id, email = %admin.email, "administrador inicial criado");
+
+    Ok(BootstrapOutcome::Created(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::password::PasswordConfig;
+
+    fn admin(password: &str) -> BootstrapAdmin {
+        BootstrapAdmin {
+            nome: "Administrador".to_string(),
+            email: "root@empresa.com".to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_should_create() {
+        let policy = PasswordPolicy::default();
+
+        assert!(should_create(false, false, &policy, &admin("Adm!nForte2024")).unwrap());
+
+        // Com um admin existente nada é feito, mesmo com a configuração inválida
+        assert!(!should_create(true, false, &policy, &admin("Adm!nForte2024")).unwrap());
+        assert!(!should_create(true, true, &policy, &admin("curta")).unwrap());
+
+        let error = should_create(false, false, &policy, &admin("curta")).unwrap_err();
+        assert!(error.to_string().contains("recusada pela política"));
+        assert!(should_create(false, true, &policy, &admin("Adm!nForte2024")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_accepts_default_password() {
+        let hasher = PasswordHasher::new(PasswordConfig {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+            ..PasswordConfig::default()
+        })
+        .unwrap();
+
+        // Hash bcrypt da conta criada pela migração original
+        let legacy = "$2b$12$TcWd/dBbtG16Q7LXDrjKdubheLLzdoHT/Z55SKko/1vOcus8BYzDy";
+        assert!(accepts_default_password(&hasher, legacy).await.unwrap());
+
+        let rehashed = hasher.hash(DEFAULT_ADMIN_PASSWORD).await.unwrap();
+        assert!(accepts_default_password(&hasher, &rehashed).await.unwrap());
+
+        let changed = hasher.hash("Adm!nForte2024").await.unwrap();
+        assert!(!accepts_default_password(&hasher, &changed).await.unwrap());
+    }
+}
@@ -0,0 +1,113 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::mailer::{self, Locale, Mailer, MessageKind};
+use crate::models::{EmailVerificationConfig, EmailVerificationToken, User};
+use crate::services::refresh_token_service::{generate_token, hash_token};
+
+// Resultado da confirmação de email
+#[derive(Debug)]
+pub enum VerifyOutcome {
+    Verified,
+    // Token desconhecido ou já utilizado
+    Invalid,
+    Expired,
+}
+
+// Emite um token de verificação para o usuário, invalidando os anteriores
+pub async fn issue(
+    pool: &PgPool,
+    user_id: Uuid,
+    expires_in_seconds: i64,
+) -> Result<String, sqlx::Error> {
+    let token = generate_token();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "UPDATE email_verification_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO email_verification_tokens (id, user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(hash_token(&token))
+    .bind(Utc::now() + Duration::seconds(expires_in_seconds))
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(token)
+}
+
+// Consome o token e marca o email do usuário como verificado
+pub async fn verify(pool: &PgPool, token: &str) -> Result<VerifyOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let current = sqlx::query_as::<_, EmailVerificationToken>(
+        "SELECT * FROM email_verification_tokens WHERE token_hash = $1 FOR UPDATE",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let current = match current {
+        Some(current) if current.used_at.is_none() => current,
+        _ => return Ok(VerifyOutcome::Invalid),
+    };
+
+    if current.is_expired() {
+        return Ok(VerifyOutcome::Expired);
+    }
+
+    sqlx::query("DELETE FROM email_verification_tokens WHERE user_id = $1")
+        .bind(current.user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        "UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1",
+    )
+    .bind(current.user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(VerifyOutcome::Verified)
+}
+
+// Emite um novo token e envia o email de verificação
+pub async fn send_verification_email(
+    pool: &PgPool,
+    mailer: &dyn Mailer,
+    config: &EmailVerificationConfig,
+    user: &User,
+    locale: Locale,
+) -> anyhow::Result<()> {
+    let token = issue(pool, user.id, config.token_ttl_seconds).await?;
+    let action = config.verify_link(&token).unwrap_or(token);
+    let expires_in = locale.format_duration(config.token_ttl_seconds);
+
+    mailer
+        .send(mailer::render(
+            MessageKind::EmailVerification,
+            locale,
+            &user.email,
+            &[
+                ("nome", &user.nome),
+                ("action", &action),
+                ("expires_in", &expires_in),
+            ],
+        ))
+        .await
+}
@@ -0,0 +1,210 @@
+use chrono::{Duration, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::{InvitationStatus, OrganizationInvitation, OrganizationRole};
+use crate::services::organization_service::OrganizationError;
+use crate::services::refresh_token_service::{generate_token, hash_token};
+
+// Colunas de `OrganizationInvitation`; o hash do token só é usado na busca
+const INVITATION_COLUMNS: &str = "id, organization_id, email, role, invited_by, expires_at, \
+     accepted_at, accepted_by, revoked_at, created_at";
+
+// Resultado da reserva de um convite para aceite
+#[derive(Debug)]
+pub enum ClaimOutcome {
+    Pending(OrganizationInvitation),
+    // Token desconhecido, já aceito ou revogado
+    Invalid,
+    Expired,
+}
+
+// Emite um convite para o email, substituindo convites pendentes para o mesmo
+// email na organização. Retorna o convite e o token em claro, que só vai no email.
+pub async fn create(
+    pool: &PgPool,
+    organization_id: Uuid,
+    invited_by: Uuid,
+    email: &str,
+    role: OrganizationRole,
+    expires_in_seconds: i64,
+) -> Result<(OrganizationInvitation, String), OrganizationError> {
+    let already_member = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM organization_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.organization_id = $1 AND u.email = $2
+        )
+        "#,
+    )
+    .bind(organization_id)
+    .bind(email)
+    .fetch_one(pool)
+    .await?;
+
+    if already_member {
+        return Err(OrganizationError::AlreadyMember);
+    }
+
+    let token = generate_token();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE organization_invitations SET revoked_at = NOW()
+        WHERE organization_id = $1 AND email = $2
+          AND accepted_at IS NULL AND revoked_at IS NULL
+        "#,
+    )
+    .bind(organization_id)
+    .bind(email)
+    .execute(&mut *tx)
+    .await?;
+
+    let invitation = sqlx::query_as::<_, OrganizationInvitation>(&format!(
+        r#"
+        INSERT INTO organization_invitations
+            (organization_id, email, role, token_hash, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {}
+        "#,
+        INVITATION_COLUMNS
+    ))
+    .bind(organization_id)
+    .bind(email)
+    .bind(role)
+    .bind(hash_token(&token))
+    .bind(invited_by)
+    .bind(Utc::now() + Duration::seconds(expires_in_seconds))
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((invitation, token))
+}
+
+// Convites da organização, do mais recente para o mais antigo
+pub async fn list(
+    pool: &PgPool,
+    organization_id: Uuid,
+) -> Result<Vec<OrganizationInvitation>, sqlx::Error> {
+    sqlx::query_as::<_, OrganizationInvitation>(&format!(
+        "SELECT {} FROM organization_invitations WHERE organization_id = $1 ORDER BY created_at DESC",
+        INVITATION_COLUMNS
+    ))
+    .bind(organization_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn find(
+    pool: &PgPool,
+    organization_id: Uuid,
+    invitation_id: Uuid,
+) -> Result<Option<OrganizationInvitation>, sqlx::Error> {
+    sqlx::query_as::<_, OrganizationInvitation>(&format!(
+        "SELECT {} FROM organization_invitations WHERE id = $1 AND organization_id = $2",
+        INVITATION_COLUMNS
+    ))
+    .bind(invitation_id)
+    .bind(organization_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Revoga um convite ainda não aceito. Retorna `false` se ele não está pendente.
+pub async fn revoke(
+    pool: &PgPool,
+    organization_id: Uuid,
+    invitation_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE organization_invitations SET revoked_at = NOW()
+        WHERE id = $1 AND organization_id = $2
+          AND accepted_at IS NULL AND revoked_at IS NULL
+        "#,
+    )
+    .bind(invitation_id)
+    .bind(organization_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Convite correspondente ao token, em qualquer situação
+pub async fn find_by_token(
+    pool: &PgPool,
+    token: &str,
+) -> Result<Option<OrganizationInvitation>, sqlx::Error> {
+    sqlx::query_as::<_, OrganizationInvitation>(&format!(
+        "SELECT {} FROM organization_invitations WHERE token_hash = $1",
+        INVITATION_COLUMNS
+    ))
+    .bind(hash_token(token))
+    .fetch_optional(pool)
+    .await
+}
+
+// Bloqueia o convite até o fim da transação do chamador, para que o mesmo
+// token não seja aceito duas vezes
+pub async fn claim(conn: &mut PgConnection, token: &str) -> Result<ClaimOutcome, sqlx::Error> {
+    let invitation = sqlx::query_as::<_, OrganizationInvitation>(&format!(
+        "SELECT {} FROM organization_invitations WHERE token_hash = $1 FOR UPDATE",
+        INVITATION_COLUMNS
+    ))
+    .bind(hash_token(token))
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(match invitation {
+        Some(invitation) => match invitation.status() {
+            InvitationStatus::Pending => ClaimOutcome::Pending(invitation),
+            InvitationStatus::Expired => ClaimOutcome::Expired,
+            InvitationStatus::Accepted | InvitationStatus::Revoked => ClaimOutcome::Invalid,
+        },
+        None => ClaimOutcome::Invalid,
+    })
+}
+
+// Vincula o usuário com o papel do convite e o marca como aceito. Retorna
+// `false` se o usuário já participava da organização (o convite é consumido).
+pub async fn accept(
+    conn: &mut PgConnection,
+    invitation: &OrganizationInvitation,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO organization_members (organization_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(invitation.organization_id)
+    .bind(user_id)
+    .bind(invitation.role)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "UPDATE organization_invitations SET accepted_at = NOW(), accepted_by = $1 WHERE id = $2",
+    )
+    .bind(user_id)
+    .bind(invitation.id)
+    .execute(&mut *conn)
+    .await?;
+
+    // O convite chegou ao email, que passa a contar como verificado
+    sqlx::query(
+        "UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1",
+    )
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(inserted.rows_affected() > 0)
+}
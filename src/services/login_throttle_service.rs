@@ -0,0 +1,127 @@
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::models::{LoginBlock, LoginThrottle, LoginThrottleConfig, ThrottleScope};
+
+// Normaliza o email usado como identificador da conta
+fn account_key(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+fn keys(email: &str, ip: Option<&str>) -> Vec<(ThrottleScope, String)> {
+    let mut keys = vec![(ThrottleScope::Account, account_key(email))];
+    if let Some(ip) = ip {
+        keys.push((ThrottleScope::Ip, ip.to_string()));
+    }
+    keys
+}
+
+fn stronger(a: LoginBlock, b: LoginBlock) -> LoginBlock {
+    match (a, b) {
+        (LoginBlock::Locked { .. }, LoginBlock::Backoff { .. }) => a,
+        (LoginBlock::Backoff { .. }, LoginBlock::Locked { .. }) => b,
+        _ if b.until() > a.until() => b,
+        _ => a,
+    }
+}
+
+// Verifica se a tentativa de login deve ser recusada. Havendo restrição na conta
+// e no IP, o bloqueio prevalece sobre o atraso e, entre iguais, o mais longo.
+pub async fn check(
+    pool: &PgPool,
+    config: &LoginThrottleConfig,
+    email: &str,
+    ip: Option<&str>,
+) -> Result<Option<LoginBlock>, sqlx::Error> {
+    let mut block: Option<LoginBlock> = None;
+    let now = Utc::now();
+
+    for (scope, identifier) in keys(email, ip) {
+        let throttle = sqlx::query_as::<_, LoginThrottle>(
+            "SELECT * FROM login_throttles WHERE scope = $1 AND identifier = $2",
+        )
+        .bind(scope.as_str())
+        .bind(&identifier)
+        .fetch_optional(pool)
+        .await?;
+
+        let current = throttle.and_then(|throttle| throttle.block(config, now));
+        block = match (block, current) {
+            (Some(existing), Some(current)) => Some(stronger(existing, current)),
+            (existing, current) => existing.or(current),
+        };
+    }
+
+    Ok(block)
+}
+
+// Registra uma falha de login para a conta e o IP, bloqueando-os ao atingir o limite.
+// Contagem e bloqueio são feitos num único upsert, para que tentativas simultâneas
+// não se percam.
+pub async fn record_failure(
+    pool: &PgPool,
+    config: &LoginThrottleConfig,
+    email: &str,
+    ip: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    for (scope, identifier) in keys(email, ip) {
+        // A contagem recomeça após um bloqueio cumprido ou um período sem falhas; o
+        // bloqueio começa quando a nova contagem atinge o limite, e um bloqueio em
+        // andamento é mantido
+        sqlx::query(
+            r#"
+            INSERT INTO login_throttles (scope, identifier, failed_count, locked_until, last_failed_at)
+            VALUES (
+                $1, $2, 1,
+                CASE WHEN 1 >= $4 THEN NOW() + make_interval(secs => $5) END,
+                NOW()
+            )
+            ON CONFLICT (scope, identifier) DO UPDATE SET
+                failed_count = CASE
+                    WHEN login_throttles.locked_until <= NOW()
+                        OR login_throttles.last_failed_at < NOW() - make_interval(secs => $3)
+                    THEN 1
+                    ELSE login_throttles.failed_count + 1
+                END,
+                locked_until = CASE
+                    WHEN login_throttles.locked_until > NOW() THEN login_throttles.locked_until
+                    WHEN (
+                        CASE
+                            WHEN login_throttles.locked_until <= NOW()
+                                OR login_throttles.last_failed_at < NOW() - make_interval(secs => $3)
+                            THEN 1
+                            ELSE login_throttles.failed_count + 1
+                        END
+                    ) >= $4 THEN NOW() + make_interval(secs => $5)
+                END,
+                last_failed_at = NOW()
+            "#,
+        )
+        .bind(scope.as_str())
+        .bind(&identifier)
+        .bind(config.reset_after_seconds as f64)
+        .bind(config.lock_threshold(scope))
+        .bind(config.lock_seconds as f64)
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Login bem-sucedido zera as falhas da conta. As do IP são mantidas, para que
+// uma conta válida não sirva para liberar tentativas contra outras contas.
+pub async fn record_success(pool: &PgPool, email: &str) -> Result<(), sqlx::Error> {
+    unlock(pool, email).await.map(|_| ())
+}
+
+// Remove bloqueio e falhas registradas da conta. Retorna se havia registro.
+pub async fn unlock(pool: &PgPool, email: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM login_throttles WHERE scope = $1 AND identifier = $2")
+        .bind(ThrottleScope::Account.as_str())
+        .bind(account_key(email))
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,14 @@
+pub mod api_key_service;
+pub mod bootstrap_service;
+pub mod email_verification_service;
+pub mod invitation_service;
+pub mod login_throttle_service;
+pub mod oauth_service;
+pub mod oidc_service;
+pub mod organization_service;
+pub mod password_history_service;
+pub mod password_reset_service;
+pub mod rbac_service;
+pub mod refresh_token_service;
+pub mod revocation_service;
+pub mod two_factor_service;
@@ -0,0 +1,381 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use uuid::Uuid;
+
+use crate::models::{OAuthAuthorizationCode, OAuthClient, OAuthClientType, OAUTH_CLIENT_ID_PREFIX};
+use crate::services::oidc_service::code_challenge;
+use crate::services::refresh_token_service::{generate_token, hash_token};
+
+// Acima deste tamanho, entradas vencidas são descartadas a cada inserção
+const CACHE_PRUNE_THRESHOLD: usize = 10_000;
+
+// Dados validados de um novo cliente
+#[derive(Debug)]
+pub struct NewClient {
+    pub name: String,
+    pub client_type: OAuthClientType,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    pub user_id: Option<Uuid>,
+}
+
+// Autorização concedida pelo usuário, guardada até a troca do código
+#[derive(Debug)]
+pub struct AuthorizationGrant {
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    pub code_challenge: String,
+}
+
+// Resultado da troca de um código de autorização
+#[derive(Debug)]
+pub enum CodeOutcome {
+    Valid(Box<OAuthAuthorizationCode>),
+    Invalid,
+    Expired,
+}
+
+// `cli_` seguido de 128 bits aleatórios em hex
+pub fn generate_client_id() -> String {
+    format!("{}{}", OAUTH_CLIENT_ID_PREFIX, &generate_token()[..32])
+}
+
+// Registra o cliente. Clientes confidenciais recebem um client_secret, retornado
+// apenas aqui; o banco guarda somente o hash.
+pub async fn create_client(
+    pool: &PgPool,
+    client: &NewClient,
+    created_by: Uuid,
+) -> Result<(Option<String>, OAuthClient), sqlx::Error> {
+    let client_secret = (client.client_type == OAuthClientType::Confidential).then(generate_token);
+
+    let created = sqlx::query_as::<_, OAuthClient>(
+        r#"
+        INSERT INTO oauth_clients
+            (client_id, name, client_type, secret_hash, redirect_uris, grant_types, scopes, user_id, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(generate_client_id())
+    .bind(&client.name)
+    .bind(client.client_type)
+    .bind(client_secret.as_deref().map(hash_token))
+    .bind(&client.redirect_uris)
+    .bind(&client.grant_types)
+    .bind(&client.scopes)
+    .bind(client.user_id)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await?;
+
+    Ok((client_secret, created))
+}
+
+// Clientes registrados, incluindo os revogados
+pub async fn list_clients(pool: &PgPool) -> Result<Vec<OAuthClient>, sqlx::Error> {
+    sqlx::query_as::<_, OAuthClient>("SELECT * FROM oauth_clients ORDER BY created_at DESC")
+        .fetch_all(pool)
+        .await
+}
+
+// Revoga o cliente: códigos pendentes são descartados e tokens já emitidos passam
+// a ser recusados. Retorna o `client_id` público, ou `None` se ele não existe ou já
+// foi revogado.
+pub async fn revoke_client(pool: &PgPool, id: Uuid) -> Result<Option<String>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let client_id = sqlx::query_scalar::<_, String>(
+        r#"
+        UPDATE oauth_clients SET revoked_at = NOW()
+        WHERE id = $1 AND revoked_at IS NULL
+        RETURNING client_id
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM oauth_authorization_codes WHERE client_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(client_id)
+}
+
+// Cliente ativo pelo `client_id` público
+pub async fn find_active_client(
+    pool: &PgPool,
+    client_id: &str,
+) -> Result<Option<OAuthClient>, sqlx::Error> {
+    sqlx::query_as::<_, OAuthClient>(
+        "SELECT * FROM oauth_clients WHERE client_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(client_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Tokens com a claim `client_id` só valem enquanto o cliente estiver ativo
+// (ver `OAuthClientStore`)
+pub async fn client_active(pool: &PgPool, client_id: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM oauth_clients WHERE client_id = $1 AND revoked_at IS NULL)",
+    )
+    .bind(client_id)
+    .fetch_one(pool)
+    .await
+}
+
+// Confidenciais apresentam o client_secret; públicos não têm segredo a apresentar
+pub fn verify_client_secret(client: &OAuthClient, client_secret: Option<&str>) -> bool {
+    match (&client.secret_hash, client_secret) {
+        (Some(secret_hash), Some(client_secret)) => hash_token(client_secret) == *secret_hash,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+// O verificador apresentado na troca corresponde ao desafio S256 da autorização
+pub fn verify_code_verifier(code_verifier: &str, challenge: &str) -> bool {
+    code_challenge(code_verifier) == challenge
+}
+
+// Gera o código de autorização entregue ao `redirect_uri`
+pub async fn create_code(
+    pool: &PgPool,
+    client: &OAuthClient,
+    user_id: Uuid,
+    organization_id: Option<Uuid>,
+    grant: &AuthorizationGrant,
+    ttl_seconds: i64,
+) -> Result<String, sqlx::Error> {
+    // Códigos não trocados são descartados aqui, sem uma rotina de limpeza separada
+    sqlx::query("DELETE FROM oauth_authorization_codes WHERE expires_at < NOW()")
+        .execute(pool)
+        .await?;
+
+    let code = generate_token();
+
+    sqlx::query(
+        r#"
+        INSERT INTO oauth_authorization_codes
+            (code_hash, client_id, user_id, organization_id, redirect_uri, scopes, code_challenge, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(hash_token(&code))
+    .bind(client.id)
+    .bind(user_id)
+    .bind(organization_id)
+    .bind(&grant.redirect_uri)
+    .bind(&grant.scopes)
+    .bind(&grant.code_challenge)
+    .bind(Utc::now() + Duration::seconds(ttl_seconds))
+    .execute(pool)
+    .await?;
+
+    Ok(code)
+}
+
+// Consome o código emitido para o cliente: cada código só pode ser trocado uma vez,
+// mesmo que a troca falhe. Códigos de outros clientes não são afetados.
+pub async fn consume_code(
+    pool: &PgPool,
+    client: &OAuthClient,
+    code: &str,
+) -> Result<CodeOutcome, sqlx::Error> {
+    let row = sqlx::query_as::<_, OAuthAuthorizationCode>(
+        r#"
+        DELETE FROM oauth_authorization_codes
+        WHERE code_hash = $1 AND client_id = $2
+        RETURNING user_id, organization_id, redirect_uri, scopes, code_challenge, expires_at
+        "#,
+    )
+    .bind(hash_token(code))
+    .bind(client.id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match row {
+        Some(code) if Utc::now() > code.expires_at => CodeOutcome::Expired,
+        Some(code) => CodeOutcome::Valid(Box::new(code)),
+        None => CodeOutcome::Invalid,
+    })
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    active: bool,
+    loaded_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct ClientCache {
+    clients: HashMap<String, CacheEntry>,
+    // Incrementada a cada invalidação: consultas iniciadas antes dela não são guardadas
+    generation: u64,
+}
+
+impl ClientCache {
+    fn get(&self, client_id: &str, ttl: std::time::Duration) -> Option<bool> {
+        self.clients
+            .get(client_id)
+            .filter(|entry| entry.loaded_at.elapsed() < ttl)
+            .map(|entry| entry.active)
+    }
+
+    fn insert(&mut self, client_id: &str, active: bool, generation: u64, ttl: std::time::Duration) {
+        if generation != self.generation {
+            return;
+        }
+        if self.clients.len() >= CACHE_PRUNE_THRESHOLD {
+            self.clients
+                .retain(|_, entry| entry.loaded_at.elapsed() < ttl);
+        }
+        self.clients.insert(
+            client_id.to_string(),
+            CacheEntry {
+                active,
+                loaded_at: Instant::now(),
+            },
+        );
+    }
+
+    fn invalidate_client(&mut self, client_id: &str) {
+        self.clients.remove(client_id);
+        self.generation += 1;
+    }
+}
+
+// Situação dos clientes OAuth com cache em memória, consultada a cada requisição
+// autenticada por token OAuth. Revogações feitas nesta instância valem
+// imediatamente; as feitas por outras instâncias, após no máximo `cache_ttl`.
+#[derive(Clone)]
+pub struct OAuthClientStore {
+    pool: PgPool,
+    cache: Arc<Mutex<ClientCache>>,
+    cache_ttl: std::time::Duration,
+}
+
+impl OAuthClientStore {
+    pub fn new(pool: PgPool, cache_ttl: std::time::Duration) -> Self {
+        Self {
+            pool,
+            cache: Arc::new(Mutex::new(ClientCache::default())),
+            cache_ttl,
+        }
+    }
+
+    pub async fn is_active(&self, client_id: &str) -> Result<bool, sqlx::Error> {
+        let generation = {
+            let cache = self.cache.lock().unwrap();
+            if let Some(active) = cache.get(client_id, self.cache_ttl) {
+                return Ok(active);
+            }
+            cache.generation
+        };
+
+        let active = client_active(&self.pool, client_id).await?;
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(client_id, active, generation, self.cache_ttl);
+
+        Ok(active)
+    }
+
+    // Deve ser chamado após revogar o cliente
+    pub fn invalidate_client(&self, client_id: &str) {
+        self.cache.lock().unwrap().invalidate_client(client_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GRANT_CLIENT_CREDENTIALS;
+
+    fn client(secret: Option<&str>) -> OAuthClient {
+        OAuthClient {
+            id: Uuid::new_v4(),
+            client_id: generate_client_id(),
+            name: "Parceiro".to_string(),
+            client_type: if secret.is_some() {
+                OAuthClientType::Confidential
+            } else {
+                OAuthClientType::Public
+            },
+            secret_hash: secret.map(hash_token),
+            redirect_uris: Vec::new(),
+            grant_types: vec![GRANT_CLIENT_CREDENTIALS.to_string()],
+            scopes: Vec::new(),
+            user_id: None,
+            created_by: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_generate_client_id() {
+        let client_id = generate_client_id();
+
+        assert!(client_id.starts_with(OAUTH_CLIENT_ID_PREFIX));
+        assert_eq!(client_id.len(), OAUTH_CLIENT_ID_PREFIX.len() + 32);
+        assert_ne!(client_id, generate_client_id());
+    }
+
+    #[test]
+    fn test_verify_client_secret() {
+        let confidential = client(Some("segredo"));
+        assert!(verify_client_secret(&confidential, Some("segredo")));
+        assert!(!verify_client_secret(&confidential, Some("outro")));
+        assert!(!verify_client_secret(&confidential, None));
+
+        let public = client(None);
+        assert!(verify_client_secret(&public, None));
+        assert!(!verify_client_secret(&public, Some("segredo")));
+    }
+
+    #[test]
+    fn test_client_cache() {
+        let ttl = std::time::Duration::from_secs(60);
+        let mut cache = ClientCache::default();
+
+        cache.insert("cli_a", true, cache.generation, ttl);
+        assert_eq!(cache.get("cli_a", ttl), Some(true));
+        assert_eq!(cache.get("cli_a", std::time::Duration::from_secs(0)), None);
+
+        cache.invalidate_client("cli_a");
+        assert_eq!(cache.get("cli_a", ttl), None);
+
+        // Consulta iniciada antes da revogação não repõe a situação antiga
+        let generation = cache.generation;
+        cache.invalidate_client("cli_a");
+        cache.insert("cli_a", true, generation, ttl);
+        assert_eq!(cache.get("cli_a", ttl), None);
+    }
+
+    #[test]
+    fn test_verify_code_verifier() {
+        let challenge = "zXV0fA6b-N3ACQUwQ5sP1AmXxMrR3kqdhBOyI3xtwcs";
+
+        assert!(verify_code_verifier(
+            "dBjftJeZ4CVP-mB92K1uhbF2J8dR2X5ARxI6hoR1mWk",
+            challenge
+        ));
+        assert!(!verify_code_verifier(
+            "dBjftJeZ4CVP-mB92K1uhbF2J8dR2X5ARxI6hoR1mWX",
+            challenge
+        ));
+    }
+}
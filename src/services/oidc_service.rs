@@ -0,0 +1,714 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration as StdDuration, Instant};
+use url::Url;
+use uuid::Uuid;
+
+use crate::models::{IdTokenClaims, OidcConfig, User};
+use crate::services::refresh_token_service::{generate_token, hash_token};
+
+// Metadados (discovery) e chaves do provedor são buscados novamente após este intervalo
+const PROVIDER_CACHE_TTL: StdDuration = StdDuration::from_secs(3600);
+// Intervalo mínimo entre recargas das chaves por causa de um `kid` desconhecido
+const PROVIDER_MIN_REFRESH: StdDuration = StdDuration::from_secs(30);
+const HTTP_TIMEOUT: StdDuration = StdDuration::from_secs(10);
+// Tolerância de relógio na validação do ID token
+const ID_TOKEN_LEEWAY_SECONDS: u64 = 60;
+
+#[derive(Debug)]
+pub enum OidcError {
+    // Falha de comunicação ou resposta inesperada do provedor
+    Provider(String),
+    // Código de autorização recusado pelo provedor (expirado, já utilizado...)
+    CodeRejected(String),
+    // Assinatura, issuer, audiência, expiração ou nonce do ID token inválidos
+    InvalidIdToken(String),
+}
+
+impl From<reqwest::Error> for OidcError {
+    fn from(error: reqwest::Error) -> Self {
+        OidcError::Provider(error.to_string())
+    }
+}
+
+// Resultado da leitura do `state` devolvido pelo provedor
+#[derive(Debug)]
+pub enum StateOutcome {
+    Valid {
+        nonce: String,
+        code_verifier: String,
+    },
+    // State desconhecido ou já utilizado
+    Invalid,
+    Expired,
+}
+
+// Login iniciado: valores guardados no banco até o retorno do provedor
+#[derive(Debug)]
+pub struct PendingLogin {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+    #[serde(default)]
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+struct Provider {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+// Cliente do provedor OIDC, com metadados e chaves públicas em cache
+#[derive(Clone)]
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    provider: Arc<RwLock<Option<Arc<Provider>>>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Result<Self, OidcError> {
+        let http = reqwest::Client::builder().timeout(HTTP_TIMEOUT).build()?;
+
+        Ok(Self {
+            config,
+            http,
+            provider: Arc::new(RwLock::new(None)),
+        })
+    }
+
+    pub fn config(&self) -> &OidcConfig {
+        &self.config
+    }
+
+    // URL de autorização do provedor para o login iniciado, com o desafio PKCE (S256)
+    pub async fn authorization_url(&self, login: &PendingLogin) -> Result<String, OidcError> {
+        let provider = self.provider(false).await?;
+        let mut url = Url::parse(&provider.metadata.authorization_endpoint)
+            .map_err(|e| OidcError::Provider(format!("authorization_endpoint inválido: {}", e)))?;
+
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_uri)
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", &login.state)
+            .append_pair("nonce", &login.nonce)
+            .append_pair("code_challenge", &code_challenge(&login.code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    // Troca o código de autorização pelo ID token no token endpoint
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<String, OidcError> {
+        let provider = self.provider(false).await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+
+        let mut request = self.http.post(&provider.metadata.token_endpoint);
+        match &self.config.client_secret {
+            // `client_secret_basic` é o padrão da especificação; `client_secret_post`
+            // apenas quando é o único método anunciado pelo provedor
+            Some(secret) if uses_client_secret_post(&provider.metadata) => {
+                form.push(("client_id", self.config.client_id.as_str()));
+                form.push(("client_secret", secret.as_str()));
+            }
+            Some(secret) => {
+                request = request.basic_auth(
+                    form_encode(&self.config.client_id),
+                    Some(form_encode(secret)),
+                );
+            }
+            None => form.push(("client_id", self.config.client_id.as_str())),
+        }
+
+        let response = request.form(&form).send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            return response
+                .json::<TokenResponse>()
+                .await?
+                .id_token
+                .ok_or_else(|| {
+                    OidcError::Provider("resposta do token endpoint sem id_token".into())
+                });
+        }
+
+        match response.json::<TokenErrorResponse>().await {
+            Ok(error) if error.error == "invalid_grant" => Err(OidcError::CodeRejected(
+                error.error_description.unwrap_or(error.error),
+            )),
+            Ok(error) => Err(OidcError::Provider(format!(
+                "token endpoint respondeu {}: {}",
+                status,
+                error.error_description.unwrap_or(error.error)
+            ))),
+            Err(_) => Err(OidcError::Provider(format!(
+                "token endpoint respondeu {}",
+                status
+            ))),
+        }
+    }
+
+    // Valida assinatura, issuer, audiência, expiração e nonce do ID token
+    pub async fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let header = jsonwebtoken::decode_header(id_token)
+            .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+        // Chaves simétricas exigiriam o segredo do cliente como chave; apenas
+        // assinaturas com as chaves públicas do provedor são aceitas
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(OidcError::InvalidIdToken(format!(
+                "algoritmo {:?} não aceito",
+                header.alg
+            )));
+        }
+
+        let mut provider = self.provider(false).await?;
+        let mut jwk = find_jwk(&provider.jwks, header.kid.as_deref(), header.alg).cloned();
+
+        // Chave nova após uma rotação no provedor
+        if jwk.is_none() && provider.fetched_at.elapsed() >= PROVIDER_MIN_REFRESH {
+            provider = self.provider(true).await?;
+            jwk = find_jwk(&provider.jwks, header.kid.as_deref(), header.alg).cloned();
+        }
+
+        let jwk = jwk.ok_or_else(|| {
+            OidcError::InvalidIdToken("chave de assinatura desconhecida".to_string())
+        })?;
+        let key =
+            DecodingKey::from_jwk(&jwk).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = ID_TOKEN_LEEWAY_SECONDS;
+
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidIdToken("nonce não confere".to_string()));
+        }
+
+        if claims
+            .azp
+            .as_deref()
+            .is_some_and(|azp| azp != self.config.client_id)
+        {
+            return Err(OidcError::InvalidIdToken(
+                "token emitido para outro cliente (azp)".to_string(),
+            ));
+        }
+
+        Ok(claims)
+    }
+
+    async fn provider(&self, refresh: bool) -> Result<Arc<Provider>, OidcError> {
+        if !refresh {
+            let cached = self
+                .provider
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone();
+            if let Some(provider) = cached {
+                if provider.fetched_at.elapsed() < PROVIDER_CACHE_TTL {
+                    return Ok(provider);
+                }
+            }
+        }
+
+        let provider = Arc::new(self.fetch_provider().await?);
+        *self.provider.write().unwrap_or_else(|e| e.into_inner()) = Some(provider.clone());
+        Ok(provider)
+    }
+
+    async fn fetch_provider(&self) -> Result<Provider, OidcError> {
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            self.config.issuer.trim_end_matches('/')
+        );
+
+        let metadata = self
+            .http
+            .get(&discovery_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ProviderMetadata>()
+            .await?;
+
+        // Impede que um documento de discovery redirecione o login para outro provedor
+        if metadata.issuer != self.config.issuer {
+            return Err(OidcError::Provider(format!(
+                "issuer do discovery ({}) difere de OIDC_ISSUER",
+                metadata.issuer
+            )));
+        }
+
+        let jwks = self
+            .http
+            .get(&metadata.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+
+        Ok(Provider {
+            metadata,
+            jwks,
+            fetched_at: Instant::now(),
+        })
+    }
+}
+
+// Desafio PKCE S256: BASE64URL(SHA256(code_verifier))
+pub fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+fn uses_client_secret_post(metadata: &ProviderMetadata) -> bool {
+    let methods = &metadata.token_endpoint_auth_methods_supported;
+    methods.iter().any(|method| method == "client_secret_post")
+        && !methods.iter().any(|method| method == "client_secret_basic")
+}
+
+// Credenciais do `client_secret_basic` são codificadas como formulário (RFC 6749, 2.3.1)
+fn form_encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+// Chave pelo `kid` do header; sem `kid`, a única chave compatível com o algoritmo
+fn find_jwk<'a>(jwks: &'a JwkSet, kid: Option<&str>, algorithm: Algorithm) -> Option<&'a Jwk> {
+    match kid {
+        Some(kid) => jwks.find(kid),
+        None => {
+            let mut candidates = jwks.keys.iter().filter(|jwk| {
+                jwk.common
+                    .key_algorithm
+                    .is_none_or(|alg| alg.to_string() == format!("{:?}", algorithm))
+            });
+            match (candidates.next(), candidates.next()) {
+                (Some(jwk), None) => Some(jwk),
+                _ => None,
+            }
+        }
+    }
+}
+
+// Registra um novo login com state, nonce e verificador PKCE aleatórios
+pub async fn start_login(pool: &PgPool, ttl_seconds: i64) -> Result<PendingLogin, sqlx::Error> {
+    // Logins abandonados são descartados aqui, sem uma rotina de limpeza separada
+    sqlx::query("DELETE FROM oidc_login_states WHERE expires_at < NOW()")
+        .execute(pool)
+        .await?;
+
+    let login = PendingLogin {
+        state: generate_token(),
+        nonce: generate_token(),
+        code_verifier: generate_token(),
+        expires_at: Utc::now() + Duration::seconds(ttl_seconds),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO oidc_login_states (state_hash, nonce, code_verifier, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(hash_token(&login.state))
+    .bind(&login.nonce)
+    .bind(&login.code_verifier)
+    .bind(login.expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(login)
+}
+
+// Consome o state: cada login só pode ser concluído uma vez
+pub async fn consume_state(pool: &PgPool, state: &str) -> Result<StateOutcome, sqlx::Error> {
+    let row = sqlx::query_as::<_, (String, String, DateTime<Utc>)>(
+        "DELETE FROM oidc_login_states WHERE state_hash = $1 RETURNING nonce, code_verifier, expires_at",
+    )
+    .bind(hash_token(state))
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match row {
+        Some((_, _, expires_at)) if Utc::now() > expires_at => StateOutcome::Expired,
+        Some((nonce, code_verifier, _)) => StateOutcome::Valid {
+            nonce,
+            code_verifier,
+        },
+        None => StateOutcome::Invalid,
+    })
+}
+
+// Usuário vinculado à identidade externa, registrando o login
+pub async fn find_linked_user(
+    pool: &PgPool,
+    claims: &IdTokenClaims,
+) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>(
+        r#"
+        WITH identity AS (
+            UPDATE user_identities
+            SET last_login_at = NOW(), email = COALESCE($3, email)
+            WHERE issuer = $1 AND subject = $2
+            RETURNING user_id
+        )
+        SELECT u.* FROM users u JOIN identity i ON i.user_id = u.id
+        "#,
+    )
+    .bind(&claims.iss)
+    .bind(&claims.sub)
+    .bind(&claims.email)
+    .fetch_optional(pool)
+    .await
+}
+
+// Conta local com o email informado pelo provedor. A comparação é exata, como no
+// login por senha: a unicidade do email diferencia maiúsculas.
+pub async fn find_user_by_email(pool: &PgPool, email: &str) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
+        .bind(email)
+        .fetch_optional(pool)
+        .await
+}
+
+// Vincula a identidade ao usuário, dentro da transação do chamador. O provedor
+// confirmou o email, que passa a contar como verificado.
+pub async fn link_identity(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    claims: &IdTokenClaims,
+) -> Result<User, sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_identities (user_id, issuer, subject, email)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (issuer, subject) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(&claims.iss)
+    .bind(&claims.sub)
+    .bind(&claims.email)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW())
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::jwt_key::tests::{RSA_PRIVATE_PEM, RSA_PUBLIC_PEM};
+    use crate::models::{JwtKey, JwtKeyRing};
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use jsonwebtoken::{EncodingKey, Header};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    const CODE: &str = "codigo-de-autorizacao";
+
+    // Provedor OIDC local: discovery, JWKS e token endpoint que emite um ID token
+    // assinado com a chave RSA de teste quando código e verificador PKCE conferem
+    struct MockProvider {
+        issuer: String,
+        key: JwtKey,
+        // Claims do próximo ID token emitido
+        claims: Mutex<serde_json::Value>,
+        // Desafio PKCE enviado na autorização
+        code_challenge: Mutex<String>,
+    }
+
+    async fn discovery(provider: web::Data<MockProvider>) -> HttpResponse {
+        HttpResponse::Ok().json(serde_json::json!({
+            "issuer": provider.issuer,
+            "authorization_endpoint": format!("{}/authorize", provider.issuer),
+            "token_endpoint": format!("{}/token", provider.issuer),
+            "jwks_uri": format!("{}/jwks", provider.issuer),
+            "token_endpoint_auth_methods_supported": ["client_secret_basic"],
+        }))
+    }
+
+    async fn jwks(provider: web::Data<MockProvider>) -> HttpResponse {
+        HttpResponse::Ok().json(JwtKeyRing::new(provider.key.clone()).jwks())
+    }
+
+    async fn token(
+        req: actix_web::HttpRequest,
+        provider: web::Data<MockProvider>,
+        form: web::Form<HashMap<String, String>>,
+    ) -> HttpResponse {
+        let authorized = req
+            .headers()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            == Some(&format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode("cliente-api:s3gr%26do")
+            ));
+        if !authorized {
+            return HttpResponse::Unauthorized()
+                .json(serde_json::json!({ "error": "invalid_client" }));
+        }
+
+        let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+        if form.get("code").map(String::as_str) != Some(CODE)
+            || code_challenge(&verifier) != *provider.code_challenge.lock().unwrap()
+        {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "invalid_grant",
+                "error_description": "código inválido"
+            }));
+        }
+
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = provider.key.kid.clone();
+        let id_token = jsonwebtoken::encode(
+            &header,
+            &*provider.claims.lock().unwrap(),
+            provider.key.encoding_key.as_ref().unwrap(),
+        )
+        .unwrap();
+
+        HttpResponse::Ok().json(serde_json::json!({
+            "access_token": "opaco",
+            "token_type": "Bearer",
+            "id_token": id_token,
+        }))
+    }
+
+    async fn start_mock_provider() -> (web::Data<MockProvider>, OidcClient) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+
+        let provider = web::Data::new(MockProvider {
+            issuer: issuer.clone(),
+            key: JwtKey::from_pem(
+                Algorithm::RS256,
+                RSA_PRIVATE_PEM.as_bytes(),
+                RSA_PUBLIC_PEM.as_bytes(),
+                Some("chave-idp".to_string()),
+            )
+            .unwrap(),
+            claims: Mutex::new(serde_json::Value::Null),
+            code_challenge: Mutex::new(String::new()),
+        });
+
+        let data = provider.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(data.clone())
+                .route(
+                    "/.well-known/openid-configuration",
+                    web::get().to(discovery),
+                )
+                .route("/jwks", web::get().to(jwks))
+                .route("/token", web::post().to(token))
+        })
+        .listen(listener)
+        .unwrap()
+        .workers(1)
+        .run();
+        actix_web::rt::spawn(server);
+
+        let client = OidcClient::new(OidcConfig {
+            issuer,
+            client_id: "cliente-api".to_string(),
+            client_secret: Some("s3gr&do".to_string()),
+            redirect_uri: "https://app.empresa.com/sso/callback".to_string(),
+            scopes: "openid email profile".to_string(),
+            allowed_domains: vec![],
+            auto_provision: true,
+            login_ttl_seconds: 600,
+        })
+        .unwrap();
+
+        (provider, client)
+    }
+
+    fn pending_login() -> PendingLogin {
+        PendingLogin {
+            state: generate_token(),
+            nonce: generate_token(),
+            code_verifier: generate_token(),
+            expires_at: Utc::now() + Duration::minutes(10),
+        }
+    }
+
+    fn id_token_claims(issuer: &str, nonce: &str) -> serde_json::Value {
+        serde_json::json!({
+            "iss": issuer,
+            "sub": "func-42",
+            "aud": "cliente-api",
+            "exp": (Utc::now() + Duration::minutes(5)).timestamp(),
+            "iat": Utc::now().timestamp(),
+            "nonce": nonce,
+            "email": "ana@empresa.com",
+            "email_verified": true,
+            "name": "Ana Souza",
+        })
+    }
+
+    // Simula o navegador: abre a URL de autorização e volta com o código
+    async fn authorize(provider: &MockProvider, client: &OidcClient, login: &PendingLogin) {
+        let url = Url::parse(&client.authorization_url(login).await.unwrap()).unwrap();
+        let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.path(), "/authorize");
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["client_id"], "cliente-api");
+        assert_eq!(params["state"], login.state);
+        assert_eq!(params["nonce"], login.nonce);
+        assert_eq!(params["code_challenge_method"], "S256");
+
+        *provider.code_challenge.lock().unwrap() = params["code_challenge"].clone();
+    }
+
+    #[test]
+    fn test_code_challenge_s256() {
+        // BASE64URL sem padding do SHA-256 do verificador
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K1uhbF2J8dR2X5ARxI6hoR1mWk"),
+            "zXV0fA6b-N3ACQUwQ5sP1AmXxMrR3kqdhBOyI3xtwcs"
+        );
+        assert_eq!(code_challenge(&generate_token()).len(), 43);
+    }
+
+    #[actix_web::test]
+    async fn test_authorization_code_flow_with_mock_provider() {
+        let (provider, client) = start_mock_provider().await;
+        let login = pending_login();
+        authorize(&provider, &client, &login).await;
+        *provider.claims.lock().unwrap() = id_token_claims(&provider.issuer, &login.nonce);
+
+        let id_token = client
+            .exchange_code(CODE, &login.code_verifier)
+            .await
+            .unwrap();
+        let claims = client
+            .validate_id_token(&id_token, &login.nonce)
+            .await
+            .unwrap();
+
+        assert_eq!(claims.sub, "func-42");
+        assert_eq!(claims.email.as_deref(), Some("ana@empresa.com"));
+        assert_eq!(claims.email_verified, Some(true));
+
+        // Verificador PKCE de outro login é recusado pelo provedor
+        assert!(matches!(
+            client.exchange_code(CODE, &generate_token()).await,
+            Err(OidcError::CodeRejected(_))
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_id_token_validation_failures() {
+        let (provider, client) = start_mock_provider().await;
+        let login = pending_login();
+        authorize(&provider, &client, &login).await;
+
+        let cases = [
+            ("nonce", serde_json::json!("outro-nonce")),
+            ("aud", serde_json::json!("outro-cliente")),
+            ("iss", serde_json::json!("https://outro-idp.com")),
+            (
+                "exp",
+                serde_json::json!((Utc::now() - Duration::minutes(5)).timestamp()),
+            ),
+            ("azp", serde_json::json!("outro-cliente")),
+        ];
+
+        for (claim, value) in cases {
+            let mut claims = id_token_claims(&provider.issuer, &login.nonce);
+            claims[claim] = value;
+            *provider.claims.lock().unwrap() = claims;
+
+            let id_token = client
+                .exchange_code(CODE, &login.code_verifier)
+                .await
+                .unwrap();
+            assert!(
+                matches!(
+                    client.validate_id_token(&id_token, &login.nonce).await,
+                    Err(OidcError::InvalidIdToken(_))
+                ),
+                "claim {} alterada deveria invalidar o ID token",
+                claim
+            );
+        }
+
+        // Token assinado com HMAC usando o segredo do cliente
+        let forged = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &id_token_claims(&provider.issuer, &login.nonce),
+            &EncodingKey::from_secret(b"s3gr&do"),
+        )
+        .unwrap();
+        assert!(matches!(
+            client.validate_id_token(&forged, &login.nonce).await,
+            Err(OidcError::InvalidIdToken(_))
+        ));
+    }
+}
@@ -0,0 +1,325 @@
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{Organization, OrganizationMember, OrganizationRole, UserOrganization};
+
+const MEMBER_SELECT: &str = r#"
+    SELECT u.id AS user_id, u.nome, u.email, m.role, m.created_at AS joined_at
+    FROM organization_members m
+    JOIN users u ON u.id = m.user_id
+"#;
+
+#[derive(Debug)]
+pub enum OrganizationError {
+    Database(sqlx::Error),
+    NotFound,
+    MemberNotFound,
+    AlreadyMember,
+    // A alteração deixaria a organização sem nenhum owner
+    LastOwner,
+}
+
+impl From<sqlx::Error> for OrganizationError {
+    fn from(error: sqlx::Error) -> Self {
+        OrganizationError::Database(error)
+    }
+}
+
+// Cria a organização com o usuário como owner, dentro da transação do chamador
+pub async fn create_with_owner(
+    conn: &mut PgConnection,
+    owner_id: Uuid,
+    name: &str,
+) -> Result<Organization, sqlx::Error> {
+    let organization = sqlx::query_as::<_, Organization>(
+        "INSERT INTO organizations (name) VALUES ($1) RETURNING *",
+    )
+    .bind(name)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO organization_members (organization_id, user_id, role) VALUES ($1, $2, 'owner')",
+    )
+    .bind(organization.id)
+    .bind(owner_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(organization)
+}
+
+pub async fn create(
+    pool: &PgPool,
+    owner_id: Uuid,
+    name: &str,
+) -> Result<Organization, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let organization = create_with_owner(&mut tx, owner_id, name).await?;
+    tx.commit().await?;
+    Ok(organization)
+}
+
+pub async fn rename(
+    pool: &PgPool,
+    organization_id: Uuid,
+    name: &str,
+) -> Result<Option<Organization>, sqlx::Error> {
+    sqlx::query_as::<_, Organization>(
+        "UPDATE organizations SET name = $1, updated_at = NOW() WHERE id = $2 RETURNING *",
+    )
+    .bind(name)
+    .bind(organization_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find(
+    pool: &PgPool,
+    organization_id: Uuid,
+) -> Result<Option<Organization>, sqlx::Error> {
+    sqlx::query_as::<_, Organization>("SELECT * FROM organizations WHERE id = $1")
+        .bind(organization_id)
+        .fetch_optional(pool)
+        .await
+}
+
+// Remove a organização e todos os vínculos. Retorna `false` se ela não existe.
+pub async fn delete(pool: &PgPool, organization_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM organizations WHERE id = $1")
+        .bind(organization_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Organização com o papel do usuário nela; `None` se ele não participa
+pub async fn find_for_user(
+    pool: &PgPool,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<UserOrganization>, sqlx::Error> {
+    sqlx::query_as::<_, UserOrganization>(
+        r#"
+        SELECT o.id, o.name, m.role, o.created_at, o.updated_at
+        FROM organization_members m
+        JOIN organizations o ON o.id = m.organization_id
+        WHERE m.organization_id = $1 AND m.user_id = $2
+        "#,
+    )
+    .bind(organization_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Organizações do usuário, da mais antiga para a mais recente
+pub async fn list_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<UserOrganization>, sqlx::Error> {
+    sqlx::query_as::<_, UserOrganization>(
+        r#"
+        SELECT o.id, o.name, m.role, o.created_at, o.updated_at
+        FROM organization_members m
+        JOIN organizations o ON o.id = m.organization_id
+        WHERE m.user_id = $1
+        ORDER BY m.created_at, o.name
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+// Papel do usuário em cada organização (ver `PermissionStore`)
+pub async fn memberships_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<HashMap<Uuid, OrganizationRole>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, OrganizationRole)>(
+        "SELECT organization_id, role FROM organization_members WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+pub async fn role_of(
+    pool: &PgPool,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<OrganizationRole>, sqlx::Error> {
+    sqlx::query_scalar::<_, OrganizationRole>(
+        "SELECT role FROM organization_members WHERE organization_id = $1 AND user_id = $2",
+    )
+    .bind(organization_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Organização ativa de um novo login: a mais antiga da qual o usuário participa
+pub async fn default_membership(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Option<(Uuid, OrganizationRole)>, sqlx::Error> {
+    sqlx::query_as::<_, (Uuid, OrganizationRole)>(
+        r#"
+        SELECT organization_id, role
+        FROM organization_members
+        WHERE user_id = $1
+        ORDER BY created_at, organization_id
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Membros de uma organização, com busca opcional por nome ou email
+pub async fn list_members(
+    pool: &PgPool,
+    organization_id: Uuid,
+    search: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<OrganizationMember>, i64), sqlx::Error> {
+    let search = search.map(|search| format!("%{}%", search));
+
+    let total = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*)
+        FROM organization_members m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.organization_id = $1
+          AND ($2::TEXT IS NULL OR u.nome ILIKE $2 OR u.email ILIKE $2)
+        "#,
+    )
+    .bind(organization_id)
+    .bind(&search)
+    .fetch_one(pool)
+    .await?;
+
+    let members = sqlx::query_as::<_, OrganizationMember>(&format!(
+        r#"
+        {}
+        WHERE m.organization_id = $1
+          AND ($2::TEXT IS NULL OR u.nome ILIKE $2 OR u.email ILIKE $2)
+        ORDER BY m.created_at, u.nome
+        LIMIT $3 OFFSET $4
+        "#,
+        MEMBER_SELECT
+    ))
+    .bind(organization_id)
+    .bind(&search)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((members, total))
+}
+
+pub async fn find_member(
+    pool: &PgPool,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<OrganizationMember>, sqlx::Error> {
+    sqlx::query_as::<_, OrganizationMember>(&format!(
+        "{} WHERE m.organization_id = $1 AND m.user_id = $2",
+        MEMBER_SELECT
+    ))
+    .bind(organization_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Bloqueia a organização até o fim da transação, serializando alterações de
+// vínculos para que duas requisições não removam juntas os últimos owners
+async fn lock(conn: &mut PgConnection, organization_id: Uuid) -> Result<(), OrganizationError> {
+    sqlx::query_scalar::<_, Uuid>("SELECT id FROM organizations WHERE id = $1 FOR UPDATE")
+        .bind(organization_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(OrganizationError::NotFound)?;
+    Ok(())
+}
+
+async fn ensure_owner_remains(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+) -> Result<(), OrganizationError> {
+    let owner_exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM organization_members WHERE organization_id = $1 AND role = 'owner')",
+    )
+    .bind(organization_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if owner_exists {
+        Ok(())
+    } else {
+        Err(OrganizationError::LastOwner)
+    }
+}
+
+pub async fn set_member_role(
+    pool: &PgPool,
+    organization_id: Uuid,
+    user_id: Uuid,
+    role: OrganizationRole,
+) -> Result<OrganizationMember, OrganizationError> {
+    let mut tx = pool.begin().await?;
+    lock(&mut tx, organization_id).await?;
+
+    let updated = sqlx::query(
+        "UPDATE organization_members SET role = $1 WHERE organization_id = $2 AND user_id = $3",
+    )
+    .bind(role)
+    .bind(organization_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(OrganizationError::MemberNotFound);
+    }
+
+    ensure_owner_remains(&mut tx, organization_id).await?;
+    tx.commit().await?;
+
+    find_member(pool, organization_id, user_id)
+        .await?
+        .ok_or(OrganizationError::MemberNotFound)
+}
+
+pub async fn remove_member(
+    pool: &PgPool,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), OrganizationError> {
+    let mut tx = pool.begin().await?;
+    lock(&mut tx, organization_id).await?;
+
+    let removed =
+        sqlx::query("DELETE FROM organization_members WHERE organization_id = $1 AND user_id = $2")
+            .bind(organization_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+    if removed.rows_affected() == 0 {
+        return Err(OrganizationError::MemberNotFound);
+    }
+
+    ensure_owner_remains(&mut tx, organization_id).await?;
+    tx.commit().await?;
+    Ok(())
+}
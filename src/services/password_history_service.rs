@@ -0,0 +1,110 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::User;
+use crate::password::{PasswordError, PasswordHasher, PasswordPolicy, PasswordViolation};
+
+// Valida a nova senha de um usuário existente: regras da política e, se aprovadas,
+// reutilização da senha atual ou das anteriores guardadas no histórico
+pub async fn validate_new_password(
+    pool: &PgPool,
+    hasher: &PasswordHasher,
+    policy: &PasswordPolicy,
+    user: &User,
+    password: &str,
+    nome: &str,
+    email: &str,
+) -> Result<Vec<PasswordViolation>, PasswordError> {
+    let violations = policy.check(password, nome, email);
+    if !violations.is_empty() || policy.history_size == 0 {
+        return Ok(violations);
+    }
+
+    let previous: Vec<String> = sqlx::query_scalar(
+        "SELECT senha_hash FROM password_history WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2",
+    )
+    .bind(user.id)
+    .bind(policy.history_size as i64 - 1)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| PasswordError::Failed(e.into()))?;
+
+    for hash in std::iter::once(&user.senha).chain(&previous) {
+        if hasher.verify(password, hash).await?.is_valid() {
+            return Ok(vec![PasswordViolation::reused(policy.history_size)]);
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+// Guarda o hash atual do usuário, que será substituído, e descarta os que já não
+// entram na verificação. Deve ser chamado na transação que grava a nova senha,
+// antes do UPDATE: a linha do usuário fica bloqueada até o fim da transação.
+pub async fn record(
+    conn: &mut PgConnection,
+    policy: &PasswordPolicy,
+    user_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let keep = policy.history_size.saturating_sub(1) as i64;
+
+    let replaced_hash: String =
+        sqlx::query_scalar("SELECT senha FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+    if keep > 0 {
+        sqlx::query("INSERT INTO password_history (user_id, senha_hash) VALUES ($1, $2)")
+            .bind(user_id)
+            .bind(&replaced_hash)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    sqlx::query(
+        r#"
+        DELETE FROM password_history
+        WHERE user_id = $1 AND id NOT IN (
+            SELECT id FROM password_history WHERE user_id = $1
+            ORDER BY created_at DESC LIMIT $2
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(keep)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Grava a nova senha junto com o histórico, encerrando a exigência de troca e
+// invalidando os tokens emitidos com a senha anterior. Retorna a nova versão de token.
+pub async fn change_password(
+    pool: &PgPool,
+    policy: &PasswordPolicy,
+    user_id: Uuid,
+    password_hash: &str,
+) -> Result<i32, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    record(&mut tx, policy, user_id).await?;
+
+    let (token_version,): (i32,) = sqlx::query_as(
+        r#"
+        UPDATE users
+        SET senha = $1, updated_at = NOW(), password_changed_at = NOW(),
+            must_change_password = FALSE, token_version = token_version + 1
+        WHERE id = $2
+        RETURNING token_version
+        "#,
+    )
+    .bind(password_hash)
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(token_version)
+}
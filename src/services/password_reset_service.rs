@@ -0,0 +1,141 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{PasswordResetToken, User};
+use crate::password::PasswordPolicy;
+use crate::services::password_history_service;
+use crate::services::refresh_token_service::{generate_token, hash_token};
+
+// Resultado da tentativa de redefinir a senha com um token
+#[derive(Debug)]
+pub enum ResetOutcome {
+    Reset { user_id: Uuid, token_version: i32 },
+    // Token desconhecido ou já utilizado
+    Invalid,
+    Expired,
+}
+
+// Emite um token de redefinição para o usuário, invalidando os anteriores.
+// Retorna `None` quando o email não está cadastrado.
+pub async fn issue(
+    pool: &PgPool,
+    email: &str,
+    expires_in_seconds: i64,
+) -> Result<Option<(User, String)>, sqlx::Error> {
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
+        .bind(email)
+        .fetch_optional(pool)
+        .await?;
+
+    let user = match user {
+        Some(user) => user,
+        None => return Ok(None),
+    };
+
+    let token = generate_token();
+    let mut tx = pool.begin().await?;
+
+    // Apenas o token mais recente é válido
+    sqlx::query(
+        "UPDATE password_reset_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(user.id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user.id)
+    .bind(hash_token(&token))
+    .bind(Utc::now() + Duration::seconds(expires_in_seconds))
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some((user, token)))
+}
+
+// Usuário dono de um token válido, para validar a nova senha antes de `reset`
+pub async fn find_user(pool: &PgPool, token: &str) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>(
+        r#"
+        SELECT u.* FROM users u
+        JOIN password_reset_tokens t ON t.user_id = u.id
+        WHERE t.token_hash = $1 AND t.used_at IS NULL AND t.expires_at > NOW()
+        "#,
+    )
+    .bind(hash_token(token))
+    .fetch_optional(pool)
+    .await
+}
+
+// Consome o token e grava o novo hash de senha e o histórico, incrementando a
+// versão de token do usuário (todas as sessões existentes deixam de valer)
+pub async fn reset(
+    pool: &PgPool,
+    policy: &PasswordPolicy,
+    token: &str,
+    password_hash: &str,
+) -> Result<ResetOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let current = sqlx::query_as::<_, PasswordResetToken>(
+        "SELECT * FROM password_reset_tokens WHERE token_hash = $1 FOR UPDATE",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let current = match current {
+        Some(current) if current.used_at.is_none() => current,
+        _ => return Ok(ResetOutcome::Invalid),
+    };
+
+    if current.is_expired() {
+        return Ok(ResetOutcome::Expired);
+    }
+
+    sqlx::query("UPDATE password_reset_tokens SET used_at = NOW() WHERE id = $1")
+        .bind(current.id)
+        .execute(&mut *tx)
+        .await?;
+
+    password_history_service::record(&mut tx, policy, current.user_id).await?;
+
+    let (token_version,): (i32,) = sqlx::query_as(
+        r#"
+        UPDATE users
+        SET senha = $1, updated_at = NOW(), password_changed_at = NOW(),
+            must_change_password = FALSE, token_version = token_version + 1
+        WHERE id = $2
+        RETURNING token_version
+        "#,
+    )
+    .bind(password_hash)
+    .bind(current.user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // Tokens expirados ou consumidos não são mais necessários
+    sqlx::query(
+        "DELETE FROM password_reset_tokens WHERE user_id = $1 AND id <> $2 AND (used_at IS NOT NULL OR expires_at < NOW())",
+    )
+    .bind(current.user_id)
+    .bind(current.id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(ResetOutcome::Reset {
+        user_id: current.user_id,
+        token_version,
+    })
+}
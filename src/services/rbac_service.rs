@@ -0,0 +1,905 @@
+use sqlx::{PgConnection, PgPool};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+use crate::models::{
+    effective_permissions, EffectivePermission, Group, GroupMember, Permission, Role, UserAccess,
+    PERMISSION_USERS_ADMIN,
+};
+use crate::services::organization_service;
+
+// Chave do advisory lock que serializa alterações de roles, evitando que duas
+// requisições simultâneas removam juntas os últimos administradores
+const RBAC_LOCK_KEY: i64 = 0x7262_6163;
+
+// Acima deste tamanho, entradas vencidas são descartadas a cada inserção
+const CACHE_PRUNE_THRESHOLD: usize = 10_000;
+
+const ROLE_SELECT: &str = r#"
+    SELECT r.id, r.name, r.description, r.is_system, r.created_at, r.updated_at,
+           COALESCE(ARRAY_AGG(p.name::TEXT ORDER BY p.name) FILTER (WHERE p.name IS NOT NULL), '{}') AS permissions
+    FROM roles r
+    LEFT JOIN role_permissions rp ON rp.role_id = r.id
+    LEFT JOIN permissions p ON p.id = rp.permission_id
+"#;
+
+const GROUP_SELECT: &str = r#"
+    SELECT g.id, g.name, g.description, g.created_at, g.updated_at,
+           COALESCE((
+               SELECT ARRAY_AGG(r.name::TEXT ORDER BY r.name)
+               FROM group_roles gr JOIN roles r ON r.id = gr.role_id
+               WHERE gr.group_id = g.id
+           ), '{}') AS roles,
+           COALESCE((
+               SELECT ARRAY_AGG(p.name::TEXT ORDER BY p.name)
+               FROM group_permissions gp JOIN permissions p ON p.id = gp.permission_id
+               WHERE gp.group_id = g.id
+           ), '{}') AS permissions,
+           (SELECT COUNT(*) FROM group_members gm WHERE gm.group_id = g.id) AS member_count
+    FROM groups g
+"#;
+
+#[derive(Debug)]
+pub enum RbacError {
+    Database(sqlx::Error),
+    NotFound,
+    AlreadyExists,
+    // Roles e permissões padrão não podem ser removidas nem renomeadas
+    SystemRole,
+    SystemPermission,
+    UnknownPermissions(Vec<String>),
+    UnknownRoles(Vec<String>),
+    UnknownUsers(Vec<Uuid>),
+    // O usuário não faz parte do grupo
+    NotMember,
+    // A alteração deixaria o sistema sem nenhum usuário com `users:admin`
+    LastAdmin,
+}
+
+impl From<sqlx::Error> for RbacError {
+    fn from(error: sqlx::Error) -> Self {
+        RbacError::Database(error)
+    }
+}
+
+// Roles diretas e grupos do usuário, a união das permissões concedidas por eles
+// e os vínculos com organizações
+pub async fn load_access(pool: &PgPool, user_id: Uuid) -> Result<UserAccess, sqlx::Error> {
+    let roles = roles_for_user(pool, user_id).await?;
+    let groups = groups_for_user(pool, user_id).await?;
+
+    let permissions = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT DISTINCT p.name
+        FROM user_permission_grants g
+        JOIN permissions p ON p.id = g.permission_id
+        WHERE g.user_id = $1
+        ORDER BY p.name
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let organizations = organization_service::memberships_for_user(pool, user_id).await?;
+
+    Ok(UserAccess {
+        roles,
+        groups,
+        permissions,
+        organizations,
+    })
+}
+
+pub async fn groups_for_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT g.name
+        FROM group_members gm
+        JOIN groups g ON g.id = gm.group_id
+        WHERE gm.user_id = $1
+        ORDER BY g.name
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+// Permissões efetivas do usuário com a origem de cada uma (roles diretas e grupos)
+pub async fn effective_permissions_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<EffectivePermission>, sqlx::Error> {
+    let grants = sqlx::query_as::<_, (String, Option<String>, Option<String>)>(
+        r#"
+        SELECT p.name, r.name, gr.name
+        FROM user_permission_grants g
+        JOIN permissions p ON p.id = g.permission_id
+        LEFT JOIN roles r ON r.id = g.role_id
+        LEFT JOIN groups gr ON gr.id = g.group_id
+        WHERE g.user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(effective_permissions(grants))
+}
+
+pub async fn roles_for_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT r.name
+        FROM user_roles ur
+        JOIN roles r ON r.id = ur.role_id
+        WHERE ur.user_id = $1
+        ORDER BY r.name
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+// Roles de vários usuários de uma vez (listagens)
+pub async fn roles_for_users(
+    pool: &PgPool,
+    user_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<String>>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        SELECT ur.user_id, r.name
+        FROM user_roles ur
+        JOIN roles r ON r.id = ur.role_id
+        WHERE ur.user_id = ANY($1)
+        ORDER BY r.name
+        "#,
+    )
+    .bind(user_ids)
+    .fetch_all(pool)
+    .await?;
+
+    let mut roles: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for (user_id, role) in rows {
+        roles.entry(user_id).or_default().push(role);
+    }
+    Ok(roles)
+}
+
+// Atribui uma role pelo nome, dentro da transação de criação do usuário
+pub async fn assign_role(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    role: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_roles (user_id, role_id)
+        SELECT $1, id FROM roles WHERE name = $2
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(role)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// Existe pelo menos um usuário com a permissão `users:admin`, direta ou por grupo
+pub async fn admin_exists(conn: &mut PgConnection) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM user_permission_grants g
+            JOIN permissions p ON p.id = g.permission_id
+            WHERE p.name = $1
+        )
+        "#,
+    )
+    .bind(PERMISSION_USERS_ADMIN)
+    .fetch_one(conn)
+    .await
+}
+
+async fn lock(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(RBAC_LOCK_KEY)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+async fn ensure_admin_remains(conn: &mut PgConnection) -> Result<(), RbacError> {
+    if admin_exists(conn).await? {
+        Ok(())
+    } else {
+        Err(RbacError::LastAdmin)
+    }
+}
+
+pub async fn list_permissions(pool: &PgPool) -> Result<Vec<Permission>, sqlx::Error> {
+    sqlx::query_as::<_, Permission>("SELECT * FROM permissions ORDER BY name")
+        .fetch_all(pool)
+        .await
+}
+
+// Nomes que não correspondem a nenhuma permissão cadastrada
+pub async fn unknown_permissions(
+    pool: &PgPool,
+    names: &[String],
+) -> Result<Vec<String>, sqlx::Error> {
+    let found =
+        sqlx::query_scalar::<_, String>("SELECT name FROM permissions WHERE name = ANY($1)")
+            .bind(names)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(names
+        .iter()
+        .filter(|name| !found.contains(name))
+        .cloned()
+        .collect())
+}
+
+pub async fn create_permission(
+    pool: &PgPool,
+    name: &str,
+    description: Option<&str>,
+) -> Result<Permission, RbacError> {
+    sqlx::query_as::<_, Permission>(
+        r#"
+        INSERT INTO permissions (name, description)
+        VALUES ($1, $2)
+        ON CONFLICT (name) DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(name)
+    .bind(description)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(RbacError::AlreadyExists)
+}
+
+// Remove a permissão de todas as roles. Permissões do sistema não podem ser removidas.
+pub async fn delete_permission(pool: &PgPool, id: Uuid) -> Result<(), RbacError> {
+    let is_system: Option<bool> =
+        sqlx::query_scalar("SELECT is_system FROM permissions WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+    match is_system {
+        None => return Err(RbacError::NotFound),
+        Some(true) => return Err(RbacError::SystemPermission),
+        Some(false) => {}
+    }
+
+    sqlx::query("DELETE FROM permissions WHERE id = $1 AND is_system = FALSE")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn list_roles(pool: &PgPool) -> Result<Vec<Role>, sqlx::Error> {
+    sqlx::query_as::<_, Role>(&format!("{} GROUP BY r.id ORDER BY r.name", ROLE_SELECT))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_role(pool: &PgPool, id: Uuid) -> Result<Option<Role>, sqlx::Error> {
+    sqlx::query_as::<_, Role>(&format!("{} WHERE r.id = $1 GROUP BY r.id", ROLE_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+// IDs das permissões pelo nome; nomes inexistentes retornam `UnknownPermissions`
+async fn permission_ids(conn: &mut PgConnection, names: &[String]) -> Result<Vec<Uuid>, RbacError> {
+    let found = sqlx::query_as::<_, (Uuid, String)>(
+        "SELECT id, name FROM permissions WHERE name = ANY($1)",
+    )
+    .bind(names)
+    .fetch_all(conn)
+    .await?;
+
+    let unknown: Vec<String> = names
+        .iter()
+        .filter(|name| !found.iter().any(|(_, found)| found == *name))
+        .cloned()
+        .collect();
+    if !unknown.is_empty() {
+        return Err(RbacError::UnknownPermissions(unknown));
+    }
+
+    Ok(found.into_iter().map(|(id, _)| id).collect())
+}
+
+// IDs e nomes das roles, em ordem alfabética; nomes inexistentes retornam `UnknownRoles`
+async fn role_ids(
+    conn: &mut PgConnection,
+    names: &[String],
+) -> Result<Vec<(Uuid, String)>, RbacError> {
+    let found = sqlx::query_as::<_, (Uuid, String)>(
+        "SELECT id, name FROM roles WHERE name = ANY($1) ORDER BY name",
+    )
+    .bind(names)
+    .fetch_all(conn)
+    .await?;
+
+    let unknown: Vec<String> = names
+        .iter()
+        .filter(|name| !found.iter().any(|(_, found)| found == *name))
+        .cloned()
+        .collect();
+    if !unknown.is_empty() {
+        return Err(RbacError::UnknownRoles(unknown));
+    }
+
+    Ok(found)
+}
+
+async fn set_role_permissions(
+    conn: &mut PgConnection,
+    role_id: Uuid,
+    permission_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM role_permissions WHERE role_id = $1")
+        .bind(role_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO role_permissions (role_id, permission_id)
+        SELECT $1, UNNEST($2::UUID[])
+        "#,
+    )
+    .bind(role_id)
+    .bind(permission_ids)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn create_role(
+    pool: &PgPool,
+    name: &str,
+    description: Option<&str>,
+    permissions: &[String],
+) -> Result<Role, RbacError> {
+    let mut tx = pool.begin().await?;
+
+    let permission_ids = permission_ids(&mut tx, permissions).await?;
+
+    let role_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO roles (name, description)
+        VALUES ($1, $2)
+        ON CONFLICT (name) DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(name)
+    .bind(description)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(RbacError::AlreadyExists)?;
+
+    set_role_permissions(&mut tx, role_id, &permission_ids).await?;
+    tx.commit().await?;
+
+    find_role(pool, role_id).await?.ok_or(RbacError::NotFound)
+}
+
+// Altera nome, descrição e/ou permissões da role. Roles do sistema não podem ser renomeadas.
+pub async fn update_role(
+    pool: &PgPool,
+    id: Uuid,
+    name: Option<&str>,
+    description: Option<&str>,
+    permissions: Option<&[String]>,
+) -> Result<Role, RbacError> {
+    let mut tx = pool.begin().await?;
+    lock(&mut tx).await?;
+
+    let current: Option<(String, bool)> =
+        sqlx::query_as("SELECT name, is_system FROM roles WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let (current_name, is_system) = current.ok_or(RbacError::NotFound)?;
+
+    if let Some(name) = name.filter(|name| *name != current_name) {
+        if is_system {
+            return Err(RbacError::SystemRole);
+        }
+
+        let taken: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM roles WHERE name = $1)")
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+        if taken {
+            return Err(RbacError::AlreadyExists);
+        }
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE roles
+        SET name = COALESCE($2, name), description = COALESCE($3, description), updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(name)
+    .bind(description)
+    .execute(&mut *tx)
+    .await?;
+
+    if let Some(permissions) = permissions {
+        let permission_ids = permission_ids(&mut tx, permissions).await?;
+        set_role_permissions(&mut tx, id, &permission_ids).await?;
+        ensure_admin_remains(&mut tx).await?;
+    }
+
+    tx.commit().await?;
+
+    find_role(pool, id).await?.ok_or(RbacError::NotFound)
+}
+
+// Remove a role e sua atribuição a todos os usuários
+pub async fn delete_role(pool: &PgPool, id: Uuid) -> Result<(), RbacError> {
+    let mut tx = pool.begin().await?;
+    lock(&mut tx).await?;
+
+    let is_system: Option<bool> = sqlx::query_scalar("SELECT is_system FROM roles WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    match is_system {
+        None => return Err(RbacError::NotFound),
+        Some(true) => return Err(RbacError::SystemRole),
+        Some(false) => {}
+    }
+
+    sqlx::query("DELETE FROM roles WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    ensure_admin_remains(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+// Substitui as roles do usuário. Retorna os nomes atribuídos.
+pub async fn set_user_roles(
+    pool: &PgPool,
+    user_id: Uuid,
+    roles: &[String],
+) -> Result<Vec<String>, RbacError> {
+    let mut tx = pool.begin().await?;
+    lock(&mut tx).await?;
+
+    let user_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !user_exists {
+        return Err(RbacError::NotFound);
+    }
+
+    let found = role_ids(&mut tx, roles).await?;
+
+    sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let role_ids: Vec<Uuid> = found.iter().map(|(id, _)| *id).collect();
+    sqlx::query(
+        r#"
+        INSERT INTO user_roles (user_id, role_id)
+        SELECT $1, UNNEST($2::UUID[])
+        "#,
+    )
+    .bind(user_id)
+    .bind(&role_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    ensure_admin_remains(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(found.into_iter().map(|(_, name)| name).collect())
+}
+
+pub async fn list_groups(pool: &PgPool) -> Result<Vec<Group>, sqlx::Error> {
+    sqlx::query_as::<_, Group>(&format!("{} ORDER BY g.name", GROUP_SELECT))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_group(pool: &PgPool, id: Uuid) -> Result<Option<Group>, sqlx::Error> {
+    sqlx::query_as::<_, Group>(&format!("{} WHERE g.id = $1", GROUP_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+// Substitui as roles e/ou as permissões concedidas pelo grupo
+async fn set_group_grants(
+    conn: &mut PgConnection,
+    group_id: Uuid,
+    roles: Option<&[String]>,
+    permissions: Option<&[String]>,
+) -> Result<(), RbacError> {
+    if let Some(roles) = roles {
+        let role_ids: Vec<Uuid> = role_ids(&mut *conn, roles)
+            .await?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+
+        sqlx::query("DELETE FROM group_roles WHERE group_id = $1")
+            .bind(group_id)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("INSERT INTO group_roles (group_id, role_id) SELECT $1, UNNEST($2::UUID[])")
+            .bind(group_id)
+            .bind(&role_ids)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    if let Some(permissions) = permissions {
+        let permission_ids = permission_ids(&mut *conn, permissions).await?;
+
+        sqlx::query("DELETE FROM group_permissions WHERE group_id = $1")
+            .bind(group_id)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(
+            "INSERT INTO group_permissions (group_id, permission_id) SELECT $1, UNNEST($2::UUID[])",
+        )
+        .bind(group_id)
+        .bind(&permission_ids)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn create_group(
+    pool: &PgPool,
+    name: &str,
+    description: Option<&str>,
+    roles: &[String],
+    permissions: &[String],
+) -> Result<Group, RbacError> {
+    let mut tx = pool.begin().await?;
+
+    let group_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO groups (name, description)
+        VALUES ($1, $2)
+        ON CONFLICT (name) DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(name)
+    .bind(description)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(RbacError::AlreadyExists)?;
+
+    set_group_grants(&mut tx, group_id, Some(roles), Some(permissions)).await?;
+    tx.commit().await?;
+
+    find_group(pool, group_id).await?.ok_or(RbacError::NotFound)
+}
+
+// Altera nome, descrição, roles e/ou permissões do grupo
+pub async fn update_group(
+    pool: &PgPool,
+    id: Uuid,
+    name: Option<&str>,
+    description: Option<&str>,
+    roles: Option<&[String]>,
+    permissions: Option<&[String]>,
+) -> Result<Group, RbacError> {
+    let mut tx = pool.begin().await?;
+    lock(&mut tx).await?;
+
+    let current_name: String =
+        sqlx::query_scalar("SELECT name FROM groups WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RbacError::NotFound)?;
+
+    if let Some(name) = name.filter(|name| *name != current_name) {
+        let taken: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM groups WHERE name = $1)")
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+        if taken {
+            return Err(RbacError::AlreadyExists);
+        }
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE groups
+        SET name = COALESCE($2, name), description = COALESCE($3, description), updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(name)
+    .bind(description)
+    .execute(&mut *tx)
+    .await?;
+
+    if roles.is_some() || permissions.is_some() {
+        set_group_grants(&mut tx, id, roles, permissions).await?;
+        ensure_admin_remains(&mut tx).await?;
+    }
+
+    tx.commit().await?;
+
+    find_group(pool, id).await?.ok_or(RbacError::NotFound)
+}
+
+// Remove o grupo; os membros perdem o que recebiam apenas por ele
+pub async fn delete_group(pool: &PgPool, id: Uuid) -> Result<(), RbacError> {
+    let mut tx = pool.begin().await?;
+    lock(&mut tx).await?;
+
+    let deleted = sqlx::query("DELETE FROM groups WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(RbacError::NotFound);
+    }
+
+    ensure_admin_remains(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn list_group_members(
+    pool: &PgPool,
+    group_id: Uuid,
+) -> Result<Vec<GroupMember>, sqlx::Error> {
+    sqlx::query_as::<_, GroupMember>(
+        r#"
+        SELECT u.id AS user_id, u.nome, u.email, gm.created_at AS added_at
+        FROM group_members gm
+        JOIN users u ON u.id = gm.user_id
+        WHERE gm.group_id = $1
+        ORDER BY u.nome
+        "#,
+    )
+    .bind(group_id)
+    .fetch_all(pool)
+    .await
+}
+
+// Adiciona vários usuários ao grupo; os que já participam são ignorados.
+// Retorna os IDs efetivamente adicionados.
+pub async fn add_group_members(
+    pool: &PgPool,
+    group_id: Uuid,
+    user_ids: &[Uuid],
+) -> Result<Vec<Uuid>, RbacError> {
+    let mut tx = pool.begin().await?;
+
+    let group_exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM groups WHERE id = $1)")
+            .bind(group_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    if !group_exists {
+        return Err(RbacError::NotFound);
+    }
+
+    let found = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE id = ANY($1)")
+        .bind(user_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+    let unknown: Vec<Uuid> = user_ids
+        .iter()
+        .filter(|id| !found.contains(id))
+        .copied()
+        .collect();
+    if !unknown.is_empty() {
+        return Err(RbacError::UnknownUsers(unknown));
+    }
+
+    let added = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO group_members (group_id, user_id)
+        SELECT $1, UNNEST($2::UUID[])
+        ON CONFLICT DO NOTHING
+        RETURNING user_id
+        "#,
+    )
+    .bind(group_id)
+    .bind(user_ids)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(added)
+}
+
+pub async fn remove_group_member(
+    pool: &PgPool,
+    group_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), RbacError> {
+    let mut tx = pool.begin().await?;
+    lock(&mut tx).await?;
+
+    let removed = sqlx::query("DELETE FROM group_members WHERE group_id = $1 AND user_id = $2")
+        .bind(group_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    if removed.rows_affected() == 0 {
+        return Err(RbacError::NotMember);
+    }
+
+    ensure_admin_remains(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    access: Arc<UserAccess>,
+    loaded_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct PermissionCache {
+    users: HashMap<Uuid, CacheEntry>,
+    // Incrementada a cada invalidação: consultas iniciadas antes dela não são guardadas
+    generation: u64,
+}
+
+impl PermissionCache {
+    fn get(&self, user_id: Uuid, ttl: Duration) -> Option<Arc<UserAccess>> {
+        self.users
+            .get(&user_id)
+            .filter(|entry| entry.loaded_at.elapsed() < ttl)
+            .map(|entry| entry.access.clone())
+    }
+
+    fn insert(&mut self, user_id: Uuid, access: Arc<UserAccess>, generation: u64, ttl: Duration) {
+        if generation != self.generation {
+            return;
+        }
+        if self.users.len() >= CACHE_PRUNE_THRESHOLD {
+            self.users
+                .retain(|_, entry| entry.loaded_at.elapsed() < ttl);
+        }
+        self.users.insert(
+            user_id,
+            CacheEntry {
+                access,
+                loaded_at: Instant::now(),
+            },
+        );
+    }
+
+    fn invalidate_user(&mut self, user_id: Uuid) {
+        self.users.remove(&user_id);
+        self.generation += 1;
+    }
+
+    fn invalidate_all(&mut self) {
+        self.users.clear();
+        self.generation += 1;
+    }
+}
+
+// Roles e permissões dos usuários com cache em memória. Alterações feitas nesta
+// instância invalidam o cache imediatamente; alterações feitas por outras
+// instâncias são vistas após no máximo `cache_ttl`.
+#[derive(Clone)]
+pub struct PermissionStore {
+    pool: PgPool,
+    cache: Arc<Mutex<PermissionCache>>,
+    cache_ttl: Duration,
+}
+
+impl PermissionStore {
+    pub fn new(pool: PgPool, cache_ttl: Duration) -> Self {
+        Self {
+            pool,
+            cache: Arc::new(Mutex::new(PermissionCache::default())),
+            cache_ttl,
+        }
+    }
+
+    pub async fn access(&self, user_id: Uuid) -> Result<Arc<UserAccess>, sqlx::Error> {
+        let generation = {
+            let cache = self.cache.lock().unwrap();
+            if let Some(access) = cache.get(user_id, self.cache_ttl) {
+                return Ok(access);
+            }
+            cache.generation
+        };
+
+        let access = Arc::new(load_access(&self.pool, user_id).await?);
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(user_id, access.clone(), generation, self.cache_ttl);
+
+        Ok(access)
+    }
+
+    // Deve ser chamado após alterar as roles ou os vínculos com organizações de um
+    // usuário, ou removê-lo
+    pub fn invalidate_user(&self, user_id: Uuid) {
+        self.cache.lock().unwrap().invalidate_user(user_id);
+    }
+
+    // Deve ser chamado após alterar ou remover roles, permissões e organizações
+    pub fn invalidate_all(&self) {
+        self.cache.lock().unwrap().invalidate_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_cache() {
+        let ttl = Duration::from_secs(60);
+        let user_id = Uuid::new_v4();
+        let access = Arc::new(UserAccess {
+            roles: vec!["USER".to_string()],
+            permissions: vec!["users:read".to_string()],
+            ..UserAccess::default()
+        });
+        let mut cache = PermissionCache::default();
+
+        cache.insert(user_id, access.clone(), cache.generation, ttl);
+        assert_eq!(cache.get(user_id, ttl).unwrap().roles, access.roles);
+        assert!(cache.get(user_id, Duration::from_secs(0)).is_none());
+
+        cache.invalidate_user(user_id);
+        assert!(cache.get(user_id, ttl).is_none());
+
+        // Consulta iniciada antes da invalidação não repõe dados antigos
+        let generation = cache.generation;
+        cache.invalidate_all();
+        cache.insert(user_id, access, generation, ttl);
+        assert!(cache.get(user_id, ttl).is_none());
+    }
+}
@@ -0,0 +1,193 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::RefreshToken;
+
+// Resultado da tentativa de rotação de um refresh token
+#[derive(Debug)]
+pub enum RotationOutcome {
+    Rotated {
+        user_id: Uuid,
+        // Organização ativa da sessão
+        organization_id: Option<Uuid>,
+        token: String,
+        expires_at: DateTime<Utc>,
+    },
+    NotFound,
+    Expired,
+    Revoked,
+    // Token já utilizado foi apresentado novamente: a família inteira é revogada
+    Reused,
+}
+
+// Gera um token opaco aleatório (256 bits, codificado em hex)
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+// Apenas o hash SHA-256 do token é persistido no banco
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Emite um novo refresh token. Sem `family_id`, uma nova família é iniciada (login).
+pub async fn issue(
+    pool: &PgPool,
+    user_id: Uuid,
+    organization_id: Option<Uuid>,
+    family_id: Option<Uuid>,
+    expires_in_seconds: i64,
+) -> Result<(String, RefreshToken), sqlx::Error> {
+    let token = generate_token();
+    let expires_at = Utc::now() + Duration::seconds(expires_in_seconds);
+
+    let record = sqlx::query_as::<_, RefreshToken>(
+        r#"
+        INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, organization_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(family_id.unwrap_or_else(Uuid::new_v4))
+    .bind(hash_token(&token))
+    .bind(expires_at)
+    .bind(organization_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok((token, record))
+}
+
+// Troca um refresh token válido por um novo da mesma família
+pub async fn rotate(
+    pool: &PgPool,
+    token: &str,
+    expires_in_seconds: i64,
+) -> Result<RotationOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let current = sqlx::query_as::<_, RefreshToken>(
+        "SELECT * FROM refresh_tokens WHERE token_hash = $1 FOR UPDATE",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let current = match current {
+        Some(current) => current,
+        None => return Ok(RotationOutcome::NotFound),
+    };
+
+    if current.used_at.is_some() {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE family_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(current.family_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        return Ok(RotationOutcome::Reused);
+    }
+
+    if current.revoked_at.is_some() {
+        return Ok(RotationOutcome::Revoked);
+    }
+
+    if current.is_expired() {
+        return Ok(RotationOutcome::Expired);
+    }
+
+    let new_token = generate_token();
+    let expires_at = Utc::now() + Duration::seconds(expires_in_seconds);
+    let new_id = Uuid::new_v4();
+
+    sqlx::query(
+        r#"
+        INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, organization_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(new_id)
+    .bind(current.user_id)
+    .bind(current.family_id)
+    .bind(hash_token(&new_token))
+    .bind(expires_at)
+    .bind(current.organization_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE refresh_tokens SET used_at = NOW(), replaced_by = $1 WHERE id = $2")
+        .bind(new_id)
+        .bind(current.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(RotationOutcome::Rotated {
+        user_id: current.user_id,
+        organization_id: current.organization_id,
+        token: new_token,
+        expires_at,
+    })
+}
+
+// Revoga a família do refresh token informado (logout da sessão)
+pub async fn revoke_family(pool: &PgPool, user_id: Uuid, token: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE refresh_tokens SET revoked_at = NOW()
+        WHERE revoked_at IS NULL
+          AND user_id = $1
+          AND family_id = (SELECT family_id FROM refresh_tokens WHERE token_hash = $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(hash_token(token))
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// Revoga todos os refresh tokens ativos de um usuário
+pub async fn revoke_all_for_user(pool: &PgPool, user_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_token_is_random() {
+        let first = generate_token();
+        let second = generate_token();
+
+        assert_eq!(first.len(), 64);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_hash_token_is_deterministic() {
+        let token = generate_token();
+
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_ne!(hash_token(&token), token);
+        assert_eq!(hash_token(&token).len(), 64);
+    }
+}
@@ -0,0 +1,230 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+use crate::models::Claims;
+use crate::services::{api_key_service, refresh_token_service};
+
+#[derive(Debug, Clone, Copy)]
+struct CacheEntry<T> {
+    value: T,
+    checked_at: Instant,
+}
+
+impl<T: Copy> CacheEntry<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            checked_at: Instant::now(),
+        }
+    }
+
+    fn fresh(&self, ttl: Duration) -> Option<T> {
+        if self.checked_at.elapsed() < ttl {
+            Some(self.value)
+        } else {
+            None
+        }
+    }
+}
+
+// Acima deste tamanho, entradas vencidas são descartadas a cada inserção
+const CACHE_PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Default)]
+struct RevocationCache {
+    // jti -> revogado?
+    tokens: HashMap<String, CacheEntry<bool>>,
+    // user_id -> versão de token vigente (None se o usuário não existe mais)
+    users: HashMap<Uuid, CacheEntry<Option<i32>>>,
+}
+
+impl RevocationCache {
+    fn insert_token(&mut self, jti: String, revoked: bool, ttl: Duration) {
+        if self.tokens.len() >= CACHE_PRUNE_THRESHOLD {
+            self.tokens.retain(|_, entry| entry.fresh(ttl).is_some());
+        }
+        self.tokens.insert(jti, CacheEntry::new(revoked));
+    }
+
+    fn insert_user(&mut self, user_id: Uuid, token_version: Option<i32>, ttl: Duration) {
+        if self.users.len() >= CACHE_PRUNE_THRESHOLD {
+            self.users.retain(|_, entry| entry.fresh(ttl).is_some());
+        }
+        self.users.insert(user_id, CacheEntry::new(token_version));
+    }
+}
+
+// Denylist de JWTs e versões de token persistidas no Postgres com cache em memória.
+// Revogações feitas nesta instância entram no cache imediatamente; revogações
+// feitas por outras instâncias são vistas após no máximo `cache_ttl`.
+#[derive(Clone)]
+pub struct RevocationStore {
+    pool: PgPool,
+    cache: Arc<Mutex<RevocationCache>>,
+    cache_ttl: Duration,
+}
+
+impl RevocationStore {
+    pub fn new(pool: PgPool, cache_ttl: Duration) -> Self {
+        Self {
+            pool,
+            cache: Arc::new(Mutex::new(RevocationCache::default())),
+            cache_ttl,
+        }
+    }
+
+    pub async fn is_revoked(&self, claims: &Claims) -> Result<bool, sqlx::Error> {
+        let user_id = claims.get_user_id().unwrap_or_default();
+
+        let (cached_token, cached_version) = {
+            let cache = self.cache.lock().unwrap();
+            (
+                cache
+                    .tokens
+                    .get(&claims.jti)
+                    .and_then(|entry| entry.fresh(self.cache_ttl)),
+                cache
+                    .users
+                    .get(&user_id)
+                    .and_then(|entry| entry.fresh(self.cache_ttl)),
+            )
+        };
+
+        let token_revoked = match cached_token {
+            Some(revoked) => revoked,
+            None => {
+                let (revoked,): (bool,) =
+                    sqlx::query_as("SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1)")
+                        .bind(&claims.jti)
+                        .fetch_one(&self.pool)
+                        .await?;
+                self.cache.lock().unwrap().insert_token(
+                    claims.jti.clone(),
+                    revoked,
+                    self.cache_ttl,
+                );
+                revoked
+            }
+        };
+
+        if token_revoked {
+            return Ok(true);
+        }
+
+        let current_version = match cached_version {
+            Some(version) => version,
+            None => {
+                let row: Option<(i32,)> =
+                    sqlx::query_as("SELECT token_version FROM users WHERE id = $1")
+                        .bind(user_id)
+                        .fetch_optional(&self.pool)
+                        .await?;
+                let version = row.map(|(version,)| version);
+                self.cache
+                    .lock()
+                    .unwrap()
+                    .insert_user(user_id, version, self.cache_ttl);
+                version
+            }
+        };
+
+        // Token de versão antiga (role/senha alterada, logout geral) ou usuário removido
+        Ok(current_version != Some(claims.token_version))
+    }
+
+    // Revoga um único token (logout da sessão atual)
+    pub async fn revoke_token(&self, claims: &Claims) -> Result<(), sqlx::Error> {
+        let user_id = claims.get_user_id().unwrap_or_default();
+        let expires_at = DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now);
+
+        sqlx::query(
+            r#"
+            INSERT INTO revoked_tokens (jti, user_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+        )
+        .bind(&claims.jti)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        // Tokens expirados não precisam mais ficar na denylist
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+            .execute(&self.pool)
+            .await?;
+
+        self.cache
+            .lock()
+            .unwrap()
+            .insert_token(claims.jti.clone(), true, self.cache_ttl);
+
+        Ok(())
+    }
+
+    // Revoga todos os tokens do usuário incrementando sua versão de token,
+    // incluindo os refresh tokens
+    pub async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), sqlx::Error> {
+        let row: Option<(i32,)> = sqlx::query_as(
+            "UPDATE users SET token_version = token_version + 1 WHERE id = $1 RETURNING token_version",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some((version,)) => self.token_version_changed(user_id, version).await,
+            None => {
+                self.forget_user(user_id);
+                Ok(())
+            }
+        }
+    }
+
+    // Deve ser chamado após incrementar `users.token_version`: atualiza o cache
+    // desta instância e revoga os refresh tokens emitidos para a versão anterior e
+    // as chaves de API do usuário
+    pub async fn token_version_changed(
+        &self,
+        user_id: Uuid,
+        token_version: i32,
+    ) -> Result<(), sqlx::Error> {
+        refresh_token_service::revoke_all_for_user(&self.pool, user_id).await?;
+        api_key_service::revoke_all_for_user(&self.pool, user_id).await?;
+
+        self.cache
+            .lock()
+            .unwrap()
+            .insert_user(user_id, Some(token_version), self.cache_ttl);
+
+        Ok(())
+    }
+
+    // Marca no cache que o usuário foi removido: seus tokens deixam de valer imediatamente
+    pub fn forget_user(&self, user_id: Uuid) {
+        self.cache
+            .lock()
+            .unwrap()
+            .insert_user(user_id, None, self.cache_ttl);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_entry_freshness() {
+        let entry = CacheEntry::new(true);
+
+        assert_eq!(entry.fresh(Duration::from_secs(60)), Some(true));
+        assert_eq!(entry.fresh(Duration::from_secs(0)), None);
+    }
+}
@@ -0,0 +1,454 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use sqlx::{PgConnection, PgPool};
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+use crate::models::{TwoFactorChallenge, User};
+use crate::services::refresh_token_service::{generate_token, hash_token};
+
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP_SECONDS: u64 = 30;
+// Intervalos aceitos antes e depois do atual, para tolerar relógios dessincronizados
+const TOTP_SKEW_STEPS: i64 = 1;
+
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_LENGTH: usize = 10;
+// Sem caracteres ambíguos (0/o, 1/l/i)
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+// Resultado da confirmação do cadastro do autenticador
+#[derive(Debug)]
+pub enum ConfirmOutcome {
+    Enabled { recovery_codes: Vec<String> },
+    NotSetUp,
+    AlreadyEnabled,
+    InvalidCode,
+}
+
+// Resultado da troca de um desafio de login pelo código do autenticador
+#[derive(Debug)]
+pub enum ChallengeOutcome {
+    Verified(User),
+    // Desafio desconhecido, já utilizado ou esgotado
+    Invalid,
+    Expired,
+    // Código incorreto; o email identifica a conta para o controle de tentativas
+    InvalidCode { email: String },
+}
+
+// Gera um novo segredo TOTP (160 bits, codificado em base32)
+pub fn generate_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+fn totp(secret: &str, issuer: &str, account_name: &str) -> Option<TOTP> {
+    let secret = Secret::Encoded(secret.to_string()).to_bytes().ok()?;
+    Some(TOTP::new_unchecked(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        0,
+        TOTP_STEP_SECONDS,
+        secret,
+        Some(issuer.to_string()),
+        account_name.to_string(),
+    ))
+}
+
+// URI `otpauth://totp/...` para cadastro no aplicativo autenticador (QR code)
+pub fn otpauth_uri(secret: &str, issuer: &str, email: &str) -> Option<String> {
+    totp(secret, issuer, email).map(|totp| totp.get_url())
+}
+
+// Retorna o intervalo TOTP em que o código é válido, considerando a tolerância
+pub fn matching_step(secret: &str, code: &str, now: DateTime<Utc>) -> Option<i64> {
+    let totp = totp(secret, "", "")?;
+    let current = now.timestamp() / TOTP_STEP_SECONDS as i64;
+
+    (current - TOTP_SKEW_STEPS..=current + TOTP_SKEW_STEPS)
+        .find(|step| *step >= 0 && totp.check(code, *step as u64 * TOTP_STEP_SECONDS))
+}
+
+// Gera códigos de recuperação no formato `xxxxx-xxxxx`
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let code: String = (0..RECOVERY_CODE_LENGTH)
+                .map(|_| {
+                    RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char
+                })
+                .collect();
+            let (first, second) = code.split_at(RECOVERY_CODE_LENGTH / 2);
+            format!("{}-{}", first, second)
+        })
+        .collect()
+}
+
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn is_totp_code(code: &str) -> bool {
+    code.len() == TOTP_DIGITS && code.chars().all(|c| c.is_ascii_digit())
+}
+
+async fn lock_user(conn: &mut PgConnection, user_id: Uuid) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await
+}
+
+// Valida um código TOTP ou de recuperação do usuário (que deve estar bloqueado
+// na transação), consumindo-o: o intervalo TOTP não pode ser reutilizado e o
+// código de recuperação é marcado como usado.
+async fn consume_code(
+    conn: &mut PgConnection,
+    user: &User,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    let secret = match &user.totp_secret {
+        Some(secret) => secret,
+        None => return Ok(false),
+    };
+    let code = normalize_code(code);
+
+    if is_totp_code(&code) {
+        let step = match matching_step(secret, &code, Utc::now()) {
+            Some(step) if user.totp_last_step.is_none_or(|last| step > last) => step,
+            _ => return Ok(false),
+        };
+
+        sqlx::query("UPDATE users SET totp_last_step = $2 WHERE id = $1")
+            .bind(user.id)
+            .bind(step)
+            .execute(conn)
+            .await?;
+
+        return Ok(true);
+    }
+
+    // Códigos de recuperação só valem com o 2FA ativo
+    if user.totp_enabled_at.is_none() {
+        return Ok(false);
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE two_factor_recovery_codes SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#,
+    )
+    .bind(user.id)
+    .bind(hash_token(&code))
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Substitui os códigos de recuperação do usuário por um novo conjunto
+async fn replace_recovery_codes(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query("DELETE FROM two_factor_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let codes = generate_recovery_codes();
+    for code in &codes {
+        sqlx::query(
+            "INSERT INTO two_factor_recovery_codes (id, user_id, code_hash) VALUES ($1, $2, $3)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(hash_token(&normalize_code(code)))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(codes)
+}
+
+// Inicia o cadastro do autenticador com um novo segredo, pendente de confirmação.
+// Retorna `None` quando o 2FA já está ativo.
+pub async fn begin_setup(pool: &PgPool, user_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+    let secret = generate_secret();
+
+    let result = sqlx::query(
+        r#"
+        UPDATE users SET totp_secret = $2, totp_last_step = NULL, updated_at = NOW()
+        WHERE id = $1 AND totp_enabled_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(&secret)
+    .execute(pool)
+    .await?;
+
+    Ok((result.rows_affected() > 0).then_some(secret))
+}
+
+// Ativa o 2FA após o primeiro código válido e gera os códigos de recuperação
+pub async fn confirm(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<ConfirmOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let user = match lock_user(&mut tx, user_id).await? {
+        Some(user) => user,
+        None => return Ok(ConfirmOutcome::NotSetUp),
+    };
+
+    if user.totp_enabled_at.is_some() {
+        return Ok(ConfirmOutcome::AlreadyEnabled);
+    }
+    if user.totp_secret.is_none() {
+        return Ok(ConfirmOutcome::NotSetUp);
+    }
+    if !is_totp_code(&normalize_code(code)) || !consume_code(&mut tx, &user, code).await? {
+        return Ok(ConfirmOutcome::InvalidCode);
+    }
+
+    sqlx::query("UPDATE users SET totp_enabled_at = NOW(), updated_at = NOW() WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let recovery_codes = replace_recovery_codes(&mut tx, user_id).await?;
+    tx.commit().await?;
+
+    Ok(ConfirmOutcome::Enabled { recovery_codes })
+}
+
+// Desativa o 2FA mediante um código válido. Retorna `false` se o código for inválido.
+pub async fn disable(pool: &PgPool, user_id: Uuid, code: &str) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let user = match lock_user(&mut tx, user_id).await? {
+        Some(user) if user.totp_enabled_at.is_some() => user,
+        _ => return Ok(false),
+    };
+
+    if !consume_code(&mut tx, &user, code).await? {
+        tx.commit().await?;
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE users
+        SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM two_factor_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM two_factor_challenges WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+// Confere (e consome) um código TOTP ou de recuperação de um usuário com 2FA ativo
+pub async fn verify_code(pool: &PgPool, user_id: Uuid, code: &str) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let user = match lock_user(&mut tx, user_id).await? {
+        Some(user) if user.totp_enabled_at.is_some() => user,
+        _ => return Ok(false),
+    };
+
+    let valid = consume_code(&mut tx, &user, code).await?;
+    tx.commit().await?;
+    Ok(valid)
+}
+
+// Gera novos códigos de recuperação (invalidando os anteriores) mediante um código TOTP
+pub async fn regenerate_recovery_codes(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<Option<Vec<String>>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let user = match lock_user(&mut tx, user_id).await? {
+        Some(user) if user.totp_enabled_at.is_some() => user,
+        _ => return Ok(None),
+    };
+
+    if !is_totp_code(&normalize_code(code)) || !consume_code(&mut tx, &user, code).await? {
+        return Ok(None);
+    }
+
+    let recovery_codes = replace_recovery_codes(&mut tx, user_id).await?;
+    tx.commit().await?;
+
+    Ok(Some(recovery_codes))
+}
+
+pub async fn recovery_codes_remaining(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM two_factor_recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+// Emite o desafio de login para uma conta com 2FA
+pub async fn issue_challenge(
+    pool: &PgPool,
+    user_id: Uuid,
+    expires_in_seconds: i64,
+) -> Result<(String, DateTime<Utc>), sqlx::Error> {
+    let token = generate_token();
+    let expires_at = Utc::now() + Duration::seconds(expires_in_seconds);
+
+    // Remove desafios vencidos ou já utilizados do usuário
+    sqlx::query(
+        r#"
+        DELETE FROM two_factor_challenges
+        WHERE user_id = $1 AND (used_at IS NOT NULL OR expires_at < NOW())
+        "#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO two_factor_challenges (id, user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(hash_token(&token))
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok((token, expires_at))
+}
+
+// Troca o desafio pelo código do autenticador ou de recuperação. Cada erro
+// consome uma tentativa; ao atingir `max_attempts`, o desafio é invalidado.
+pub async fn verify_challenge(
+    pool: &PgPool,
+    token: &str,
+    code: &str,
+    max_attempts: i32,
+) -> Result<ChallengeOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let challenge = sqlx::query_as::<_, TwoFactorChallenge>(
+        "SELECT * FROM two_factor_challenges WHERE token_hash = $1 AND used_at IS NULL FOR UPDATE",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let challenge = match challenge {
+        Some(challenge) => challenge,
+        None => return Ok(ChallengeOutcome::Invalid),
+    };
+
+    if challenge.is_expired() {
+        return Ok(ChallengeOutcome::Expired);
+    }
+
+    let user = match lock_user(&mut tx, challenge.user_id).await? {
+        Some(user) if user.totp_enabled_at.is_some() => user,
+        _ => return Ok(ChallengeOutcome::Invalid),
+    };
+
+    if consume_code(&mut tx, &user, code).await? {
+        sqlx::query("UPDATE two_factor_challenges SET used_at = NOW() WHERE id = $1")
+            .bind(challenge.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        return Ok(ChallengeOutcome::Verified(user));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE two_factor_challenges
+        SET attempts = attempts + 1,
+            used_at = CASE WHEN attempts + 1 >= $2 THEN NOW() ELSE NULL END
+        WHERE id = $1
+        "#,
+    )
+    .bind(challenge.id)
+    .bind(max_attempts)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(ChallengeOutcome::InvalidCode { email: user.email })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_step_accepts_skew_only() {
+        let secret = generate_secret();
+        let totp = totp(&secret, "API", "user@example.com").unwrap();
+        let now = Utc::now();
+        let step = now.timestamp() / TOTP_STEP_SECONDS as i64;
+
+        let previous = totp.generate((step as u64 - 1) * TOTP_STEP_SECONDS);
+        assert_eq!(matching_step(&secret, &previous, now), Some(step - 1));
+
+        let current = totp.generate(step as u64 * TOTP_STEP_SECONDS);
+        assert_eq!(matching_step(&secret, &current, now), Some(step));
+
+        let old = totp.generate((step as u64 - 3) * TOTP_STEP_SECONDS);
+        assert_eq!(matching_step(&secret, &old, now), None);
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        let uri = otpauth_uri("JBSWY3DPEHPK3PXP", "API Rust", "joao@exemplo.com").unwrap();
+
+        assert!(uri.starts_with("otpauth://totp/API%20Rust:joao%40exemplo.com?"));
+        assert!(uri.contains("secret=JBSWY3DPEHPK3PXP"));
+        assert!(uri.contains("issuer=API%20Rust"));
+    }
+
+    #[test]
+    fn test_recovery_codes() {
+        let codes = generate_recovery_codes();
+
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert!(codes
+            .iter()
+            .all(|code| code.len() == RECOVERY_CODE_LENGTH + 1));
+        assert_eq!(normalize_code(" ABCDE-fghjk "), "abcdefghjk");
+        assert!(!is_totp_code(&normalize_code(&codes[0])));
+        assert!(is_totp_code("123456"));
+    }
+}
@@ -30,6 +30,9 @@
 # =============================================================================
 
 BASE_URL="http://localhost:8080"
+# Admin criado na inicialização via BOOTSTRAP_ADMIN_EMAIL / BOOTSTRAP_ADMIN_PASSWORD_FILE
+ADMIN_EMAIL="${ADMIN_EMAIL:-admin@localhost}"
+ADMIN_PASSWORD="${ADMIN_PASSWORD:-}"
 JWT_TOKEN=""
 USER_ID=""
 ADMIN_TOKEN=""
@@ -119,7 +122,7 @@ run_diagnostics() {
     local login_test=$(curl -s -w "%{http_code}" -o /dev/null \
         -X POST "$BASE_URL/api/v1/auth/login" \
         -H "Content-Type: application/json" \
-        -d '{"email":"admin@sistema.com","senha":"admin123"}' 2>/dev/null)
+        -d "{\"email\":\"$ADMIN_EMAIL\",\"senha\":\"$ADMIN_PASSWORD\"}" 2>/dev/null)
 
     if [ "${login_test: -3}" = "200" ]; then
         print_status 0 "Admin login endpoint: OK"
@@ -173,10 +176,10 @@ fi
 # Test 2: Login with admin user
 echo ""
 print_info "Test 2: Login with admin user"
-admin_login_data='{
-    "email": "admin@sistema.com",
-    "senha": "admin123"
-}'
+admin_login_data="{
+    \"email\": \"$ADMIN_EMAIL\",
+    \"senha\": \"$ADMIN_PASSWORD\"
+}"
 
 response=$(curl -s -w "%{http_code}" -o /tmp/admin_login_response.json \
     -X POST "$BASE_URL/api/v1/auth/login" \
@@ -520,6 +523,13 @@ if [ -n "$USER_ID" ] && [ -n "$JWT_TOKEN" ]; then
     if [ "$http_code" -eq 200 ]; then
         print_status 0 "Change password with JWT passed (HTTP $http_code)"
         echo "Response: $(cat /tmp/change_password_response.json)"
+
+        # Changing the password invalidates existing tokens: log in again with the new one
+        curl -s -o /tmp/user_login_response.json \
+            -X POST "$BASE_URL/api/v1/auth/login" \
+            -H "Content-Type: application/json" \
+            -d '{"email": "joao.silva@exemplo.com", "senha": "novasenha456"}'
+        JWT_TOKEN=$(cat /tmp/user_login_response.json | python3 -c "import sys, json; print(json.load(sys.stdin)['token'])" 2>/dev/null || echo "")
     else
         print_status 1 "Change password with JWT failed (HTTP $http_code)"
         echo "Response: $(cat /tmp/change_password_response.json 2>/dev/null || echo 'No response')"
@@ -751,8 +761,8 @@ print_info "Test completed!"
 print_warning "Remember to check your database to verify users were created correctly"
 echo ""
 print_info "To run the server: make run"
-print_info "To check database: Connect to your PostgreSQL and run: SELECT id, nome, email, role FROM users;"
-print_info "Default admin login: admin@sistema.com / admin123"
+print_info "To check database: Connect to your PostgreSQL and run: SELECT id, nome, email FROM users;"
+print_info "Admin login: $ADMIN_EMAIL (ADMIN_EMAIL / ADMIN_PASSWORD)"
 echo ""
 print_info "📊 Summary of test results:"
 if [ -n "$ADMIN_TOKEN" ]; then
@@ -787,7 +797,7 @@ echo "  🧪 Manual Testing:"
 echo "    # Test admin login"
 echo "    curl -X POST http://localhost:8080/api/v1/auth/login \\"
 echo "      -H 'Content-Type: application/json' \\"
-echo "      -d '{\"email\":\"admin@sistema.com\",\"senha\":\"admin123\"}'"
+echo "      -d '{\"email\":\"$ADMIN_EMAIL\",\"senha\":\"...\"}'"
 echo ""
 echo "    # Test authenticated endpoint (replace YOUR_TOKEN)"
 echo "    curl -H 'Authorization: Bearer YOUR_TOKEN' \\"
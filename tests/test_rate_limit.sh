@@ -18,6 +18,9 @@
 # =============================================================================
 
 BASE_URL="http://localhost:8080"
+# Admin criado na inicialização via BOOTSTRAP_ADMIN_EMAIL / BOOTSTRAP_ADMIN_PASSWORD_FILE
+ADMIN_EMAIL="${ADMIN_EMAIL:-admin@localhost}"
+ADMIN_PASSWORD="${ADMIN_PASSWORD:-}"
 CONCURRENT_REQUESTS=20
 RATE_LIMIT_ENDPOINT="/health"
 
@@ -174,7 +177,7 @@ test_authenticated_rate_limiting() {
     login_response=$(curl -s -w "%{http_code}" -o /tmp/login_response.json \
         -X POST "$BASE_URL/api/v1/auth/login" \
         -H "Content-Type: application/json" \
-        -d '{"email":"admin@sistema.com","senha":"admin123"}' 2>/dev/null)
+        -d "{\"email\":\"$ADMIN_EMAIL\",\"senha\":\"$ADMIN_PASSWORD\"}" 2>/dev/null)
 
     login_code="${login_response: -3}"
 